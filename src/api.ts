@@ -0,0 +1,545 @@
+import * as path from "path";
+import {
+  FlowchartIR,
+  FlowchartNode,
+  CallGraphIR,
+  ClassDiagramIR,
+  DiffStatus,
+  ParseErrorInfo,
+  SequenceDiagramIR,
+} from "./ir/ir";
+import {
+  analyzeCode,
+  analyzeFunctionMatches,
+  analyzeTypes,
+  CLASS_DIAGRAM_LANGUAGES,
+  clearAnalysisCache,
+  describeParseError,
+  findParseErrors,
+  isPlaceholderIR,
+  isSupportedLanguage,
+  listFunctions,
+  SupportedLanguage,
+  SUPPORTED_LANGUAGES,
+} from "./logic/analyzer";
+import {
+  DiagramExportOptions,
+  DiagramFormat,
+  generateDiagram,
+  generatePng,
+} from "./logic/DiagramExporter";
+import { PngOptions } from "./logic/PngGenerator";
+import { JsonEdge, JsonGraph, JsonNode, JsonSpan, JSON_SCHEMA_VERSION } from "./logic/JsonGenerator";
+import { ClassDiagramGenerator } from "./logic/ClassDiagramGenerator";
+import {
+  createSummary,
+  FunctionMetrics,
+  MetricsSummary,
+  summarizeSource,
+  SUMMARY_SCHEMA_VERSION,
+} from "./logic/MetricsSummary";
+import { analyzeCallGraph, CallGraphOptions } from "./logic/CallGraphBuilder";
+import { CallGraphFormat, CallGraphGenerator } from "./logic/CallGraphGenerator";
+import { analyzeSequence, SEQUENCE_DIAGRAM_LANGUAGES } from "./logic/SequenceDiagramBuilder";
+import { SequenceDiagramGenerator } from "./logic/SequenceDiagramGenerator";
+import { ComplexityAnalyzer } from "./logic/utils/ComplexityAnalyzer";
+import { LabelMode, LabelOverflow, LABEL_MODES } from "./logic/utils/NodeLabels";
+import { DiagramColors, DIAGRAM_COLOR_THEMES } from "./logic/utils/DiagramColors";
+import {
+  addDataflowOverlay,
+  applyViewOptions,
+  FlowchartViewOptions,
+} from "./logic/utils/FlowchartTransforms";
+import { diffFlowcharts } from "./logic/utils/FlowchartDiff";
+import { LANGUAGE_SERVICES } from "./logic/language-services/registry";
+import {
+  AnalysisLimitError,
+  AnalysisLimits,
+  DEFAULT_MAX_LINES,
+  DEFAULT_TIMEOUT_MS,
+  setAnalysisLimits,
+} from "./logic/utils/AnalysisLimits";
+
+/**
+ * Public library API for Visor's analysis core.
+ *
+ * The extension UI is built on these same functions; other tools can call
+ * them directly (for example from a build script) without going through the
+ * webview or the clipboard. Nothing in this module depends on VS Code APIs.
+ */
+
+export type {
+  FlowchartIR,
+  FlowchartNode,
+  CallGraphIR,
+  ClassDiagramIR,
+  DiffStatus,
+  ParseErrorInfo,
+  SupportedLanguage,
+  DiagramFormat,
+  DiagramColors,
+  FlowchartViewOptions,
+  PngOptions,
+  JsonGraph,
+  JsonNode,
+  JsonEdge,
+  JsonSpan,
+  LabelMode,
+  LabelOverflow,
+  CallGraphFormat,
+  AnalysisLimits,
+  FunctionMetrics,
+  MetricsSummary,
+};
+
+export type GraphOutputOptions = DiagramExportOptions & FlowchartViewOptions;
+export {
+  SUPPORTED_LANGUAGES,
+  DIAGRAM_COLOR_THEMES,
+  JSON_SCHEMA_VERSION,
+  SUMMARY_SCHEMA_VERSION,
+  LABEL_MODES,
+  DEFAULT_MAX_LINES,
+  DEFAULT_TIMEOUT_MS,
+};
+
+export type VisorErrorKind =
+  | "unsupportedLanguage"
+  | "notInitialized"
+  | "functionNotFound"
+  | "parseError"
+  | "limitExceeded"
+  | "analysisFailed";
+
+export class VisorError extends Error {
+  /** Where parsing failed, for kind "parseError" (1-based). */
+  public readonly line?: number;
+  public readonly column?: number;
+  /** The text that failed to parse, shortened. */
+  public readonly snippet?: string;
+
+  constructor(
+    public readonly kind: VisorErrorKind,
+    message: string,
+    parseError?: ParseErrorInfo
+  ) {
+    super(message);
+    this.name = "VisorError";
+    if (parseError) {
+      this.line = parseError.line;
+      this.column = parseError.column;
+      this.snippet = parseError.snippet;
+    }
+  }
+}
+
+export interface AnalyzeOptions {
+  /** Analyze the function containing this character offset. */
+  position?: number;
+  /** Analyze the function with this name (ignored when position is set). */
+  functionName?: string;
+}
+
+/**
+ * The analyzed control-flow graph of one function, with emitters for every
+ * supported text format. Emitters never modify the wrapped IR.
+ */
+export class Graph {
+  /**
+   * @param parseErrors Regions elsewhere in the file that did not parse. The
+   * function itself parsed cleanly; these are listed for reporting only.
+   */
+  constructor(
+    public readonly ir: FlowchartIR,
+    public readonly parseErrors: ParseErrorInfo[] = []
+  ) {}
+
+  /** McCabe cyclomatic complexity of the graph (edges - nodes + 2). */
+  public cyclomaticComplexity(): number {
+    return (
+      this.ir.functionComplexity?.cyclomaticComplexity ??
+      ComplexityAnalyzer.calculateGraphComplexity(this.ir)
+    );
+  }
+
+  /**
+   * Statements no control-flow path from the entry reaches, such as code
+   * after a `return` or an unconditional loop, in graph order. Empty for a
+   * function without dead code, so it doubles as a lightweight lint.
+   */
+  public unreachableNodes(): FlowchartNode[] {
+    return this.ir.nodes.filter((node) => node.unreachable);
+  }
+
+  /**
+   * Node ids along the longest acyclic path from entry to exit, the
+   * worst-case route through the function. Loop back-edges are left out to
+   * keep the graph acyclic. Emit with `{ longestPath: true }` to draw the
+   * path's edges bold.
+   */
+  public longestPath(): string[] {
+    return ComplexityAnalyzer.findLongestPath(this.ir);
+  }
+
+  /**
+   * Returns a copy with the data-flow overlay: each node lists the variables
+   * it defines and uses, and dashed `dataflow` edges link every definition
+   * to the uses it reaches. Equivalent to emitting with `{ dataflow: true }`.
+   */
+  public withDataflow(): Graph {
+    const ir = cloneIR(this.ir);
+    addDataflowOverlay(ir);
+    return new Graph(ir, this.parseErrors);
+  }
+
+  public toMermaid(options: GraphOutputOptions = {}): string {
+    return this.to("mermaid", options);
+  }
+
+  public toPlantUML(options: GraphOutputOptions = {}): string {
+    return this.to("plantuml", options);
+  }
+
+  public toDot(options: GraphOutputOptions = {}): string {
+    return this.to("dot", options);
+  }
+
+  /** Renders a standalone SVG with Visor's built-in layout. */
+  public toSvg(options: GraphOutputOptions = {}): string {
+    return this.to("svg", options);
+  }
+
+  /**
+   * Serializes the raw graph (nodes with id, kind, label and source span;
+   * edges with from, to, label and kind) as documented in
+   * docs/json-format.md. `JSON.parse` the result to get a `JsonGraph`.
+   */
+  public toJson(options: FlowchartViewOptions = {}): string {
+    return this.to("json", options);
+  }
+
+  /**
+   * Rasterizes the built-in layout to PNG bytes (192 DPI unless `dpi` is
+   * set). Needs no browser or external service.
+   * @throws Error when the DPI is out of range or the image would be too
+   * large.
+   */
+  public toPng(options: GraphOutputOptions & PngOptions = {}): Buffer {
+    const ir = applyViewOptions(cloneIR(this.ir), options);
+    return generatePng(ir, options);
+  }
+
+  public to(format: DiagramFormat, options: GraphOutputOptions = {}): string {
+    const ir = applyViewOptions(cloneIR(this.ir), options);
+    return generateDiagram(ir, format, options);
+  }
+}
+
+let initializedFrom: string | undefined;
+
+/**
+ * Loads the tree-sitter grammars from a directory containing the
+ * `tree-sitter-*.wasm` files (the extension's `dist` folder).
+ */
+export async function initVisor(wasmDirectory: string): Promise<void> {
+  for (const service of LANGUAGE_SERVICES) {
+    await service.init(path.join(wasmDirectory, service.wasmFile));
+  }
+  initializedFrom = wasmDirectory;
+}
+
+/**
+ * Parses the source and returns the control-flow graph of the selected
+ * function (by position, by name, or the first function in the file).
+ * @throws VisorError when the language is unsupported, the parsers are not
+ * initialized, or no matching function exists.
+ */
+export async function analyzeSource(
+  code: string,
+  language: SupportedLanguage | string,
+  options: AnalyzeOptions = {}
+): Promise<Graph> {
+  assertSupportedLanguage(language);
+
+  let ir: FlowchartIR;
+  let parseErrors: ParseErrorInfo[];
+  try {
+    ir = await analyzeCode(code, language, options.functionName, options.position);
+    parseErrors = await findParseErrors(code, language);
+  } catch (error) {
+    throw toVisorError(error);
+  }
+
+  if (ir.parseErrors && ir.parseErrors.length > 0) {
+    throw parseErrorOf(ir.parseErrors, "The function has a syntax error");
+  }
+  if (isPlaceholderIR(ir)) {
+    if (parseErrors.length > 0) {
+      // The function may be what failed to parse
+      throw parseErrorOf(parseErrors, ir.nodes[0]?.label || "No function found to analyze.");
+    }
+    throw new VisorError(
+      "functionNotFound",
+      ir.nodes[0]?.label || "No function found to analyze."
+    );
+  }
+
+  return new Graph(ir, parseErrors);
+}
+
+/**
+ * Returns the control-flow graph of every function with the given name, in
+ * source order. More than one graph means the name is overloaded or defined
+ * more than once.
+ * @throws VisorError with kind "functionNotFound", listing the available
+ * names, when nothing matches.
+ */
+export async function analyzeFunction(
+  code: string,
+  language: SupportedLanguage | string,
+  name: string
+): Promise<Graph[]> {
+  assertSupportedLanguage(language);
+
+  let matches: FlowchartIR[];
+  let available: string[];
+  let parseErrors: ParseErrorInfo[];
+  try {
+    matches = await analyzeFunctionMatches(code, language, name);
+    available = matches.length > 0 ? [] : await listFunctions(code, language);
+    parseErrors = await findParseErrors(code, language);
+  } catch (error) {
+    throw toVisorError(error);
+  }
+
+  // Definitions that failed to parse are left out while any other parsed
+  const clean = matches.filter((ir) => !ir.parseErrors?.length);
+  if (clean.length === 0 && matches.length > 0) {
+    throw parseErrorOf(matches[0].parseErrors!, `Function '${name}' has a syntax error`);
+  }
+  if (matches.length === 0) {
+    const names = Array.from(new Set(available));
+    const message =
+      names.length > 0
+        ? `No function named '${name}'. Available: ${names.join(", ")}`
+        : `No function named '${name}'; the source defines no functions.`;
+    if (parseErrors.length > 0) {
+      throw parseErrorOf(parseErrors, message);
+    }
+    throw new VisorError("functionNotFound", message);
+  }
+  return clean.map((ir) => new Graph(ir, parseErrors));
+}
+
+/**
+ * Compares two versions of a function. The returned graph is `after` with
+ * every node and edge that is new marked `diff: "added"`, nodes at the same
+ * place with new text marked `"changed"`, and the nodes and edges only
+ * `before` had put back in, marked `"removed"`. Every emitter colors them
+ * green, amber and red.
+ */
+export function diffGraphs(before: Graph, after: Graph): Graph {
+  return new Graph(diffFlowcharts(before.ir, after.ir), after.parseErrors);
+}
+
+/**
+ * Builds a PlantUML class diagram of every type defined in the source.
+ * @throws VisorError when the language has no structural view.
+ */
+export async function classDiagramFromSource(
+  code: string,
+  language: SupportedLanguage | string
+): Promise<string> {
+  if (!CLASS_DIAGRAM_LANGUAGES.includes(language)) {
+    throw new VisorError(
+      "unsupportedLanguage",
+      `Class diagrams are supported for: ${CLASS_DIAGRAM_LANGUAGES.join(", ")}`
+    );
+  }
+
+  try {
+    return new ClassDiagramGenerator().generate(analyzeTypes(code, language));
+  } catch (error) {
+    throw toVisorError(error);
+  }
+}
+
+export interface CallGraphSourceFile {
+  code: string;
+  language: SupportedLanguage | string;
+  /** Path shown as the group around the file's functions. */
+  file?: string;
+}
+
+export interface CallGraphOutputOptions extends CallGraphOptions {
+  /** Default: "mermaid". */
+  format?: CallGraphFormat;
+  title?: string;
+}
+
+/**
+ * Draws which function calls which in one source file. A recursive function
+ * gets a self-loop; a call to a function the file does not define becomes a
+ * dashed external node unless `external` is false.
+ * @throws VisorError when the language is unsupported or the parsers are
+ * not initialized.
+ */
+export async function callGraphFromSource(
+  code: string,
+  language: SupportedLanguage | string,
+  options: CallGraphOutputOptions = {}
+): Promise<string> {
+  return callGraphFromFiles([{ code, language }], options);
+}
+
+/**
+ * Draws one call graph of several files, with each file's functions grouped
+ * under its `file` name. Calls resolve to a function of the calling file
+ * first, then of the file whose module path matches the call's qualifier
+ * (`http::get` in `net/http.rs`), then of the file nearest to the caller.
+ */
+export async function callGraphFromFiles(
+  files: CallGraphSourceFile[],
+  options: CallGraphOutputOptions = {}
+): Promise<string> {
+  for (const file of files) {
+    assertSupportedLanguage(file.language);
+  }
+
+  let ir: CallGraphIR;
+  try {
+    ir = await analyzeCallGraph(
+      files.map((file) => ({ code: file.code, languageId: file.language, file: file.file })),
+      options
+    );
+  } catch (error) {
+    throw toVisorError(error);
+  }
+  if (options.title) {
+    ir.title = options.title;
+  }
+  return new CallGraphGenerator().generate(ir, options.format || "mermaid");
+}
+
+/**
+ * Builds a PlantUML sequence diagram of the awaited calls of one async
+ * function (selected like `analyzeSource`): each `await` is a message from
+ * the function to a participant named after the callee, in source order,
+ * and awaits nested in another awaited call's expression nest as
+ * activations under it.
+ * @throws VisorError when the language has no `await` or no function
+ * matches.
+ */
+export async function sequenceDiagramFromSource(
+  code: string,
+  language: SupportedLanguage | string,
+  options: AnalyzeOptions = {}
+): Promise<string> {
+  if (!SEQUENCE_DIAGRAM_LANGUAGES.includes(language)) {
+    throw new VisorError(
+      "unsupportedLanguage",
+      `Sequence diagrams are supported for: ${SEQUENCE_DIAGRAM_LANGUAGES.join(", ")}`
+    );
+  }
+
+  let ir: SequenceDiagramIR | undefined;
+  try {
+    ir = await analyzeSequence(code, language, options.functionName, options.position);
+  } catch (error) {
+    throw toVisorError(error);
+  }
+  if (!ir) {
+    throw new VisorError(
+      "functionNotFound",
+      options.functionName
+        ? `No function named '${options.functionName}'.`
+        : "No function found to analyze."
+    );
+  }
+  return new SequenceDiagramGenerator().generate(ir);
+}
+
+/**
+ * Counts the nodes, edges, returns, loops and panic points of every function
+ * in the source (or only those named `functionName`) without drawing them.
+ * The schema is documented in docs/summary-format.md.
+ * @throws VisorError when the language is unsupported or the source is over
+ * the limits.
+ */
+export async function summarizeFunctions(
+  code: string,
+  language: SupportedLanguage | string,
+  options: Pick<AnalyzeOptions, "functionName"> & { file?: string } = {}
+): Promise<MetricsSummary> {
+  assertSupportedLanguage(language);
+
+  const summary = createSummary();
+  try {
+    summary.functions = await summarizeSource(code, language, {
+      file: options.file,
+      function: options.functionName,
+    });
+  } catch (error) {
+    throw toVisorError(error);
+  }
+  return summary;
+}
+
+/**
+ * Sets the input size limit and the time budget for each later analysis
+ * (defaults: `DEFAULT_MAX_LINES` lines, `DEFAULT_TIMEOUT_MS` ms). A source
+ * over the limit, or an analysis that runs out of time, fails with a
+ * VisorError of kind "limitExceeded" instead of blocking the caller.
+ */
+export function setLimits(limits: Partial<AnalysisLimits>): void {
+  setAnalysisLimits(limits);
+}
+
+/**
+ * Drops cached parse trees and flowcharts. Results never depend on the
+ * cache; use this to measure or force a cold analysis.
+ */
+export function clearCache(): void {
+  clearAnalysisCache();
+}
+
+function assertSupportedLanguage(
+  language: string
+): asserts language is SupportedLanguage {
+  if (!isSupportedLanguage(language)) {
+    throw new VisorError(
+      "unsupportedLanguage",
+      `Unsupported language: ${language}. Supported: ${SUPPORTED_LANGUAGES.join(", ")}`
+    );
+  }
+}
+
+function parseErrorOf(errors: ParseErrorInfo[], context: string): VisorError {
+  const [first] = errors;
+  const more = errors.length > 1 ? ` (and ${errors.length - 1} more)` : "";
+  return new VisorError(
+    "parseError",
+    `${context.replace(/\.$/, "")}. Syntax error at ${describeParseError(first)}${more}`,
+    first
+  );
+}
+
+function toVisorError(error: unknown): VisorError {
+  if (error instanceof AnalysisLimitError) {
+    return new VisorError("limitExceeded", error.message);
+  }
+  const message = error instanceof Error ? error.message : String(error);
+  if (/not initialized/i.test(message)) {
+    return new VisorError(
+      "notInitialized",
+      initializedFrom
+        ? message
+        : `${message} Call initVisor(wasmDirectory) before analyzing.`
+    );
+  }
+  return new VisorError("analysisFailed", message);
+}
+
+function cloneIR(ir: FlowchartIR): FlowchartIR {
+  return JSON.parse(JSON.stringify(ir));
+}
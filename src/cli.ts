@@ -0,0 +1,920 @@
+import * as fs from "fs";
+import * as path from "path";
+import {
+  analyzeSource,
+  DEFAULT_MAX_LINES,
+  DEFAULT_TIMEOUT_MS,
+  diffGraphs,
+  Graph,
+  initVisor,
+  MetricsSummary,
+  sequenceDiagramFromSource,
+  setLimits,
+  summarizeFunctions,
+  SUPPORTED_LANGUAGES,
+  VisorError,
+} from "./api";
+import { describeParseError, isSupportedLanguage } from "./logic/analyzer";
+import {
+  exportCallGraph,
+  exportDirectory,
+  BatchExportOptions,
+  getLanguageForFile,
+  SINGLE_FILE_NAME,
+  summarizeDirectory,
+} from "./logic/BatchExporter";
+import { analyzeCallGraph } from "./logic/CallGraphBuilder";
+import { CALL_GRAPH_FORMATS, CallGraphFormat, CallGraphGenerator } from "./logic/CallGraphGenerator";
+import {
+  DiagramFormat,
+  DIAGRAM_FORMATS,
+  getDiagramFormatInfo,
+  isDiagramFormat,
+} from "./logic/DiagramExporter";
+import { DEFAULT_PNG_DPI, MAX_PNG_DPI, MIN_PNG_DPI } from "./logic/PngGenerator";
+import { isLabelMode, LABEL_MODES } from "./logic/utils/NodeLabels";
+import {
+  DIAGRAM_COLOR_THEMES,
+  isDiagramColorTheme,
+  validateDiagramColors,
+} from "./logic/utils/DiagramColors";
+import { copyToSystemClipboard } from "./logic/utils/SystemClipboard";
+import { CONFIG_FILE_NAME, ConfigError, loadVisorConfig, VisorConfig } from "./logic/utils/VisorConfig";
+
+/**
+ * Command-line entry point. One source file prints its diagram to stdout
+ * (or writes it with --out, or copies it with --clipboard):
+ *
+ *   visor --file ./src/lib.rs [--function <name>] [--out <file>] [--clipboard]
+ *         [--diff <old version>]
+ *
+ * --sequence prints a PlantUML sequence diagram of the function's awaited
+ * calls instead of its flowchart.
+ *
+ * Without --file the source is read from stdin, in the language given with
+ * --lang (`git show HEAD~1:src/lib.rs | visor --lang rust`).
+ *
+ * With --watch it stays running and redraws the diagram every time the file
+ * is saved (`visor --watch ./src/lib.rs` is short for `--file ... --watch`).
+ *
+ * A whole directory is written one diagram per function:
+ *
+ *   visor --dir ./src --out ./diagrams [--recursive] [--format plantuml]
+ *         [--include "<glob>"]... [--exclude "<glob>"]... [--[no-]line-numbers] [--[no-]compact] [--dataflow]
+ *         [--max-depth <n>] [--longest-path] [--scopes] [--no-cold-paths]
+ *         [--labels full|summary|kind]
+ *         [--label-width <n> [--truncate-labels]]
+ *         [--min-complexity <n>] [--function <name>] [--theme dark] [--colors <json>]
+ *         [--png [--dpi <n>] [--transparent]] [--single-file]
+ *         [--max-lines <n>] [--timeout-ms <n>]
+ *
+ * Either mode draws a call graph of the functions instead with --call-graph
+ * (a directory's goes to <out>/call-graph.<ext>):
+ *
+ *   visor --dir ./src --out ./diagrams --call-graph [--no-external | --collapse-external]
+ *         [--format dot]
+ *
+ * --summary json prints counts per function (nodes, edges, complexity,
+ * returns, loops, panics) of --file or of every file in --dir instead of
+ * any diagram, to stdout or the --out file (see docs/summary-format.md).
+ *
+ * Defaults for --format, --theme, --labels, --include, --exclude,
+ * --line-numbers and --compact are read from a visor.toml in the working
+ * directory (or the file named by --config); flags override them.
+ *
+ * Built into `dist/cli.js` next to the tree-sitter grammars. Uses only the
+ * VS Code-independent library API.
+ */
+
+const USAGE = `Usage: visor --file <source> [--out <file>] [--clipboard] [--watch] [options]
+       visor --lang <language> [options] < <source>
+       visor --dir <directory> --out <directory> [options]
+
+Options:
+  --file <path>       Source file to analyze; its diagram is printed to stdout
+  --lang <language>   Language of the source: ${SUPPORTED_LANGUAGES.join(" | ")}. Without --file
+                      the source is read from stdin (also with --file -); with --file it
+                      overrides the language the extension implies
+  --dir <path>        Directory containing the source files to analyze
+  --out <path>        With --file, the file to write the diagram to instead of stdout.
+                      With --dir, the directory to write one diagram per function into,
+                      mirroring the layout of --dir
+  --clipboard         With --file, copy the diagram to the clipboard instead of printing
+                      it (falls back to stdout when no clipboard is available)
+  --watch [<path>]    With --file, keep running and redraw the diagram whenever the file
+                      changes; a path sets --file. Stop with Ctrl+C
+  --diff <path>       With --file, compare the function with its version in this file:
+                      added nodes and edges in green, removed in red, changed in amber
+  -r, --recursive     Also walk subdirectories
+  --include <glob>    Only process files matching the glob (repeatable or comma-separated)
+  --exclude <glob>    Skip files matching the glob (repeatable or comma-separated)
+  --line-numbers      Prefix node labels with their source line (L12: ...)
+  --compact           Merge straight-line statement runs into single nodes
+  --no-line-numbers, --no-compact
+                      Turn off line numbers or compaction set in the config file
+  --dataflow          List variables defined/used per node and draw def-use edges
+  --max-depth <n>     Fold code nested more than n levels deep into a summary node
+  --longest-path      Draw the edges of the longest path from entry to exit in bold
+  --scopes            Draw every nested block as a dashed box around its nodes
+                      (plantuml and dot)
+  --no-cold-paths     Draw edges into panics, #[cold] calls and unlikely() branches like
+                      any other instead of in thin gray
+  --labels <mode>     Node labels: ${LABEL_MODES.join(" | ")} (default: the analyzer's own)
+  --label-width <n>   Wrap label lines longer than n characters
+  --truncate-labels   With --label-width, cut long lines with an ellipsis instead of wrapping
+  --function <name>   Only write diagrams for the function(s) with this name (with --file,
+                      the function to draw; default: the first one)
+  --min-complexity <n>
+                      Only write diagrams for functions with cyclomatic complexity >= n
+  --format <format>   ${DIAGRAM_FORMATS.map((f) => f.id).join(" | ")} (default: plantuml)
+  --theme <name>      Color preset for the diagrams: ${Object.keys(DIAGRAM_COLOR_THEMES).join(" | ")}
+  --colors <json>     Custom colors as inline JSON or a path to a JSON file, e.g.
+                      '{"background":"#101010","nodeText":"white"}' (overrides --theme)
+  --png               Write PNG images (rendered with the built-in layout) instead of --format
+  --dpi <n>           PNG resolution, ${MIN_PNG_DPI}-${MAX_PNG_DPI} (default: ${DEFAULT_PNG_DPI})
+  --transparent       Leave the PNG background transparent instead of filling it
+  --single-file       Write every diagram into one PlantUML file (${SINGLE_FILE_NAME}), with a
+                      package per source file and module
+  --call-graph        Draw which function calls which instead of flowcharts (with --dir, one
+                      graph of every file, written to call-graph.<ext>); ${CALL_GRAPH_FORMATS.join(", ")} only
+  --sequence          With --file, draw the awaited calls of the (async) function as a
+                      PlantUML sequence diagram instead of a flowchart
+  --no-external       With --call-graph, leave out calls to functions defined in none of the files
+  --collapse-external With --call-graph, draw those functions as a single "external" node
+  --summary json      Print the line range, node and edge counts, complexity, returns, loops
+                      and panic points of every function as JSON instead of diagrams (with
+                      --dir, --out is the file to write it to and is optional)
+  --max-lines <n>     Skip source files longer than n lines (default: ${DEFAULT_MAX_LINES})
+  --timeout-ms <n>    Give up on a file after n milliseconds of parsing and analysis
+                      (default: ${DEFAULT_TIMEOUT_MS}); with --dir the file is skipped and reported
+  --config <path>     Read defaults from this file instead of ./${CONFIG_FILE_NAME} (keys: format,
+                      theme, labels, include, exclude, line-numbers, compact); flags win
+  --wasm-dir <path>   Directory with the tree-sitter-*.wasm grammars (default: next to this script)
+  -h, --help          Show this help
+`;
+
+interface CliOptions extends BatchExportOptions {
+  /** Exactly one of `dir` and `file` is set; `file` is STDIN to read stdin. */
+  dir?: string;
+  file?: string;
+  /** Language of `file`, instead of the one its extension implies. */
+  lang?: string;
+  out?: string;
+  clipboard: boolean;
+  /** Earlier version of `file` to draw the changes against. */
+  diff?: string;
+  /** Redraw whenever `file` changes, until interrupted. */
+  watch: boolean;
+  /** Draw a call graph instead of flowcharts. */
+  callGraph: boolean;
+  /** Draw the function's awaited calls as a sequence diagram. */
+  sequence: boolean;
+  /** Draw calls to functions defined in none of the files. */
+  external: boolean;
+  /** Draw those callees as one node. */
+  collapseExternal: boolean;
+  /** Print the metrics of every function as JSON instead of diagrams. */
+  summary: boolean;
+  maxLines?: number;
+  timeoutMs?: number;
+  wasmDir: string;
+}
+
+/** The `file` that stands for stdin. */
+const STDIN = "-";
+
+/** Flags that only make sense when walking a directory. */
+const DIRECTORY_FLAGS = ["--recursive", "--include", "--exclude", "--min-complexity", "--single-file"];
+
+export class UsageError extends Error {}
+
+/** Exported for the tests; `main` is the entry point. */
+export function parseArgs(argv: string[], config: VisorConfig = {}): CliOptions | undefined {
+  const options: Partial<CliOptions> & BatchExportOptions = {
+    format: "plantuml",
+    recursive: false,
+    include: [],
+    exclude: [],
+  };
+  let png = false;
+  let dpi: number | undefined;
+  let transparent = false;
+  let formatGiven = false;
+  let clipboard = false;
+  let callGraph = false;
+  let watch = false;
+  let sequence = false;
+  let external = true;
+  let collapseExternal = false;
+  let summary = false;
+  const directoryFlags = new Set<string>();
+
+  const takeValue = (flag: string, index: number): string => {
+    const value = argv[index + 1];
+    if (value === undefined || value.startsWith("--")) {
+      throw new UsageError(`Missing value for ${flag}`);
+    }
+    return value;
+  };
+  // A comma inside `{a,b}` separates alternatives of one glob
+  const splitGlobs = (value: string): string[] => {
+    const globs: string[] = [];
+    let depth = 0;
+    let start = 0;
+    for (let index = 0; index < value.length; index++) {
+      if (value[index] === "{") {
+        depth++;
+      } else if (value[index] === "}") {
+        depth = Math.max(0, depth - 1);
+      } else if (value[index] === "," && depth === 0) {
+        globs.push(value.slice(start, index));
+        start = index + 1;
+      }
+    }
+    globs.push(value.slice(start));
+    return globs.map((glob) => glob.trim()).filter(Boolean);
+  };
+
+  for (let i = 0; i < argv.length; i++) {
+    const arg = argv[i];
+    switch (arg) {
+      case "-h":
+      case "--help":
+        return undefined;
+      case "-r":
+      case "--recursive":
+        options.recursive = true;
+        directoryFlags.add("--recursive");
+        break;
+      case "--line-numbers":
+        options.lineNumbers = true;
+        break;
+      case "--compact":
+        options.compact = true;
+        break;
+      case "--no-line-numbers":
+        options.lineNumbers = false;
+        break;
+      case "--no-compact":
+        options.compact = false;
+        break;
+      case "--dataflow":
+        options.dataflow = true;
+        break;
+      case "--longest-path":
+        options.longestPath = true;
+        break;
+      case "--scopes":
+        options.scopes = true;
+        break;
+      case "--no-cold-paths":
+        options.coldPaths = false;
+        break;
+      case "--labels": {
+        const mode = takeValue(arg, i++);
+        if (!isLabelMode(mode)) {
+          throw new UsageError(`Unknown label mode: ${mode}`);
+        }
+        options.labelMode = mode;
+        break;
+      }
+      case "--label-width": {
+        const value = takeValue(arg, i++);
+        const width = Number(value);
+        if (!Number.isInteger(width) || width < 4) {
+          throw new UsageError(`Invalid label width: ${value}`);
+        }
+        options.labelWidth = width;
+        break;
+      }
+      case "--truncate-labels":
+        options.labelOverflow = "truncate";
+        break;
+      case "--max-depth": {
+        const value = takeValue(arg, i++);
+        const depth = Number(value);
+        if (!Number.isInteger(depth) || depth < 1) {
+          throw new UsageError(`Invalid nesting depth: ${value}`);
+        }
+        options.maxDepth = depth;
+        break;
+      }
+      case "--dir":
+        options.dir = takeValue(arg, i++);
+        break;
+      case "--file":
+        options.file = takeValue(arg, i++);
+        break;
+      case "--lang": {
+        const language = takeValue(arg, i++);
+        if (!isSupportedLanguage(language)) {
+          throw new UsageError(`Unknown language: ${language} (expected ${SUPPORTED_LANGUAGES.join(", ")})`);
+        }
+        options.lang = language;
+        break;
+      }
+      case "--out":
+        options.out = takeValue(arg, i++);
+        break;
+      case "--clipboard":
+        clipboard = true;
+        break;
+      case "--watch": {
+        watch = true;
+        const next = argv[i + 1];
+        if (next !== undefined && !next.startsWith("-")) {
+          options.file = next;
+          i++;
+        }
+        break;
+      }
+      case "--diff":
+        options.diff = takeValue(arg, i++);
+        break;
+      case "--include":
+        options.include.push(...splitGlobs(takeValue(arg, i++)));
+        directoryFlags.add(arg);
+        break;
+      case "--exclude":
+        options.exclude.push(...splitGlobs(takeValue(arg, i++)));
+        directoryFlags.add(arg);
+        break;
+      case "--format": {
+        const format = takeValue(arg, i++);
+        if (!isDiagramFormat(format)) {
+          throw new UsageError(`Unknown format: ${format}`);
+        }
+        options.format = format;
+        formatGiven = true;
+        break;
+      }
+      case "--function":
+        options.function = takeValue(arg, i++);
+        break;
+      case "--min-complexity": {
+        const value = takeValue(arg, i++);
+        const threshold = Number(value);
+        if (!Number.isInteger(threshold) || threshold < 1) {
+          throw new UsageError(`Invalid complexity threshold: ${value}`);
+        }
+        options.minComplexity = threshold;
+        directoryFlags.add(arg);
+        break;
+      }
+      case "--theme": {
+        const theme = takeValue(arg, i++);
+        if (!isDiagramColorTheme(theme)) {
+          throw new UsageError(`Unknown theme: ${theme}`);
+        }
+        options.colorTheme = theme;
+        break;
+      }
+      case "--colors":
+        options.colors = readColors(takeValue(arg, i++));
+        break;
+      case "--png":
+        png = true;
+        break;
+      case "--dpi": {
+        const value = takeValue(arg, i++);
+        dpi = Number(value);
+        if (!Number.isFinite(dpi) || dpi < MIN_PNG_DPI || dpi > MAX_PNG_DPI) {
+          throw new UsageError(`Invalid DPI: ${value} (expected ${MIN_PNG_DPI}-${MAX_PNG_DPI})`);
+        }
+        break;
+      }
+      case "--transparent":
+        transparent = true;
+        break;
+      case "--single-file":
+        options.singleFile = true;
+        directoryFlags.add(arg);
+        break;
+      case "--call-graph":
+        callGraph = true;
+        break;
+      case "--sequence":
+        sequence = true;
+        break;
+      case "--no-external":
+        external = false;
+        break;
+      case "--collapse-external":
+        collapseExternal = true;
+        break;
+      case "--summary": {
+        const format = takeValue(arg, i++);
+        if (format !== "json") {
+          throw new UsageError(`Unknown summary format: ${format} (expected json)`);
+        }
+        summary = true;
+        break;
+      }
+      case "--max-lines": {
+        const value = takeValue(arg, i++);
+        const lines = Number(value);
+        if (!Number.isInteger(lines) || lines < 1) {
+          throw new UsageError(`Invalid line limit: ${value}`);
+        }
+        options.maxLines = lines;
+        break;
+      }
+      case "--timeout-ms": {
+        const value = takeValue(arg, i++);
+        const timeout = Number(value);
+        if (!Number.isInteger(timeout) || timeout < 1) {
+          throw new UsageError(`Invalid timeout: ${value}`);
+        }
+        options.timeoutMs = timeout;
+        break;
+      }
+      case "--wasm-dir":
+        options.wasmDir = takeValue(arg, i++);
+        break;
+      case "--config":
+        // Already read by readConfig
+        takeValue(arg, i++);
+        break;
+      default:
+        throw new UsageError(`Unknown argument: ${arg}`);
+    }
+  }
+
+  // The config file fills in what the flags left unset; its globs only
+  // apply when no --include/--exclude is given
+  if (!formatGiven && config.format) {
+    const usable = !callGraph || (CALL_GRAPH_FORMATS as readonly string[]).includes(config.format);
+    options.format = usable ? config.format : options.format;
+  }
+  options.colorTheme = options.colorTheme ?? config.theme;
+  options.labelMode = options.labelMode ?? config.labels;
+  options.lineNumbers = options.lineNumbers ?? config.lineNumbers;
+  options.compact = options.compact ?? config.compact;
+  if (options.include.length === 0 && config.include) {
+    options.include = [...config.include];
+  }
+  if (options.exclude.length === 0 && config.exclude) {
+    options.exclude = [...config.exclude];
+  }
+
+  if (!options.dir && !options.file && options.lang) {
+    options.file = STDIN;
+  }
+  if (options.dir && options.file) {
+    throw new UsageError("Use either --dir or --file, not both");
+  }
+  if (!options.dir && !options.file) {
+    throw new UsageError("One of --dir or --file is required, or --lang to read the source from stdin");
+  }
+  if (options.dir && options.lang) {
+    throw new UsageError("--lang only applies with --file or stdin");
+  }
+  if (options.file === STDIN && !options.lang) {
+    throw new UsageError("Reading stdin requires --lang, since there is no file extension to go by");
+  }
+  if (options.file === STDIN && watch) {
+    throw new UsageError("--watch needs a file to watch, not stdin");
+  }
+  if (options.dir && !options.out && !summary) {
+    throw new UsageError("--dir requires --out");
+  }
+  if (options.dir && (clipboard || options.diff)) {
+    throw new UsageError("--clipboard and --diff only apply with --file");
+  }
+  if (options.dir && watch) {
+    throw new UsageError("--watch only applies with --file");
+  }
+  if (options.file) {
+    const given = DIRECTORY_FLAGS.filter((flag) => directoryFlags.has(flag));
+    if (given.length > 0) {
+      throw new UsageError(`${given.join(", ")} only apply with --dir`);
+    }
+    if (clipboard && (options.out || png)) {
+      throw new UsageError("--clipboard cannot be combined with --out or --png");
+    }
+    if (png && !options.out) {
+      throw new UsageError("--png with --file requires --out");
+    }
+  }
+  if (png) {
+    options.png = { dpi, transparent };
+  } else if (dpi !== undefined || transparent) {
+    throw new UsageError("--dpi and --transparent only apply with --png");
+  }
+  if (options.labelOverflow && options.labelWidth === undefined) {
+    throw new UsageError("--truncate-labels requires --label-width");
+  }
+  if (callGraph) {
+    if (png || options.diff || options.singleFile) {
+      throw new UsageError("--call-graph cannot be combined with --png, --diff or --single-file");
+    }
+    if (!(CALL_GRAPH_FORMATS as readonly string[]).includes(options.format)) {
+      throw new UsageError(`--call-graph writes ${CALL_GRAPH_FORMATS.join(", ")} only`);
+    }
+  } else if (!external || collapseExternal) {
+    throw new UsageError("--no-external and --collapse-external only apply with --call-graph");
+  }
+  if (!external && collapseExternal) {
+    throw new UsageError("--collapse-external cannot be combined with --no-external");
+  }
+  if (sequence) {
+    if (options.dir || callGraph || png || options.diff) {
+      throw new UsageError("--sequence only applies with --file, without --call-graph, --png or --diff");
+    }
+    if (formatGiven && options.format !== "plantuml") {
+      throw new UsageError("--sequence always writes PlantUML; drop --format");
+    }
+  }
+  if (summary) {
+    if (png || callGraph || sequence || options.diff || watch || options.singleFile) {
+      throw new UsageError(
+        "--summary cannot be combined with --png, --call-graph, --sequence, --diff, --watch or --single-file"
+      );
+    }
+    if (formatGiven) {
+      throw new UsageError("--summary always writes JSON; drop --format");
+    }
+  }
+  if (options.singleFile && (png || (formatGiven && options.format !== "plantuml"))) {
+    throw new UsageError("--single-file always writes PlantUML; drop --png/--format");
+  }
+
+  return {
+    ...options,
+    clipboard,
+    watch,
+    callGraph,
+    sequence,
+    external,
+    collapseExternal,
+    summary,
+    wasmDir: options.wasmDir || __dirname,
+  };
+}
+
+/**
+ * Reads a `--colors` value: inline JSON when it looks like an object,
+ * otherwise the path of a JSON file.
+ */
+function readColors(value: string): Record<string, string> {
+  let colors: unknown;
+  try {
+    const json = value.trim().startsWith("{") ? value : fs.readFileSync(value, "utf8");
+    colors = JSON.parse(json);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    throw new UsageError(`Cannot read --colors: ${message}`);
+  }
+  const errors = validateDiagramColors(colors);
+  if (errors.length > 0) {
+    throw new UsageError(`Invalid --colors: ${errors.join("; ")}`);
+  }
+  return colors as Record<string, string>;
+}
+
+/**
+ * Loads the file given with --config, or ./visor.toml when there is one.
+ * Read before the other flags so that they can override it.
+ */
+function readConfig(argv: string[]): VisorConfig {
+  const index = argv.indexOf("--config");
+  if (index === -1) {
+    return loadVisorConfig(process.cwd());
+  }
+  const file = argv[index + 1];
+  if (file === undefined || file.startsWith("--")) {
+    throw new UsageError("Missing value for --config");
+  }
+  return loadVisorConfig(process.cwd(), file);
+}
+
+export async function main(argv: string[]): Promise<number> {
+  let options: CliOptions | undefined;
+  try {
+    options = parseArgs(argv, readConfig(argv));
+  } catch (error) {
+    if (error instanceof UsageError) {
+      process.stderr.write(`visor: ${error.message}\n\n${USAGE}`);
+      return 2;
+    }
+    if (error instanceof ConfigError) {
+      process.stderr.write(`visor: ${error.message}\n`);
+      return 2;
+    }
+    throw error;
+  }
+  if (!options) {
+    process.stdout.write(USAGE);
+    return 0;
+  }
+
+  await initVisor(path.resolve(options.wasmDir));
+  setLimits({ maxLines: options.maxLines, timeoutMs: options.timeoutMs });
+  if (options.watch) {
+    return watchFile(options.file!, options);
+  }
+  if (options.summary) {
+    return writeSummary(options);
+  }
+  if (options.callGraph) {
+    return writeCallGraph(options);
+  }
+  if (options.sequence) {
+    return writeSequenceDiagram(options.file!, options);
+  }
+  if (options.file) {
+    return writeFileDiagram(options.file, options);
+  }
+  const out = options.out!;
+  const result = await exportDirectory(path.resolve(options.dir!), path.resolve(out), options);
+
+  if (
+    options.function !== undefined &&
+    result.functionsProcessed + result.functionsBelowThreshold + result.functionsWithParseErrors === 0
+  ) {
+    process.stderr.write(
+      result.functionNames.length > 0
+        ? `visor: No function named '${options.function}'. Available: ${result.functionNames.join(", ")}\n`
+        : `visor: No function named '${options.function}'; no functions were found.\n`
+    );
+    return 1;
+  }
+
+  const destination = options.singleFile ? path.join(out, SINGLE_FILE_NAME) : out;
+  process.stdout.write(
+    `Processed ${result.functionsProcessed} function(s) in ${result.filesProcessed} file(s); ` +
+      `wrote diagrams to ${destination}\n`
+  );
+  if (result.functionsBelowThreshold > 0) {
+    process.stdout.write(
+      `Left out ${result.functionsBelowThreshold} function(s) below complexity ${options.minComplexity}\n`
+    );
+  }
+  for (const { file, name, count } of result.ambiguous) {
+    process.stdout.write(`Note: ${file} defines '${name}' ${count} times; wrote a diagram for each\n`);
+  }
+  for (const { file, name, lines } of result.unreachable) {
+    process.stdout.write(
+      `Warning: ${file}: '${name}' has unreachable code at line(s) ${lines.join(", ")}\n`
+    );
+  }
+  for (const { file, errors } of result.parseErrors) {
+    process.stdout.write(`Syntax errors in ${file}:\n`);
+    for (const error of errors) {
+      process.stdout.write(`  ${describeParseError(error)}\n`);
+    }
+  }
+  if (result.functionsWithParseErrors > 0) {
+    process.stdout.write(
+      `Left out ${result.functionsWithParseErrors} function(s) containing syntax errors\n`
+    );
+  }
+  if (result.filesSkipped.length > 0) {
+    process.stdout.write(`Skipped ${result.filesSkipped.length} file(s):\n`);
+    for (const skipped of result.filesSkipped) {
+      process.stdout.write(`  ${skipped.file}: ${skipped.reason}\n`);
+    }
+  }
+  return 0;
+}
+
+/** A source file that cannot be read or analyzed; reported, not thrown. */
+class FileError extends Error {}
+
+/** Reads --file (or stdin for STDIN) in `language`, or the one its extension implies. */
+function readSourceFile(file: string, language?: string): { source: string; language: string } {
+  const resolved = language ?? getLanguageForFile(file);
+  if (!resolved) {
+    throw new FileError(`Unsupported file type: ${file} (pass --lang to set the language)`);
+  }
+  try {
+    return { source: fs.readFileSync(file === STDIN ? 0 : file, "utf8"), language: resolved };
+  } catch (error) {
+    throw new FileError(`Cannot read ${displayName(file)}: ${error instanceof Error ? error.message : error}`);
+  }
+}
+
+function displayName(file: string): string {
+  return file === STDIN ? "<stdin>" : file;
+}
+
+async function analyzeFile(file: string, functionName?: string, language?: string): Promise<Graph> {
+  const { source, language: resolved } = readSourceFile(file, language);
+  try {
+    return await analyzeSource(source, resolved, { functionName });
+  } catch (error) {
+    if (error instanceof VisorError) {
+      throw new FileError(`${displayName(file)}: ${error.message}`);
+    }
+    throw error;
+  }
+}
+
+/**
+ * Draws one function of one file. The diagram goes to --out, the clipboard
+ * or stdout; warnings go to stderr so piped output stays clean.
+ */
+async function writeFileDiagram(file: string, options: CliOptions): Promise<number> {
+  let graph: Graph;
+  try {
+    graph = await analyzeFile(file, options.function, options.lang);
+    if (options.diff) {
+      graph = diffGraphs(await analyzeFile(options.diff, options.function, options.lang), graph);
+    }
+  } catch (error) {
+    if (error instanceof FileError) {
+      process.stderr.write(`visor: ${error.message}\n`);
+      return 1;
+    }
+    throw error;
+  }
+
+  for (const error of graph.parseErrors) {
+    process.stderr.write(`visor: warning: syntax error at ${describeParseError(error)}\n`);
+  }
+  const unreachableLines = graph
+    .unreachableNodes()
+    .map((node) => node.line)
+    .filter((line): line is number => line !== undefined);
+  if (unreachableLines.length > 0) {
+    process.stderr.write(
+      `visor: warning: unreachable code at line(s) ${Array.from(new Set(unreachableLines)).join(", ")}\n`
+    );
+  }
+
+  if (options.png) {
+    fs.writeFileSync(options.out!, graph.toPng({ ...options, ...options.png }));
+    return 0;
+  }
+  return deliver(graph.to(options.format, options), options.format, options);
+}
+
+/**
+ * Writes a diagram of --file to --out, the clipboard or stdout. The
+ * clipboard falls back to stdout when there is none.
+ */
+async function deliver(diagram: string, format: DiagramFormat, options: CliOptions): Promise<number> {
+  if (options.out) {
+    fs.writeFileSync(options.out, diagram, "utf8");
+    return 0;
+  }
+  if (options.clipboard) {
+    if (await copyToSystemClipboard(diagram)) {
+      process.stderr.write(`${getDiagramFormatInfo(format).label} code copied to clipboard\n`);
+      return 0;
+    }
+    process.stderr.write(
+      "visor: warning: no clipboard available (install wl-copy, xclip or xsel); writing to stdout instead\n"
+    );
+  }
+  process.stdout.write(diagram.endsWith("\n") ? diagram : `${diagram}\n`);
+  return 0;
+}
+
+/** Draws the awaited calls of one function of --file as a sequence diagram. */
+async function writeSequenceDiagram(file: string, options: CliOptions): Promise<number> {
+  let diagram: string;
+  try {
+    const { source, language } = readSourceFile(file, options.lang);
+    diagram = await sequenceDiagramFromSource(source, language, { functionName: options.function });
+  } catch (error) {
+    if (error instanceof FileError || error instanceof VisorError) {
+      process.stderr.write(`visor: ${error instanceof VisorError ? `${displayName(file)}: ` : ""}${error.message}\n`);
+      return 1;
+    }
+    throw error;
+  }
+  return deliver(diagram, "plantuml", options);
+}
+
+/**
+ * Prints the metrics of every function in --file or --dir as JSON (to --out,
+ * the clipboard or stdout). Unreadable files in --dir are listed under
+ * `skipped` rather than failing the run.
+ */
+async function writeSummary(options: CliOptions): Promise<number> {
+  let summary: MetricsSummary;
+  if (options.dir) {
+    summary = await summarizeDirectory(path.resolve(options.dir), options);
+  } else {
+    const file = options.file!;
+    try {
+      const { source, language } = readSourceFile(file, options.lang);
+      summary = await summarizeFunctions(source, language, {
+        functionName: options.function,
+        file: file === STDIN ? undefined : file,
+      });
+    } catch (error) {
+      if (error instanceof FileError || error instanceof VisorError) {
+        process.stderr.write(`visor: ${error instanceof VisorError ? `${displayName(file)}: ` : ""}${error.message}\n`);
+        return 1;
+      }
+      throw error;
+    }
+  }
+  if (options.function !== undefined && summary.functions.length === 0) {
+    process.stderr.write(`visor: No function named '${options.function}'\n`);
+    return 1;
+  }
+  return deliver(JSON.stringify(summary, null, 2), "json", options);
+}
+
+/** How long the file must stay unchanged before it is redrawn. */
+const WATCH_DEBOUNCE_MS = 150;
+
+/**
+ * Draws the file once, then again after every change until interrupted.
+ * Saves in quick succession (or editors that write a file in several steps)
+ * are debounced into one redraw. The directory is watched rather than the
+ * file because many editors save by replacing the file. Each redraw reuses
+ * the cached parse tree and the flowcharts of unchanged functions.
+ */
+async function watchFile(file: string, options: CliOptions): Promise<number> {
+  const draw = async (): Promise<void> => {
+    try {
+      const status = options.callGraph
+        ? await writeCallGraph(options)
+        : options.sequence
+          ? await writeSequenceDiagram(file, options)
+          : await writeFileDiagram(file, options);
+      if (status === 0) {
+        process.stderr.write(`[${new Date().toTimeString().slice(0, 8)}] Redrew ${file}\n`);
+      }
+    } catch (error) {
+      process.stderr.write(`visor: ${error instanceof Error ? error.message : error}\n`);
+    }
+  };
+
+  await draw();
+  process.stderr.write(`Watching ${file} for changes (Ctrl+C to stop)\n`);
+
+  const name = path.basename(file);
+  let timer: NodeJS.Timeout | undefined;
+  let drawing = Promise.resolve();
+  const watcher = fs.watch(path.dirname(path.resolve(file)), (_event, changed) => {
+    if (changed && changed.toString() !== name) {
+      return;
+    }
+    clearTimeout(timer);
+    timer = setTimeout(() => {
+      // Never run two redraws at once; a change during one queues the next
+      drawing = drawing.then(draw);
+    }, WATCH_DEBOUNCE_MS);
+  });
+
+  return new Promise((resolve) => {
+    process.once("SIGINT", () => {
+      clearTimeout(timer);
+      watcher.close();
+      resolve(0);
+    });
+  });
+}
+
+/**
+ * Draws the call graph of --file (to --out, the clipboard or stdout) or of
+ * every file in --dir (to <out>/call-graph.<ext>).
+ */
+async function writeCallGraph(options: CliOptions): Promise<number> {
+  const format = options.format as CallGraphFormat;
+  if (options.dir) {
+    const result = await exportCallGraph(path.resolve(options.dir), path.resolve(options.out!), {
+      ...options,
+      format,
+    });
+    process.stdout.write(
+      `Found ${result.functionsFound} function(s) and ${result.calls} call(s) in ` +
+        `${result.filesProcessed} file(s); wrote the call graph to ${result.outputPath}\n`
+    );
+    for (const skipped of result.filesSkipped) {
+      process.stdout.write(`Skipped ${skipped.file}: ${skipped.reason}\n`);
+    }
+    return 0;
+  }
+
+  const file = options.file!;
+  let source: string;
+  let language: string;
+  try {
+    ({ source, language } = readSourceFile(file, options.lang));
+  } catch (error) {
+    if (error instanceof FileError) {
+      process.stderr.write(`visor: ${error.message}\n`);
+      return 1;
+    }
+    throw error;
+  }
+  const ir = await analyzeCallGraph([{ code: source, languageId: language }], options);
+  ir.title = file === STDIN ? "stdin" : path.basename(file);
+  return deliver(new CallGraphGenerator().generate(ir, format), format, options);
+}
+
+if (require.main === module) {
+  main(process.argv.slice(2)).then(
+    (code) => process.exit(code),
+    (error) => {
+      process.stderr.write(`visor: ${error instanceof Error ? error.message : error}\n`);
+      process.exit(1);
+    }
+  );
+}
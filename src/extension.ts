@@ -5,6 +5,22 @@ import { initLanguageServices } from "./logic/language-services";
 import { LLMManager } from "./logic/llm/LLMManager";
 import { setExtensionContext } from "./logic/llm/LLMContext";
 import { EnvironmentDetector } from "./logic/utils/EnvironmentDetector";
+import {
+  analyzeTypes,
+  clearAnalysisCache,
+  CLASS_DIAGRAM_LANGUAGES,
+  findFunctionAtPosition,
+  isSupportedLanguage,
+} from "./logic/analyzer";
+import { isDiagramFormat } from "./logic/DiagramExporter";
+import { ClassDiagramGenerator } from "./logic/ClassDiagramGenerator";
+import { analyzeCallGraph, CallGraphSource } from "./logic/CallGraphBuilder";
+import { CALL_GRAPH_FORMATS, CallGraphFormat, CallGraphGenerator } from "./logic/CallGraphGenerator";
+import { DEFAULT_BATCH_EXCLUDES, getLanguageForFile } from "./logic/BatchExporter";
+import { analyzeSequence, SEQUENCE_DIAGRAM_LANGUAGES } from "./logic/SequenceDiagramBuilder";
+import { SequenceDiagramGenerator } from "./logic/SequenceDiagramGenerator";
+import * as visorApi from "./api";
+import { setAnalysisLimits } from "./logic/utils/AnalysisLimits";
 
 export async function activate(context: vscode.ExtensionContext) {
   console.log("Visor extension is now active!");
@@ -28,6 +44,16 @@ export async function activate(context: vscode.ExtensionContext) {
     );
   }
 
+  // Registered before the views so they re-analyze under the new limits
+  applyAnalysisLimits();
+  context.subscriptions.push(
+    vscode.workspace.onDidChangeConfiguration((e) => {
+      if (e.affectsConfiguration("visor.analysis")) {
+        applyAnalysisLimits();
+      }
+    })
+  );
+
   // Register sidebar provider
   const sidebarProvider = new FlowchartViewProvider(context.extensionUri);
   context.subscriptions.push(
@@ -115,6 +141,193 @@ export async function activate(context: vscode.ExtensionContext) {
       }
     }),
 
+    vscode.commands.registerCommand("visor.generateClassDiagram", async () => {
+      const editor = vscode.window.activeTextEditor;
+      if (!editor) {
+        vscode.window.showInformationMessage(
+          "Visor: Open a source file to generate a class diagram."
+        );
+        return;
+      }
+
+      const languageId = editor.document.languageId;
+      if (!CLASS_DIAGRAM_LANGUAGES.includes(languageId)) {
+        vscode.window.showInformationMessage(
+          `Visor: Class diagrams are currently available for ${CLASS_DIAGRAM_LANGUAGES.join(", ")} files.`
+        );
+        return;
+      }
+
+      try {
+        const ir = analyzeTypes(editor.document.getText(), languageId);
+        if (ir.classes.length === 0) {
+          vscode.window.showInformationMessage(
+            "Visor: No structs, enums, traits or impl blocks found in this file."
+          );
+          return;
+        }
+        const document = await vscode.workspace.openTextDocument({
+          content: new ClassDiagramGenerator().generate(ir),
+          language: "plantuml",
+        });
+        await vscode.window.showTextDocument(document, vscode.ViewColumn.Beside);
+      } catch (error: any) {
+        vscode.window.showErrorMessage(
+          `Visor: Failed to generate class diagram: ${error.message}`
+        );
+      }
+    }),
+
+    vscode.commands.registerCommand("visor.generateCallGraph", async (folder?: vscode.Uri) => {
+      const editor = vscode.window.activeTextEditor;
+      const sources: CallGraphSource[] = [];
+      let title: string;
+      if (folder) {
+        // Invoked on a folder in the Explorer: every supported file below it
+        const files = await vscode.workspace.findFiles(
+          new vscode.RelativePattern(folder, "**/*"),
+          `{${DEFAULT_BATCH_EXCLUDES.join(",")}}`
+        );
+        files.sort((a, b) => a.fsPath.localeCompare(b.fsPath));
+        for (const file of files) {
+          const languageId = getLanguageForFile(file.fsPath);
+          if (!languageId) {
+            continue;
+          }
+          const bytes = await vscode.workspace.fs.readFile(file);
+          sources.push({
+            code: Buffer.from(bytes).toString("utf8"),
+            languageId,
+            file: vscode.workspace.asRelativePath(file, false),
+          });
+        }
+        title = vscode.workspace.asRelativePath(folder, false);
+      } else if (editor && isSupportedLanguage(editor.document.languageId)) {
+        sources.push({
+          code: editor.document.getText(),
+          languageId: editor.document.languageId,
+        });
+        title = editor.document.uri.path.split("/").pop() || "";
+      } else {
+        vscode.window.showInformationMessage(
+          "Visor: Open a supported source file, or pick a folder in the Explorer, to generate a call graph."
+        );
+        return;
+      }
+
+      try {
+        const config = vscode.workspace.getConfiguration("visor");
+        const ir = await analyzeCallGraph(sources, {
+          external: config.get<boolean>("callGraph.showExternal", true),
+          collapseExternal: config.get<boolean>("callGraph.collapseExternal", false),
+        });
+        if (!ir.functions.some((fn) => !fn.external)) {
+          vscode.window.showInformationMessage("Visor: No functions found to draw a call graph of.");
+          return;
+        }
+        ir.title = title;
+        const configured = config.get<string>("export.format", "mermaid");
+        const format: CallGraphFormat = (CALL_GRAPH_FORMATS as readonly string[]).includes(configured)
+          ? (configured as CallGraphFormat)
+          : "mermaid";
+        const document = await vscode.workspace.openTextDocument({
+          content: new CallGraphGenerator().generate(ir, format),
+          language: format,
+        });
+        await vscode.window.showTextDocument(document, vscode.ViewColumn.Beside);
+      } catch (error: any) {
+        vscode.window.showErrorMessage(
+          `Visor: Failed to generate call graph: ${error.message}`
+        );
+      }
+    }),
+
+    vscode.commands.registerCommand("visor.generateSequenceDiagram", async () => {
+      const editor = vscode.window.activeTextEditor;
+      const languageId = editor?.document.languageId;
+      if (!editor || !languageId || !SEQUENCE_DIAGRAM_LANGUAGES.includes(languageId)) {
+        vscode.window.showInformationMessage(
+          `Visor: Place the cursor in an async function of a ${SEQUENCE_DIAGRAM_LANGUAGES.join(", ")} file to generate a sequence diagram.`
+        );
+        return;
+      }
+
+      try {
+        const ir = await analyzeSequence(
+          editor.document.getText(),
+          languageId,
+          undefined,
+          editor.document.offsetAt(editor.selection.active)
+        );
+        if (!ir) {
+          vscode.window.showInformationMessage("Visor: Place the cursor inside a function.");
+          return;
+        }
+        if (ir.messages.length === 0) {
+          vscode.window.showInformationMessage(`Visor: '${ir.caller}' has no awaited calls.`);
+          return;
+        }
+        const document = await vscode.workspace.openTextDocument({
+          content: new SequenceDiagramGenerator().generate(ir),
+          language: "plantuml",
+        });
+        await vscode.window.showTextDocument(document, vscode.ViewColumn.Beside);
+      } catch (error: any) {
+        vscode.window.showErrorMessage(
+          `Visor: Failed to generate sequence diagram: ${error.message}`
+        );
+      }
+    }),
+
+    vscode.commands.registerCommand("visor.compareWithClipboard", async () => {
+      const editor = vscode.window.activeTextEditor;
+      const languageId = editor?.document.languageId;
+      if (!editor || !languageId || !isSupportedLanguage(languageId)) {
+        vscode.window.showInformationMessage(
+          "Visor: Place the cursor in a function of a supported file, with its old version in the clipboard."
+        );
+        return;
+      }
+
+      const code = editor.document.getText();
+      const position = editor.document.offsetAt(editor.selection.active);
+      const previous = await vscode.env.clipboard.readText();
+      if (!previous.trim()) {
+        vscode.window.showInformationMessage(
+          "Visor: Copy the old version of the function to the clipboard first."
+        );
+        return;
+      }
+
+      try {
+        const after = await visorApi.analyzeSource(code, languageId, { position });
+        // The pasted text may hold more than the function; prefer the same name
+        const name = await findFunctionAtPosition(code, languageId, position);
+        const before = await visorApi
+          .analyzeSource(previous, languageId, { functionName: name })
+          .catch(() => visorApi.analyzeSource(previous, languageId));
+        const configured = vscode.workspace
+          .getConfiguration("visor")
+          .get<string>("export.format", "mermaid");
+        const format = isDiagramFormat(configured) ? configured : "mermaid";
+        const document = await vscode.workspace.openTextDocument({
+          content: visorApi.diffGraphs(before, after).to(format),
+          language: format === "svg" ? "xml" : format,
+        });
+        await vscode.window.showTextDocument(document, vscode.ViewColumn.Beside);
+      } catch (error: any) {
+        vscode.window.showErrorMessage(
+          `Visor: Failed to compare the function: ${error.message}`
+        );
+      }
+    }),
+
+    vscode.commands.registerCommand("visor.clearCache", () => {
+      clearAnalysisCache();
+      sidebarProvider.refresh();
+      vscode.window.showInformationMessage("Visor: Analysis cache cleared.");
+    }),
+
     // LLM: Enable labels (provider+key flow)
     vscode.commands.registerCommand("visor.llm.enableLabels", async () => {
       await LLMManager.enableLLM(context);
@@ -130,6 +343,17 @@ export async function activate(context: vscode.ExtensionContext) {
       await LLMManager.resetCache(context);
     })
   );
+
+  // Other extensions can reuse the analysis core through `extension.exports`
+  return visorApi;
+}
+
+function applyAnalysisLimits(): void {
+  const config = vscode.workspace.getConfiguration("visor.analysis");
+  setAnalysisLimits({
+    maxLines: config.get<number>("maxLines"),
+    timeoutMs: config.get<number>("timeoutMs"),
+  });
 }
 
 export function deactivate() {
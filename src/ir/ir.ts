@@ -44,12 +44,23 @@ export interface SemanticNodeInfo {
   language?: string;
 }
 
+/** How a node or edge of a diff view relates to the earlier version. */
+export type DiffStatus = "added" | "removed" | "changed";
+
 export interface FlowchartNode {
   id: string;
   label: string;
   location?: Location;
+  line?: number; // 1-based source line the node starts on
+  depth?: number; // Nesting level: 1 directly in the function body, +1 per enclosing body
+  scope?: string; // Id of the innermost enclosing body in FlowchartIR.scopes; unset in the function body
   shape?: "rect" | "diamond" | "round" | "stadium";
   style?: string;
+  defines?: string[]; // Variables written by this node
+  uses?: string[]; // Variables read by this node
+  unreachable?: boolean; // No control-flow path from the entry reaches this node
+  diff?: DiffStatus; // Set only on diff views; "changed" means same place, new text
+  folded?: number; // Set on a summary node standing for this many nodes nested too deep
 
   // Enhanced node categorization
   nodeType?: NodeType;
@@ -59,10 +70,29 @@ export interface FlowchartNode {
   semanticInfo?: SemanticNodeInfo;
 }
 
+export enum EdgeType {
+  NORMAL = "normal",
+  BACK = "back", // Loop back-edge: end of body (or continue) to loop header
+  DATAFLOW = "dataflow", // Definition of a variable to a use it reaches; not control flow
+}
+
 export interface FlowchartEdge {
   from: string; // nodeId
   to: string; // nodeId
   label?: string;
+  edgeType?: EdgeType;
+  onLongestPath?: boolean; // Set when the longest-path view is on and the edge is part of it
+  cold?: boolean; // On a path into a panic, a cold call or an unlikely() branch; drawn de-emphasized
+  diff?: DiffStatus; // Set only on diff views; edges are "added" or "removed"
+}
+
+/** A nested body (a `{ }` block, a case) inside the function. */
+export interface FlowchartScope {
+  id: string;
+  /** The statement the body belongs to, e.g. `if x % 2 == 0` or `else`. */
+  label: string;
+  /** The enclosing scope; unset for bodies directly in the function body. */
+  parent?: string;
 }
 
 export interface LocationMapEntry {
@@ -71,6 +101,20 @@ export interface LocationMapEntry {
   nodeId: string;
 }
 
+/**
+ * A region tree-sitter could not parse. Lines and columns are 1-based;
+ * offsets index into the analyzed source.
+ */
+export interface ParseErrorInfo {
+  line: number;
+  column: number;
+  endLine: number;
+  start: number;
+  end: number;
+  /** The unparsed text (first line, shortened), or the missing token. */
+  snippet: string;
+}
+
 export interface FlowchartIR {
   nodes: FlowchartNode[];
   edges: FlowchartEdge[];
@@ -78,10 +122,126 @@ export interface FlowchartIR {
   exitNodeId?: string;
   locationMap: LocationMapEntry[];
   functionRange?: { start: number; end: number };
+  /** Source text of the function; index with offsets minus functionRange.start. */
+  functionSource?: string;
   title?: string;
   functionComplexity?: {
     cyclomaticComplexity: number;
     rating: "low" | "medium" | "high" | "very-high";
     description: string;
   };
-}
\ No newline at end of file
+  /** Syntax errors inside the analyzed function; the graph may be incomplete. */
+  parseErrors?: ParseErrorInfo[];
+  /**
+   * Modules or namespaces enclosing the function, outermost first (Rust
+   * `mod`, TypeScript `namespace`/`module`, C++ `namespace`).
+   */
+  modulePath?: string[];
+  /**
+   * Nested bodies holding at least one node, outermost first. Drawn as
+   * boxes around their nodes when the scopes view option is on.
+   */
+  scopes?: FlowchartScope[];
+}
+
+/**
+ * Structural view of a source file: type definitions and the relations
+ * between them, used for class diagrams rather than per-function flowcharts.
+ */
+export interface ClassMember {
+  name: string;
+  type?: string; // Field type or method return type
+  parameters?: string; // Method parameters without the receiver
+  visibility: "public" | "private";
+  isStatic?: boolean; // Associated function without a `self` receiver
+}
+
+export interface ClassDefinition {
+  name: string;
+  kind: "class" | "enum" | "interface";
+  typeParameters?: string;
+  fields: ClassMember[];
+  methods: ClassMember[];
+  variants: string[];
+  location?: Location;
+}
+
+export interface ClassRelation {
+  from: string; // ClassDefinition name
+  to: string; // ClassDefinition name
+  kind: "realization";
+}
+
+/** One function definition found in a source file. */
+export interface FunctionDefinition {
+  /** The name listFunctions gives it. */
+  name: string;
+  /** Character offsets of the definition, end exclusive. */
+  start: number;
+  end: number;
+}
+
+/** One call expression found in a source file. */
+export interface CallSite {
+  /** The enclosing function, as listFunctions names it. */
+  caller: string;
+  /** The callee as written: `helper`, `self.fetch_data`, `fmt.Println`, `vec!`. */
+  callee: string;
+  /** Character offsets of the call expression, end exclusive. */
+  position: number;
+  end: number;
+  /** The call is awaited (`await f()`, `f().await`). */
+  awaited?: boolean;
+}
+
+/** A function in a call graph. */
+export interface CallGraphFunction {
+  id: string;
+  name: string;
+  /** Source file, when the graph spans several files. */
+  file?: string;
+  /** Called but not defined in any analyzed file. */
+  external?: boolean;
+}
+
+export interface CallGraphCall {
+  from: string;
+  to: string;
+  /** How many call sites make this call. */
+  count: number;
+}
+
+/**
+ * Which functions call which, across one file or a directory. A recursive
+ * function has a call to itself.
+ */
+export interface CallGraphIR {
+  functions: CallGraphFunction[];
+  calls: CallGraphCall[];
+  title?: string;
+}
+
+/** One awaited call in a sequence diagram. */
+export interface SequenceMessage {
+  /** Who receives the call, such as `client` for `self.client.get()`. */
+  participant: string;
+  /** The called function or method. */
+  label: string;
+  /** Awaited calls made inside this call's arguments or receiver. */
+  nested: SequenceMessage[];
+}
+
+export interface SequenceDiagramIR {
+  /** The async function the calls are made from. */
+  caller: string;
+  /** Every participant other than the caller, in order of first call. */
+  participants: string[];
+  messages: SequenceMessage[];
+  title?: string;
+}
+
+export interface ClassDiagramIR {
+  classes: ClassDefinition[];
+  relations: ClassRelation[];
+  title?: string;
+}
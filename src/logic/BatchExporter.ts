@@ -0,0 +1,370 @@
+import * as fs from "fs/promises";
+import * as path from "path";
+import { ParseErrorInfo } from "../ir/ir";
+import { analyzeFunctionMatches, findParseErrors, listCalls, listFunctions } from "./analyzer";
+import { buildCallGraph, CallGraphFileInput, CallGraphOptions } from "./CallGraphBuilder";
+import { CallGraphFormat, CallGraphGenerator } from "./CallGraphGenerator";
+import {
+  DiagramExportOptions,
+  DiagramFormat,
+  generateDiagram,
+  generatePlantUMLBundle,
+  generatePng,
+  getDiagramFormatInfo,
+} from "./DiagramExporter";
+import { createSummary, MetricsSummary, summarizeSource } from "./MetricsSummary";
+import { PlantUMLBundleEntry } from "./PlantUMLGenerator";
+import { PngOptions } from "./PngGenerator";
+import { ComplexityAnalyzer } from "./utils/ComplexityAnalyzer";
+import { GlobMatcher } from "./utils/GlobMatcher";
+import { beginSharedDeadline, endSharedDeadline } from "./utils/AnalysisLimits";
+import { applyViewOptions, FlowchartViewOptions } from "./utils/FlowchartTransforms";
+
+/**
+ * Language IDs for the file extensions Visor can analyze.
+ */
+export const LANGUAGE_BY_EXTENSION: Readonly<Record<string, string>> = {
+  ".py": "python",
+  ".ts": "typescript",
+  ".tsx": "typescript",
+  ".js": "javascript",
+  ".jsx": "javascript",
+  ".mjs": "javascript",
+  ".cjs": "javascript",
+  ".java": "java",
+  ".cpp": "cpp",
+  ".cc": "cpp",
+  ".cxx": "cpp",
+  ".hpp": "cpp",
+  ".hh": "cpp",
+  ".c": "c",
+  ".h": "c",
+  ".rs": "rust",
+  ".go": "go",
+};
+
+export function getLanguageForFile(filePath: string): string | undefined {
+  return LANGUAGE_BY_EXTENSION[path.extname(filePath).toLowerCase()];
+}
+
+export interface BatchExportOptions
+  extends DiagramExportOptions,
+    FlowchartViewOptions {
+  format: DiagramFormat;
+  recursive: boolean;
+  /** Globs relative to the input directory; empty means every supported file. */
+  include: string[];
+  exclude: string[];
+  /** Only write diagrams for functions at least this complex. */
+  minComplexity?: number;
+  /** Only write diagrams for functions with this name. */
+  function?: string;
+  /** Write PNG images instead of `format`. */
+  png?: PngOptions;
+  /**
+   * Write every diagram into one PlantUML file (`SINGLE_FILE_NAME`), with a
+   * `package` per source file and module, instead of one file per function.
+   */
+  singleFile?: boolean;
+}
+
+export interface BatchExportResult {
+  filesProcessed: number;
+  functionsProcessed: number;
+  /** Functions analyzed but left out by `minComplexity`. */
+  functionsBelowThreshold: number;
+  filesSkipped: { file: string; reason: string }[];
+  /** Names defined more than once in a file; every definition is written. */
+  ambiguous: { file: string; name: string; count: number }[];
+  /** Every named function seen, for reporting a `function` filter that matched nothing. */
+  functionNames: string[];
+  /** Regions that did not parse, per file; other functions are still written. */
+  parseErrors: { file: string; errors: ParseErrorInfo[] }[];
+  /** Functions left out because they contain a syntax error. */
+  functionsWithParseErrors: number;
+  /** Written functions containing code no path reaches, with its source lines. */
+  unreachable: { file: string; name: string; lines: number[] }[];
+  outputs: string[];
+}
+
+export const DEFAULT_BATCH_EXCLUDES = ["**/node_modules/**", "**/.git/**", "**/target/**"];
+
+export const SINGLE_FILE_NAME = "diagrams.puml";
+
+/** Written into the output directory by `exportCallGraph`, plus the format's extension. */
+export const CALL_GRAPH_FILE_NAME = "call-graph";
+
+/**
+ * Walks a directory and writes one diagram per function, mirroring the
+ * input tree: `src/net/http.rs` yields `<out>/src/net/http_<function>.<ext>`
+ * (with any inline module path between the two). Titles of functions inside
+ * modules or namespaces name the module path. Files that cannot be read or
+ * analyzed, or that use up the analysis time budget, are skipped and
+ * reported rather than aborting or stalling the batch.
+ */
+export async function exportDirectory(
+  inputDir: string,
+  outputDir: string,
+  options: BatchExportOptions
+): Promise<BatchExportResult> {
+  const extension = options.png ? "png" : getDiagramFormatInfo(options.format).extension;
+
+  const result: BatchExportResult = {
+    filesProcessed: 0,
+    functionsProcessed: 0,
+    functionsBelowThreshold: 0,
+    filesSkipped: [],
+    ambiguous: [],
+    functionNames: [],
+    parseErrors: [],
+    functionsWithParseErrors: 0,
+    unreachable: [],
+    outputs: [],
+  };
+  const usedNames = new Set<string>();
+  const functionNames = new Set<string>();
+  const bundle: PlantUMLBundleEntry[] = [];
+
+  const files = await selectSourceFiles(inputDir, options);
+  await fs.mkdir(outputDir, { recursive: true });
+
+  for (const { file, relativePath, languageId } of files) {
+    // One time budget for all of a file's functions
+    beginSharedDeadline();
+    try {
+      const source = await fs.readFile(file, "utf8");
+      const parseErrors = await findParseErrors(source, languageId);
+      if (parseErrors.length > 0) {
+        result.parseErrors.push({ file: relativePath, errors: parseErrors });
+      }
+      // Anonymous entries cannot be selected by name
+      const allNames = Array.from(new Set(await listFunctions(source, languageId)))
+        .filter((name) => !name.startsWith("["));
+      if (allNames.length === 0) {
+        result.filesSkipped.push({ file: relativePath, reason: "no functions found" });
+        continue;
+      }
+      allNames.forEach((name) => functionNames.add(name));
+      const names =
+        options.function === undefined
+          ? allNames
+          : allNames.filter((name) => name === options.function);
+      if (names.length === 0) {
+        continue;
+      }
+
+      const moduleName = path.basename(file, path.extname(file));
+      const relativeDir = path.dirname(relativePath);
+      for (const name of names) {
+        const matches = await analyzeFunctionMatches(source, languageId, name);
+        if (matches.length > 1) {
+          result.ambiguous.push({ file: relativePath, name, count: matches.length });
+        }
+        for (const ir of matches) {
+          if (ir.parseErrors && ir.parseErrors.length > 0) {
+            result.functionsWithParseErrors++;
+            continue;
+          }
+          const complexity =
+            ir.functionComplexity?.cyclomaticComplexity ??
+            ComplexityAnalyzer.calculateGraphComplexity(ir);
+          if (options.minComplexity !== undefined && complexity < options.minComplexity) {
+            result.functionsBelowThreshold++;
+            continue;
+          }
+          const unreachableLines = ir.nodes
+            .filter((node) => node.unreachable && node.line !== undefined)
+            .map((node) => node.line!);
+          if (unreachableLines.length > 0) {
+            result.unreachable.push({
+              file: relativePath,
+              name,
+              lines: Array.from(new Set(unreachableLines)).sort((a, b) => a - b),
+            });
+          }
+          applyViewOptions(ir, options);
+          result.functionsProcessed++;
+          if (options.singleFile) {
+            const packagePath = [relativePath.split(path.sep).join("/"), ...(ir.modulePath || [])];
+            bundle.push({ packagePath, ir });
+            continue;
+          }
+
+          const baseName = [moduleName, ...(ir.modulePath || []), name]
+            .map(sanitizeFileName)
+            .join("_");
+          const outputName = uniqueName(path.join(relativeDir, baseName), usedNames);
+          const outputPath = path.join(outputDir, `${outputName}.${extension}`);
+          await fs.mkdir(path.dirname(outputPath), { recursive: true });
+          if (options.png) {
+            await fs.writeFile(outputPath, generatePng(ir, { ...options, ...options.png }));
+          } else {
+            await fs.writeFile(outputPath, generateDiagram(ir, options.format, options), "utf8");
+          }
+          result.outputs.push(outputPath);
+        }
+      }
+      result.filesProcessed++;
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      result.filesSkipped.push({ file: relativePath, reason: message });
+    } finally {
+      endSharedDeadline();
+    }
+  }
+
+  if (options.singleFile && bundle.length > 0) {
+    // Stable sort: a module's functions stay in source order in one block
+    const key = (entry: PlantUMLBundleEntry) => entry.packagePath.join("\u0000");
+    bundle.sort((a, b) => (key(a) < key(b) ? -1 : key(a) > key(b) ? 1 : 0));
+    const outputPath = path.join(outputDir, SINGLE_FILE_NAME);
+    await fs.writeFile(outputPath, generatePlantUMLBundle(bundle, options), "utf8");
+    result.outputs.push(outputPath);
+  }
+
+  result.functionNames = Array.from(functionNames).sort();
+  return result;
+}
+
+export interface CallGraphExportResult {
+  filesProcessed: number;
+  /** Functions defined in the files, not counting external callees. */
+  functionsFound: number;
+  calls: number;
+  filesSkipped: { file: string; reason: string }[];
+  outputPath: string;
+}
+
+/**
+ * Writes one call graph for every selected file in the directory to
+ * `<out>/call-graph.<ext>`, with the functions grouped by file and calls
+ * resolved across files by name and module path (see buildCallGraph).
+ */
+export async function exportCallGraph(
+  inputDir: string,
+  outputDir: string,
+  options: Pick<BatchExportOptions, "recursive" | "include" | "exclude"> &
+    CallGraphOptions & { format: CallGraphFormat }
+): Promise<CallGraphExportResult> {
+  const inputs: CallGraphFileInput[] = [];
+  const filesSkipped: { file: string; reason: string }[] = [];
+  for (const { file, relativePath, languageId } of await selectSourceFiles(inputDir, options)) {
+    beginSharedDeadline();
+    try {
+      const source = await fs.readFile(file, "utf8");
+      inputs.push({
+        file: relativePath.split(path.sep).join("/"),
+        functions: await listFunctions(source, languageId),
+        calls: await listCalls(source, languageId),
+      });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      filesSkipped.push({ file: relativePath, reason: message });
+    } finally {
+      endSharedDeadline();
+    }
+  }
+
+  const ir = buildCallGraph(inputs, options);
+  const extension = getDiagramFormatInfo(options.format).extension;
+  const outputPath = path.join(outputDir, `${CALL_GRAPH_FILE_NAME}.${extension}`);
+  await fs.mkdir(outputDir, { recursive: true });
+  await fs.writeFile(outputPath, new CallGraphGenerator().generate(ir, options.format), "utf8");
+
+  return {
+    filesProcessed: inputs.length,
+    functionsFound: ir.functions.filter((fn) => !fn.external).length,
+    calls: ir.calls.reduce((total, call) => total + call.count, 0),
+    filesSkipped,
+    outputPath,
+  };
+}
+
+/**
+ * Counts the contents of every function in the selected files of the
+ * directory (see summarizeSource) instead of drawing them. Files are named
+ * relative to the directory with `/` separators; ones that cannot be read
+ * or analyzed are listed as skipped.
+ */
+export async function summarizeDirectory(
+  inputDir: string,
+  options: Pick<BatchExportOptions, "recursive" | "include" | "exclude" | "function" | "minComplexity">
+): Promise<MetricsSummary> {
+  const summary = createSummary();
+  for (const { file, relativePath, languageId } of await selectSourceFiles(inputDir, options)) {
+    const name = relativePath.split(path.sep).join("/");
+    beginSharedDeadline();
+    try {
+      const source = await fs.readFile(file, "utf8");
+      const functions = await summarizeSource(source, languageId, { file: name, function: options.function });
+      summary.functions.push(
+        ...functions.filter(
+          (fn) => options.minComplexity === undefined || fn.cyclomaticComplexity >= options.minComplexity
+        )
+      );
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      summary.skipped.push({ file: name, reason: message });
+    } finally {
+      endSharedDeadline();
+    }
+  }
+  return summary;
+}
+
+/**
+ * The supported source files under the directory that pass the include and
+ * exclude globs, in a stable order.
+ */
+async function selectSourceFiles(
+  inputDir: string,
+  options: Pick<BatchExportOptions, "recursive" | "include" | "exclude">
+): Promise<{ file: string; relativePath: string; languageId: string }[]> {
+  const include = new GlobMatcher(options.include);
+  const exclude = new GlobMatcher([...DEFAULT_BATCH_EXCLUDES, ...options.exclude]);
+  const selected: { file: string; relativePath: string; languageId: string }[] = [];
+  for (const file of await collectFiles(inputDir, options.recursive)) {
+    const relativePath = path.relative(inputDir, file);
+    const languageId = getLanguageForFile(file);
+    if (!languageId || exclude.matches(relativePath)) {
+      continue;
+    }
+    if (!include.isEmpty && !include.matches(relativePath)) {
+      continue;
+    }
+    selected.push({ file, relativePath, languageId });
+  }
+  return selected;
+}
+
+async function collectFiles(dir: string, recursive: boolean): Promise<string[]> {
+  const entries = await fs.readdir(dir, { withFileTypes: true });
+  entries.sort((a, b) => a.name.localeCompare(b.name));
+
+  const files: string[] = [];
+  for (const entry of entries) {
+    const fullPath = path.join(dir, entry.name);
+    if (entry.isDirectory()) {
+      if (recursive) {
+        files.push(...(await collectFiles(fullPath, recursive)));
+      }
+    } else if (entry.isFile()) {
+      files.push(fullPath);
+    }
+  }
+  return files;
+}
+
+function sanitizeFileName(name: string): string {
+  return name.replace(/[^\w.-]+/g, "_").replace(/^_+|_+$/g, "") || "unnamed";
+}
+
+function uniqueName(base: string, used: Set<string>): string {
+  let candidate = base;
+  let suffix = 2;
+  while (used.has(candidate)) {
+    candidate = `${base}_${suffix++}`;
+  }
+  used.add(candidate);
+  return candidate;
+}
@@ -0,0 +1,198 @@
+import { CallGraphCall, CallGraphFunction, CallGraphIR, CallSite } from "../ir/ir";
+import { listCalls, listFunctions } from "./analyzer";
+
+export interface CallGraphSource {
+  code: string;
+  languageId: string;
+  /** Shown as the group around the file's functions when there are several files. */
+  file?: string;
+}
+
+export interface CallGraphOptions {
+  /** Draw calls to functions defined in none of the files (default true). */
+  external?: boolean;
+  /** Draw all external callees as one node instead of one node each. */
+  collapseExternal?: boolean;
+}
+
+/** The functions and call sites of one file, as the parsers report them. */
+export interface CallGraphFileInput {
+  file?: string;
+  functions: string[];
+  calls: CallSite[];
+}
+
+/**
+ * Scans each source for its functions and the calls made inside them, and
+ * links every call to the function it names.
+ */
+export async function analyzeCallGraph(
+  sources: CallGraphSource[],
+  options: CallGraphOptions = {}
+): Promise<CallGraphIR> {
+  const files: CallGraphFileInput[] = [];
+  for (const source of sources) {
+    files.push({
+      file: source.file,
+      functions: await listFunctions(source.code, source.languageId),
+      calls: await listCalls(source.code, source.languageId),
+    });
+  }
+  return buildCallGraph(files, options);
+}
+
+/**
+ * Resolves calls by name, best effort. The callee's last segment
+ * (`fetch_data` in `self.fetch_data`, `run` in `worker->run`) is looked up
+ * in every file. A qualified callee (`http::get`, `utils.parse`) goes to a
+ * file whose module path ends with the qualifier when one defines it;
+ * otherwise the calling file's own function wins, then the other files
+ * nearest first, by how much of the calling file's module path they
+ * share. A callee found nowhere becomes an external node named as
+ * written, or part of the one external node with `collapseExternal`. A
+ * function calling itself gets a self-loop. Functions sharing a name in
+ * one file share a node.
+ */
+export function buildCallGraph(
+  files: CallGraphFileInput[],
+  options: CallGraphOptions = {}
+): CallGraphIR {
+  const multiFile = files.length > 1;
+  const functions: CallGraphFunction[] = [];
+  // Per file: full name -> id and last segment -> id (first definition wins)
+  const byName: Map<string, string>[] = [];
+  const bySegment: Map<string, string>[] = [];
+  const modulePaths = files.map((input) => (input.file ? fileModulePath(input.file) : []));
+
+  files.forEach((input, index) => {
+    const names = new Map<string, string>();
+    const segments = new Map<string, string>();
+    for (const name of input.functions) {
+      if (names.has(name)) {
+        continue;
+      }
+      const id = multiFile ? `f${index + 1}:${name}` : name;
+      names.set(name, id);
+      const segment = lastSegment(name);
+      if (!segments.has(segment)) {
+        segments.set(segment, id);
+      }
+      functions.push(multiFile ? { id, name, file: input.file } : { id, name });
+    }
+    byName.push(names);
+    bySegment.push(segments);
+  });
+
+  const lookup = (i: number, callee: string): string | undefined =>
+    byName[i].get(callee) ?? bySegment[i].get(lastSegment(callee));
+  const resolve = (callee: string, fileIndex: number): string | undefined => {
+    const qualifier = qualifierOf(callee);
+    const candidates = files
+      .map((_, i) => i)
+      .filter((i) => lookup(i, callee) !== undefined)
+      .map((i) => ({
+        index: i,
+        qualified: suffixLength(modulePaths[i], qualifier),
+        local: i === fileIndex ? 1 : 0,
+        shared: prefixLength(modulePaths[i], modulePaths[fileIndex]),
+      }));
+    // Stable sort: among equals the earlier file wins
+    candidates.sort(
+      (a, b) => b.qualified - a.qualified || b.local - a.local || b.shared - a.shared
+    );
+    return candidates.length > 0 ? lookup(candidates[0].index, callee) : undefined;
+  };
+
+  const externals = new Map<string, string>();
+  const calls = new Map<string, CallGraphCall>();
+  files.forEach((input, index) => {
+    for (const site of input.calls) {
+      const from = byName[index].get(site.caller);
+      if (from === undefined) {
+        continue;
+      }
+      let to = resolve(site.callee, index);
+      if (to === undefined) {
+        if (options.external === false) {
+          continue;
+        }
+        to = externals.get(site.callee);
+        if (to === undefined) {
+          to = options.collapseExternal ? COLLAPSED_EXTERNAL_ID : `external:${site.callee}`;
+          externals.set(site.callee, to);
+          if (!options.collapseExternal) {
+            functions.push({ id: to, name: site.callee, external: true });
+          }
+        }
+      }
+      const key = `${from}\u0000${to}`;
+      const existing = calls.get(key);
+      if (existing) {
+        existing.count++;
+      } else {
+        calls.set(key, { from, to, count: 1 });
+      }
+    }
+  });
+  if (options.collapseExternal && externals.size > 0) {
+    functions.push({
+      id: COLLAPSED_EXTERNAL_ID,
+      name: `${externals.size} external function${externals.size === 1 ? "" : "s"}`,
+      external: true,
+    });
+  }
+
+  return { functions, calls: Array.from(calls.values()) };
+}
+
+const COLLAPSED_EXTERNAL_ID = "external:*";
+
+// Qualifier segments that name no module of their own
+const RELATIVE_SEGMENTS: ReadonlySet<string> = new Set(["crate", "self", "super", "Self", "this"]);
+
+// File names that stand for their directory's module
+const MODULE_INDEX_FILES: ReadonlySet<string> = new Set(["mod", "lib", "main", "index", "__init__"]);
+
+/** `src/net/http.rs` -> `[src, net, http]`, `pkg/__init__.py` -> `[pkg]`. */
+function fileModulePath(file: string): string[] {
+  const parts = file.replace(/\\/g, "/").split("/").filter((part) => part && part !== ".");
+  const last = parts.pop();
+  if (last !== undefined) {
+    const stem = last.replace(/\.[^.]*$/, "");
+    if (!MODULE_INDEX_FILES.has(stem)) {
+      parts.push(stem);
+    }
+  }
+  return parts;
+}
+
+/** `crate::net::http::get` -> `[net, http]`, `self.client.get` -> `[client]`. */
+function qualifierOf(callee: string): string[] {
+  const withoutGenerics = callee.replace(/(::)?<[^<>]*>$/, "");
+  const parts = withoutGenerics.split(/::|\.|->/);
+  return parts.slice(0, -1).filter((part) => part && !RELATIVE_SEGMENTS.has(part));
+}
+
+/** How many trailing segments of `path` spell `qualifier`; 0 unless all of it. */
+function suffixLength(path: string[], qualifier: string[]): number {
+  if (qualifier.length === 0 || qualifier.length > path.length) {
+    return 0;
+  }
+  const offset = path.length - qualifier.length;
+  return qualifier.every((part, i) => path[offset + i] === part) ? qualifier.length : 0;
+}
+
+function prefixLength(a: string[], b: string[]): number {
+  let shared = 0;
+  while (shared < a.length && shared < b.length && a[shared] === b[shared]) {
+    shared++;
+  }
+  return shared;
+}
+
+/** `Type::method::<T>` -> `method`, `self.fetch_data` -> `fetch_data`. */
+function lastSegment(name: string): string {
+  const withoutGenerics = name.replace(/(::)?<[^<>]*>$/, "");
+  const parts = withoutGenerics.split(/::|\.|->/);
+  return parts[parts.length - 1] || name;
+}
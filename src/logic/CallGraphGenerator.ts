@@ -0,0 +1,200 @@
+import { CallGraphCall, CallGraphFunction, CallGraphIR } from "../ir/ir";
+import {
+  IdSanitizer,
+  MERMAID_RESERVED_WORDS,
+  PLANTUML_RESERVED_WORDS,
+} from "./utils/IdSanitizer";
+import { StringProcessor } from "./utils/StringProcessor";
+
+export type CallGraphFormat = "mermaid" | "plantuml" | "dot";
+
+export const CALL_GRAPH_FORMATS: readonly CallGraphFormat[] = [
+  "mermaid",
+  "plantuml",
+  "dot",
+];
+
+const PLANTUML_SIGNIFICANT_CHARS = /[&"`{}\\#<>]/g;
+
+// Stands for the group of external callees, which no file name can equal
+const EXTERNAL_GROUP = Symbol("external");
+
+/**
+ * Emits a call graph: one box per function, grouped by file when the graph
+ * spans several, and one arrow per caller/callee pair labelled with the
+ * number of call sites when there is more than one. External callees are
+ * drawn dashed, together in a dashed "external" group.
+ */
+export class CallGraphGenerator {
+  public generate(ir: CallGraphIR, format: CallGraphFormat): string {
+    switch (format) {
+      case "mermaid":
+        return this.toMermaid(ir);
+      case "plantuml":
+        return this.toPlantUML(ir);
+      case "dot":
+        return this.toDot(ir);
+    }
+  }
+
+  private toMermaid(ir: CallGraphIR): string {
+    const ids = new IdSanitizer(MERMAID_RESERVED_WORDS);
+    const lines: string[] = [];
+    if (ir.title) {
+      lines.push("---", `title: ${StringProcessor.escapeString(ir.title)}`, "---");
+    }
+    lines.push("flowchart LR");
+
+    const node = (fn: CallGraphFunction): string => {
+      const label = StringProcessor.escapeString(fn.name, Infinity);
+      return fn.external
+        ? `${ids.get(fn.id)}(["${label}"]):::external`
+        : `${ids.get(fn.id)}["${label}"]`;
+    };
+    this.forEachGroup(ir, (group, functions) => {
+      if (group === undefined) {
+        functions.forEach((fn) => lines.push(`    ${node(fn)}`));
+        return;
+      }
+      const id = group === EXTERNAL_GROUP ? ids.get("group:external") : ids.get(`file:${group}`);
+      const label = group === EXTERNAL_GROUP ? "external" : group;
+      lines.push(`    subgraph ${id}["${StringProcessor.escapeString(label, Infinity)}"]`);
+      functions.forEach((fn) => lines.push(`        ${node(fn)}`));
+      lines.push("    end");
+      if (group === EXTERNAL_GROUP) {
+        lines.push(`    style ${id} fill:none,stroke:#888888,stroke-dasharray:4 3`);
+      }
+    });
+
+    for (const call of ir.calls) {
+      const label = this.countLabel(call);
+      lines.push(
+        `    ${ids.get(call.from)} -->${label ? `|"${label}"|` : ""} ${ids.get(call.to)}`
+      );
+    }
+    if (ir.functions.some((fn) => fn.external)) {
+      lines.push(
+        "    classDef external fill:none,stroke:#888888,stroke-dasharray:4 3,color:#888888"
+      );
+    }
+    return lines.join("\n") + "\n";
+  }
+
+  private toPlantUML(ir: CallGraphIR): string {
+    const ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+    const escape = (text: string) =>
+      text.replace(PLANTUML_SIGNIFICANT_CHARS, (char) => `&#${char.charCodeAt(0)};`);
+    const lines: string[] = ["@startuml"];
+    if (ir.title) {
+      lines.push(`title ${escape(ir.title)}`);
+    }
+    lines.push("left to right direction");
+
+    const node = (fn: CallGraphFunction): string =>
+      fn.external
+        ? `usecase "${escape(fn.name)}" as ${ids.get(fn.id)} #line.dashed`
+        : `rectangle "${escape(fn.name)}" as ${ids.get(fn.id)}`;
+    this.forEachGroup(ir, (group, functions) => {
+      if (group === undefined) {
+        functions.forEach((fn) => lines.push(node(fn)));
+        return;
+      }
+      lines.push(
+        group === EXTERNAL_GROUP
+          ? `package "external" as ${ids.get("group:external")} #line.dashed {`
+          : `package "${escape(group)}" {`
+      );
+      functions.forEach((fn) => lines.push(`  ${node(fn)}`));
+      lines.push("}");
+    });
+
+    for (const call of ir.calls) {
+      const label = this.countLabel(call);
+      lines.push(
+        `${ids.get(call.from)} --> ${ids.get(call.to)}${label ? ` : ${label}` : ""}`
+      );
+    }
+    lines.push("@enduml");
+    return lines.join("\n") + "\n";
+  }
+
+  private toDot(ir: CallGraphIR): string {
+    const ids = new IdSanitizer(new Set(["node", "edge", "graph", "digraph", "subgraph", "strict"]));
+    const escape = (text: string) => text.replace(/\\/g, "\\\\").replace(/"/g, '\\"');
+    const lines: string[] = ["digraph {"];
+    if (ir.title) {
+      lines.push(`    label="${escape(ir.title)}";`);
+    }
+    lines.push("    rankdir=LR;", '    node [shape=box, fontname="Helvetica"];');
+
+    const node = (fn: CallGraphFunction): string =>
+      fn.external
+        ? `${ids.get(fn.id)} [label="${escape(fn.name)}", shape=ellipse, style=dashed];`
+        : `${ids.get(fn.id)} [label="${escape(fn.name)}"];`;
+    let cluster = 0;
+    this.forEachGroup(ir, (group, functions) => {
+      if (group === undefined) {
+        functions.forEach((fn) => lines.push(`    ${node(fn)}`));
+        return;
+      }
+      if (group === EXTERNAL_GROUP) {
+        lines.push("    subgraph cluster_external {", '        label="external";', "        style=dashed;");
+      } else {
+        lines.push(`    subgraph cluster_${cluster++} {`, `        label="${escape(group)}";`);
+      }
+      functions.forEach((fn) => lines.push(`        ${node(fn)}`));
+      lines.push("    }");
+    });
+
+    for (const call of ir.calls) {
+      const label = this.countLabel(call);
+      lines.push(
+        `    ${ids.get(call.from)} -> ${ids.get(call.to)}${label ? ` [label="${label}"]` : ""};`
+      );
+    }
+    lines.push("}");
+    return lines.join("\n") + "\n";
+  }
+
+  /**
+   * Calls the visitor once per file with that file's functions, in order,
+   * then once with `undefined` for the functions outside any file
+   * (everything in a single-file graph), then once with EXTERNAL_GROUP for
+   * the external callees.
+   */
+  private forEachGroup(
+    ir: CallGraphIR,
+    visit: (group: string | typeof EXTERNAL_GROUP | undefined, functions: CallGraphFunction[]) => void
+  ): void {
+    const groups = new Map<string, CallGraphFunction[]>();
+    const ungrouped: CallGraphFunction[] = [];
+    const external: CallGraphFunction[] = [];
+    for (const fn of ir.functions) {
+      if (fn.external) {
+        external.push(fn);
+        continue;
+      }
+      if (fn.file === undefined) {
+        ungrouped.push(fn);
+        continue;
+      }
+      const group = groups.get(fn.file);
+      if (group) {
+        group.push(fn);
+      } else {
+        groups.set(fn.file, [fn]);
+      }
+    }
+    groups.forEach((functions, file) => visit(file, functions));
+    if (ungrouped.length > 0) {
+      visit(undefined, ungrouped);
+    }
+    if (external.length > 0) {
+      visit(EXTERNAL_GROUP, external);
+    }
+  }
+
+  private countLabel(call: CallGraphCall): string {
+    return call.count > 1 ? `${call.count} calls` : "";
+  }
+}
@@ -0,0 +1,117 @@
+import { ClassDefinition, ClassDiagramIR, ClassMember } from "../ir/ir";
+import { IdSanitizer, PLANTUML_RESERVED_WORDS } from "./utils/IdSanitizer";
+
+// Optimized string building
+class StringBuilder {
+  private parts: string[] = [];
+
+  append(str: string): void {
+    this.parts.push(str);
+  }
+
+  appendLine(str: string): void {
+    this.parts.push(str, "\n");
+  }
+
+  toString(): string {
+    return this.parts.join("");
+  }
+
+  clear(): void {
+    this.parts.length = 0;
+  }
+}
+
+/**
+ * Emits a PlantUML class diagram: fields as attributes, methods as
+ * operations, enum variants inside the enum box, and dashed realization
+ * arrows from types to the traits/interfaces they implement.
+ */
+export class ClassDiagramGenerator {
+  private sb = new StringBuilder();
+
+  public generate(ir: ClassDiagramIR): string {
+    this.sb.clear();
+    const ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+
+    this.sb.appendLine("@startuml");
+    if (ir.title) {
+      this.sb.appendLine(`title ${this.escapeMember(ir.title)}`);
+    }
+    this.sb.appendLine("hide empty members");
+
+    for (const definition of ir.classes) {
+      this.appendClass(definition, ids.get(definition.name));
+    }
+
+    for (const relation of ir.relations) {
+      this.sb.appendLine(`${ids.get(relation.from)} ..|> ${ids.get(relation.to)}`);
+    }
+
+    this.sb.appendLine("@enduml");
+    return this.sb.toString();
+  }
+
+  private appendClass(definition: ClassDefinition, alias: string): void {
+    const displayName = definition.name + (definition.typeParameters || "");
+    this.sb.append(`${definition.kind} "${displayName.replace(/"/g, "'")}" as ${alias}`);
+
+    const hasBody =
+      definition.fields.length > 0 ||
+      definition.methods.length > 0 ||
+      definition.variants.length > 0;
+    if (!hasBody) {
+      this.sb.appendLine("");
+      return;
+    }
+
+    this.sb.appendLine(" {");
+    for (const variant of definition.variants) {
+      this.sb.appendLine(`  ${this.escapeMember(variant)}`);
+    }
+    for (const field of definition.fields) {
+      this.sb.appendLine(`  ${this.formatField(field)}`);
+    }
+    if (definition.variants.length > 0 && definition.methods.length > 0) {
+      this.sb.appendLine("  --");
+    }
+    for (const method of definition.methods) {
+      this.sb.appendLine(`  ${this.formatMethod(method)}`);
+    }
+    this.sb.appendLine("}");
+  }
+
+  private formatField(field: ClassMember): string {
+    const type = field.type ? ` : ${field.type}` : "";
+    return this.escapeMember(
+      `${this.getVisibilitySymbol(field)}${field.name}${type}`
+    );
+  }
+
+  private formatMethod(method: ClassMember): string {
+    const modifier = method.isStatic ? "{static} " : "";
+    const returnType = method.type ? ` : ${method.type}` : "";
+    return (
+      modifier +
+      this.escapeMember(
+        `${this.getVisibilitySymbol(method)}${method.name}(${method.parameters || ""})${returnType}`
+      )
+    );
+  }
+
+  private getVisibilitySymbol(member: ClassMember): string {
+    return member.visibility === "public" ? "+" : "-";
+  }
+
+  /**
+   * Members are written on their own line inside a `{ }` body, so braces
+   * (struct-like enum variants, closure types) would end the body early.
+   */
+  private escapeMember(str: string): string {
+    return str
+      .replace(/\r?\n/g, " ")
+      .replace(/\{/g, "(")
+      .replace(/\}/g, ")")
+      .trim();
+  }
+}
@@ -0,0 +1,147 @@
+import { FlowchartIR } from "../ir/ir";
+import { EnhancedMermaidGenerator } from "./EnhancedMermaidGenerator";
+import {
+  PlantUMLBundleEntry,
+  PlantUMLGenerator,
+  validatePlantUML,
+} from "./PlantUMLGenerator";
+import { DotGenerator } from "./DotGenerator";
+import { SvgGenerator } from "./SvgGenerator";
+import { JsonGenerator } from "./JsonGenerator";
+import { PngGenerator, PngOptions } from "./PngGenerator";
+import { DiagramColors, resolveDiagramColors } from "./utils/DiagramColors";
+
+export type DiagramFormat = "mermaid" | "plantuml" | "dot" | "svg" | "json";
+
+export interface DiagramFormatInfo {
+  id: DiagramFormat;
+  label: string;
+  extension: string;
+}
+
+/**
+ * Formats offered by the copy/export controls, in display order.
+ */
+export const DIAGRAM_FORMATS: DiagramFormatInfo[] = [
+  { id: "mermaid", label: "Mermaid", extension: "mmd" },
+  { id: "plantuml", label: "PlantUML", extension: "puml" },
+  { id: "dot", label: "Graphviz DOT", extension: "dot" },
+  { id: "svg", label: "SVG", extension: "svg" },
+  { id: "json", label: "JSON", extension: "json" },
+];
+
+export interface DiagramExportOptions {
+  themeKey?: string;
+  vsCodeTheme?: "light" | "dark";
+  /** Built-in color preset for the emitted diagram ("light" or "dark"). */
+  colorTheme?: string;
+  /** Custom colors, applied over `colorTheme` (or the light preset). */
+  colors?: Partial<DiagramColors>;
+}
+
+export function isDiagramFormat(value: unknown): value is DiagramFormat {
+  return DIAGRAM_FORMATS.some((format) => format.id === value);
+}
+
+export function getDiagramFormatInfo(format: DiagramFormat): DiagramFormatInfo {
+  return DIAGRAM_FORMATS.find((info) => info.id === format) || DIAGRAM_FORMATS[0];
+}
+
+/**
+ * Emits the flowchart in the requested text format. All emitters read the
+ * same IR, so the node/edge structure is identical across formats.
+ */
+export function generateDiagram(
+  ir: FlowchartIR,
+  format: DiagramFormat,
+  options: DiagramExportOptions = {}
+): string {
+  const colors = resolveDiagramColors(options.colorTheme, options.colors);
+  switch (format) {
+    case "plantuml":
+      return new PlantUMLGenerator(colors).generate(ir);
+    case "dot":
+      return new DotGenerator(colors).generate(ir);
+    case "svg":
+      return new SvgGenerator(colors).generate(ir);
+    case "json":
+      // Raw structure only; colors do not apply
+      return new JsonGenerator().generate(ir);
+    case "mermaid":
+    default:
+      return new EnhancedMermaidGenerator(
+        options.themeKey,
+        options.vsCodeTheme,
+        colors
+      ).generate(ir);
+  }
+}
+
+/**
+ * Emits many flowcharts as a single PlantUML document, grouped into
+ * `package` blocks by each entry's package path.
+ */
+export function generatePlantUMLBundle(
+  entries: PlantUMLBundleEntry[],
+  options: DiagramExportOptions = {}
+): string {
+  const colors = resolveDiagramColors(options.colorTheme, options.colors);
+  return new PlantUMLGenerator(colors).generateBundle(entries);
+}
+
+/**
+ * Rasterizes the built-in SVG layout to PNG bytes. PNG is binary, so it is
+ * not one of the copyable text formats.
+ */
+export function generatePng(
+  ir: FlowchartIR,
+  options: DiagramExportOptions & PngOptions = {}
+): Buffer {
+  const colors = resolveDiagramColors(options.colorTheme, options.colors);
+  return new PngGenerator(colors, options).generate(ir);
+}
+
+/**
+ * Checks emitted text for structural problems before it is copied or
+ * saved. Mermaid is validated by rendering it in the webview, so only the
+ * formats Visor cannot render itself are checked here.
+ */
+export function validateDiagram(code: string, format: DiagramFormat): string[] {
+  switch (format) {
+    case "plantuml":
+      return validatePlantUML(code);
+    case "dot":
+      return validateDot(code);
+    case "mermaid":
+    default:
+      return [];
+  }
+}
+
+function validateDot(code: string): string[] {
+  let depth = 0;
+  let inString = false;
+  for (let i = 0; i < code.length; i++) {
+    const char = code[i];
+    if (inString) {
+      if (char === "\\") {
+        i++;
+      } else if (char === '"') {
+        inString = false;
+      }
+    } else if (char === '"') {
+      inString = true;
+    } else if (char === "{") {
+      depth++;
+    } else if (char === "}") {
+      depth--;
+      if (depth < 0) {
+        return ["Unmatched '}'"];
+      }
+    }
+  }
+  if (inString) {
+    return ["Unterminated string literal"];
+  }
+  return depth === 0 ? [] : ["Unmatched '{'"];
+}
@@ -0,0 +1,219 @@
+import {
+  FlowchartIR,
+  FlowchartEdge,
+  FlowchartNode,
+  NodeType,
+  EdgeType,
+} from "../ir/ir";
+import { IdSanitizer } from "./utils/IdSanitizer";
+import { StringProcessor } from "./utils/StringProcessor";
+import {
+  COLD_EDGE_COLOR,
+  DiagramColors,
+  DIFF_COLORS,
+  UNREACHABLE_COLOR,
+} from "./utils/DiagramColors";
+import { groupByScope, ScopeBox } from "./utils/FlowchartTransforms";
+
+// Optimized string building
+class StringBuilder {
+  private parts: string[] = [];
+
+  append(str: string): void {
+    this.parts.push(str);
+  }
+
+  appendLine(str: string): void {
+    this.parts.push(str, "\n");
+  }
+
+  toString(): string {
+    return this.parts.join("");
+  }
+
+  clear(): void {
+    this.parts.length = 0;
+  }
+}
+
+/**
+ * Keywords Graphviz treats specially when they appear as bare IDs.
+ */
+const DOT_RESERVED_WORDS: ReadonlySet<string> = new Set([
+  "node",
+  "edge",
+  "graph",
+  "digraph",
+  "subgraph",
+  "strict",
+]);
+
+/**
+ * Emits a Graphviz `digraph` with the same nodes and edges as the other
+ * formats: diamonds for conditionals, boxes for statements.
+ */
+export class DotGenerator {
+  private sb = new StringBuilder();
+
+  constructor(private colors?: DiagramColors) {}
+
+  public generate(ir: FlowchartIR): string {
+    this.sb.clear();
+    const ids = new IdSanitizer(DOT_RESERVED_WORDS);
+
+    this.sb.appendLine("digraph {");
+    if (ir.title) {
+      this.sb.appendLine(`    label="${this.escapeLabel(ir.title)}";`);
+      this.sb.appendLine("    labelloc=t;");
+    }
+    this.sb.appendLine("    rankdir=TB;");
+    if (this.colors) {
+      const { background, nodeBackground, nodeBorder, nodeText, edge } = this.colors;
+      this.sb.appendLine(`    bgcolor="${background}";`);
+      this.sb.appendLine(`    fontcolor="${nodeText}";`);
+      this.sb.appendLine(
+        `    node [fontname="Helvetica", style=filled, fillcolor="${nodeBackground}", color="${nodeBorder}", fontcolor="${nodeText}"];`
+      );
+      this.sb.appendLine(
+        `    edge [fontname="Helvetica", color="${edge}", fontcolor="${nodeText}"];`
+      );
+    } else {
+      this.sb.appendLine('    node [fontname="Helvetica"];');
+      this.sb.appendLine('    edge [fontname="Helvetica"];');
+    }
+
+    this.appendScopeBox(groupByScope(ir), ids, "    ");
+
+    for (const edge of ir.edges) {
+      this.sb.append("    ");
+      this.sb.append(ids.get(edge.from));
+      this.sb.append(" -> ");
+      this.sb.append(ids.get(edge.to));
+      const attributes = this.getEdgeAttributes(edge);
+      if (attributes.length > 0) {
+        this.sb.append(` [${attributes.join(", ")}]`);
+      }
+      this.sb.appendLine(";");
+    }
+
+    this.sb.appendLine("}");
+    return this.sb.toString();
+  }
+
+  /** Declares a scope's nodes, then its nested scopes as dashed clusters. */
+  private appendScopeBox(box: ScopeBox, ids: IdSanitizer, indent: string): void {
+    for (const node of box.nodes) {
+      this.sb.append(indent);
+      this.sb.append(ids.get(node.id));
+      this.sb.append(' [label="');
+      this.sb.append(this.escapeLabel(node.label));
+      this.sb.append('", shape=');
+      this.sb.append(this.getShape(node));
+      const attributes = this.getNodeAttributes(node);
+      if (attributes.length > 0) {
+        this.sb.append(`, ${attributes.join(", ")}`);
+      }
+      this.sb.appendLine("];");
+    }
+    for (const child of box.children) {
+      // Graphviz only draws subgraphs whose name starts with "cluster"
+      this.sb.appendLine(`${indent}subgraph cluster_${child.scope!.id} {`);
+      this.sb.appendLine(`${indent}    label="${this.escapeLabel(child.scope!.label)}";`);
+      this.sb.appendLine(`${indent}    style=dashed;`);
+      if (this.colors) {
+        this.sb.appendLine(`${indent}    color="${this.colors.nodeBorder}";`);
+      }
+      this.appendScopeBox(child, ids, `${indent}    `);
+      this.sb.appendLine(`${indent}}`);
+    }
+  }
+
+  /** Styling for diff views and unreachable nodes; empty for plain nodes. */
+  private getNodeAttributes(node: FlowchartNode): string[] {
+    const styles: string[] = [];
+    const attributes: string[] = [];
+    if (node.diff) {
+      const { fill, stroke } = DIFF_COLORS[node.diff];
+      styles.push("filled");
+      attributes.push(`fillcolor="${fill}"`, "penwidth=2");
+      if (!node.unreachable) {
+        attributes.push(`color="${stroke}"`);
+      }
+    } else if (node.unreachable && this.colors) {
+      // Keep the fill a color map adds; only the outline changes
+      styles.push("filled");
+    }
+    if (node.unreachable) {
+      styles.push("dashed");
+      attributes.push(`color="${UNREACHABLE_COLOR}"`);
+    }
+    if (styles.length > 0) {
+      attributes.unshift(`style="${styles.join(",")}"`);
+    }
+    return attributes;
+  }
+
+  private getEdgeAttributes(edge: FlowchartEdge): string[] {
+    const attributes: string[] = [];
+    if (edge.label) {
+      attributes.push(`label="${this.escapeLabel(edge.label)}"`);
+    }
+    if (edge.edgeType === EdgeType.BACK) {
+      // Dashed, and excluded from ranking so loops don't stretch the layout
+      attributes.push("style=dashed", "constraint=false");
+    } else if (edge.edgeType === EdgeType.DATAFLOW) {
+      attributes.push("style=dotted", "constraint=false", 'color="#8a8f98"', 'fontcolor="#8a8f98"');
+    }
+    if (edge.diff) {
+      attributes.push(`color="${DIFF_COLORS[edge.diff].stroke}"`);
+    } else if (edge.cold && edge.edgeType !== EdgeType.DATAFLOW) {
+      attributes.push(`color="${COLD_EDGE_COLOR}"`, `fontcolor="${COLD_EDGE_COLOR}"`);
+    }
+    if (edge.onLongestPath) {
+      attributes.push("penwidth=3");
+    } else if (edge.diff) {
+      attributes.push("penwidth=2");
+    } else if (edge.cold) {
+      attributes.push("penwidth=0.6");
+    }
+    return attributes;
+  }
+
+  private getShape(node: FlowchartNode): string {
+    switch (node.nodeType) {
+      case NodeType.DECISION:
+      case NodeType.LOOP_START:
+        return "diamond";
+      case NodeType.ENTRY:
+      case NodeType.EXIT:
+        return "ellipse";
+    }
+
+    switch (node.shape) {
+      case "diamond":
+        return "diamond";
+      case "round":
+      case "stadium":
+        return "ellipse";
+      case "rect":
+      default:
+        return "box";
+    }
+  }
+
+  /**
+   * Escapes a label for use inside a double-quoted DOT string. IR labels
+   * carry Mermaid entity escapes, so those are decoded first. Backslashes
+   * must be escaped before quotes so the quote escapes survive.
+   */
+  private escapeLabel(str: string): string {
+    if (!str) {
+      return "";
+    }
+    return StringProcessor.unescapeString(str)
+      .replace(/\\/g, "\\\\")
+      .replace(/"/g, '\\"')
+      .replace(/\r?\n/g, "\\n")
+      .trim();
+  }
+}
@@ -1,7 +1,21 @@
-import { FlowchartIR, FlowchartNode, FlowchartEdge, NodeType } from "../ir/ir";
+import {
+  FlowchartIR,
+  FlowchartNode,
+  FlowchartEdge,
+  NodeType,
+  EdgeType,
+  DiffStatus,
+} from "../ir/ir";
 import { StringProcessor } from "./utils/StringProcessor";
 import { SubtleThemeManager, ThemeStyles } from "./utils/ThemeManager";
+import {
+  COLD_EDGE_COLOR,
+  DiagramColors,
+  DIFF_COLORS,
+  UNREACHABLE_COLOR,
+} from "./utils/DiagramColors";
 import { getComplexityConfig } from "./utils/ComplexityConfig";
+import { IdSanitizer, MERMAID_RESERVED_WORDS } from "./utils/IdSanitizer";
 
 // Optimized string building
 class StringBuilder {
@@ -28,23 +42,29 @@ export class EnhancedMermaidGenerator {
   private sb = new StringBuilder();
   private themeStyles: ThemeStyles;
   private complexityConfig = getComplexityConfig();
+  private ids = new IdSanitizer(MERMAID_RESERVED_WORDS);
 
   constructor(
     private themeKey: string = "monokai",
-    private vsCodeTheme: "light" | "dark" = "dark"
+    private vsCodeTheme: "light" | "dark" = "dark",
+    private colors?: DiagramColors
   ) {
-    this.themeStyles = SubtleThemeManager.getThemeStyles(themeKey, vsCodeTheme);
+    this.themeStyles = colors
+      ? SubtleThemeManager.getUniformThemeStyles({
+          fill: colors.nodeBackground,
+          stroke: colors.nodeBorder,
+          textColor: colors.nodeText,
+        })
+      : SubtleThemeManager.getThemeStyles(themeKey, vsCodeTheme);
   }
 
   /**
    * Sanitizes an ID string to be Mermaid-compatible.
-   * It replaces spaces with underscores and removes all non-alphanumeric characters.
+   * Distinct raw IDs always map to distinct output IDs, and Mermaid keywords
+   * such as `end` are prefixed so they cannot terminate a block.
    */
   private sanitizeId(id: string): string {
-    return id
-      .replace(/\s+/g, '_')
-      .replace(/[^\w]/g, '') // Remove all non-alphanumeric characters
-      .replace(/_+/g, '_');
+    return this.ids.get(id);
   }
 
   /**
@@ -56,8 +76,9 @@ export class EnhancedMermaidGenerator {
     const edgeMap = new Map<string, {source: string, target: string}>();
     
     for (const edge of ir.edges) {
-      const sanitizedFrom = this.sanitizeId(edge.from);
-      const sanitizedTo = this.sanitizeId(edge.to);
+      // Edge endpoints have already been sanitized in generate()
+      const sanitizedFrom = edge.from;
+      const sanitizedTo = edge.to;
       const edgeId = `${sanitizedFrom}_${sanitizedTo}`;
       
       // Store both source and target
@@ -72,6 +93,19 @@ export class EnhancedMermaidGenerator {
 
   public generate(ir: FlowchartIR): string {
     this.sb.clear();
+    this.ids = new IdSanitizer(MERMAID_RESERVED_WORDS);
+    if (this.colors) {
+      // Must precede the diagram type; covers what classDefs cannot reach
+      const themeVariables = {
+        background: this.colors.background,
+        lineColor: this.colors.edge,
+        primaryTextColor: this.colors.nodeText,
+        edgeLabelBackground: this.colors.background,
+      };
+      this.sb.appendLine(
+        `%%{init: ${JSON.stringify({ theme: "base", themeVariables })}}%%`
+      );
+    }
     this.sb.appendLine("graph TD");
     
     // Add this line to avoid default node styling conflicts
@@ -97,11 +131,20 @@ export class EnhancedMermaidGenerator {
         edge.from = this.sanitizeId(edge.from);
         edge.to = this.sanitizeId(edge.to);
     }
+    for (const entry of ir.locationMap) {
+        entry.nodeId = this.sanitizeId(entry.nodeId);
+    }
+    if (ir.entryNodeId) {
+        ir.entryNodeId = this.sanitizeId(ir.entryNodeId);
+    }
+    if (ir.exitNodeId) {
+        ir.exitNodeId = this.sanitizeId(ir.exitNodeId);
+    }
 
     // Generate nodes efficiently
     for (const node of ir.nodes) {
       const shape = this.getShape(node);
-      let label = this.escapeString(node.label);
+      let label = this.escapeNodeLabel(node.label);
 
       // Add complexity annotation to nodes
       if (
@@ -127,21 +170,59 @@ export class EnhancedMermaidGenerator {
     }
 
     // Generate edges efficiently
-    for (const edge of ir.edges) {
+    const dataflowEdgeIndexes: number[] = [];
+    const diffEdgeIndexes = new Map<DiffStatus, number[]>();
+    const longestPathEdgeIndexes: number[] = [];
+    const coldEdgeIndexes: number[] = [];
+    ir.edges.forEach((edge, index) => {
       this.sb.append("    ");
       this.sb.append(edge.from);
 
+      // Loop back-edges are drawn dotted so iteration stands out from forward
+      // flow; def-use edges are dotted too and recolored below
+      const isBackEdge =
+        edge.edgeType === EdgeType.BACK || edge.edgeType === EdgeType.DATAFLOW;
+      if (edge.edgeType === EdgeType.DATAFLOW) {
+        dataflowEdgeIndexes.push(index);
+      }
+      if (edge.diff) {
+        diffEdgeIndexes.set(edge.diff, [...(diffEdgeIndexes.get(edge.diff) || []), index]);
+      }
+      if (edge.onLongestPath) {
+        longestPathEdgeIndexes.push(index);
+      }
+      if (edge.cold && !edge.diff && edge.edgeType !== EdgeType.DATAFLOW) {
+        coldEdgeIndexes.push(index);
+      }
       if (edge.label) {
         const label = this.escapeString(edge.label);
-        this.sb.append(' -- "');
+        this.sb.append(isBackEdge ? ' -. "' : ' -- "');
         this.sb.append(label);
-        this.sb.append('" --> ');
+        this.sb.append(isBackEdge ? '" .-> ' : '" --> ');
       } else {
-        this.sb.append(" --> ");
+        this.sb.append(isBackEdge ? " -.-> " : " --> ");
       }
 
       this.sb.append(edge.to);
       this.sb.appendLine("");
+    });
+    if (dataflowEdgeIndexes.length > 0) {
+      this.sb.appendLine(
+        `    linkStyle ${dataflowEdgeIndexes.join(",")} stroke:#8a8f98,stroke-width:1px,stroke-dasharray:2 3,color:#8a8f98`
+      );
+    }
+    if (coldEdgeIndexes.length > 0) {
+      this.sb.appendLine(
+        `    linkStyle ${coldEdgeIndexes.join(",")} stroke:${COLD_EDGE_COLOR},stroke-width:1px,color:${COLD_EDGE_COLOR}`
+      );
+    }
+    for (const [status, indexes] of diffEdgeIndexes) {
+      this.sb.appendLine(
+        `    linkStyle ${indexes.join(",")} stroke:${DIFF_COLORS[status].stroke},stroke-width:2px`
+      );
+    }
+    if (longestPathEdgeIndexes.length > 0) {
+      this.sb.appendLine(`    linkStyle ${longestPathEdgeIndexes.join(",")} stroke-width:3.5px`);
     }
     
     // Generate edge metadata as comments for JavaScript to parse
@@ -181,12 +262,41 @@ export class EnhancedMermaidGenerator {
       }
     }
 
+    // Diff views: added, removed and changed nodes override the type styling
+    for (const status of Object.keys(DIFF_COLORS) as DiffStatus[]) {
+      const ids = ir.nodes.filter((node) => node.diff === status).map((node) => node.id);
+      if (ids.length > 0) {
+        const { fill, stroke } = DIFF_COLORS[status];
+        this.sb.appendLine(`    classDef diff_${status} fill:${fill},stroke:${stroke},stroke-width:2px,color:#222222`);
+        this.sb.appendLine(`    class ${ids.join(",")} diff_${status}`);
+      }
+    }
+
+    // Unreachable code keeps its type styling under a dashed red outline
+    const unreachableIds = ir.nodes
+      .filter((node) => node.unreachable)
+      .map((node) => node.id);
+    if (unreachableIds.length > 0) {
+      this.sb.appendLine(
+        `    classDef unreachable stroke:${UNREACHABLE_COLOR},stroke-width:1.5px,stroke-dasharray:4 3`
+      );
+      this.sb.appendLine(`    class ${unreachableIds.join(",")} unreachable`);
+    }
+
+    // Summary nodes of folded nesting expand on click instead of jumping to code
+    const foldIds = new Set(ir.nodes.filter((node) => node.folded).map((node) => node.id));
+    for (const id of foldIds) {
+      this.sb.appendLine(`    click ${id} call onFoldClick()`);
+    }
+
     // Generate click handlers efficiently
     for (const entry of ir.locationMap) {
+      if (foldIds.has(entry.nodeId)) {
+        continue;
+      }
       // Use the already sanitized node ID
-      const sanitizedNodeId = this.sanitizeId(entry.nodeId);
       this.sb.append("    click ");
-      this.sb.append(sanitizedNodeId);
+      this.sb.append(entry.nodeId);
       this.sb.append(" call onNodeClick(");
       this.sb.append(entry.start.toString());
       this.sb.append(", ");
@@ -261,6 +371,7 @@ export class EnhancedMermaidGenerator {
       case NodeType.FUNCTION_CALL:
         return this.themeStyles.functionCall;
       case NodeType.ASYNC_OPERATION:
+      case NodeType.AWAIT:
         return this.themeStyles.asyncOperation;
       case NodeType.BREAK_CONTINUE:
         return this.themeStyles.breakContinue;
@@ -322,6 +433,17 @@ export class EnhancedMermaidGenerator {
     return StringProcessor.escapeString(str);
   }
 
+  /**
+   * Escapes a node label, keeping multi-statement labels (one statement per
+   * line, as produced by compact mode) on separate lines.
+   */
+  private escapeNodeLabel(label: string): string {
+    return label
+      .split("\n")
+      .map((line) => this.escapeString(line))
+      .join("<br/>");
+  }
+
   private getComplexityIndicator(
     rating?: "low" | "medium" | "high" | "very-high"
   ): string {
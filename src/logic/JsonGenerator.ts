@@ -0,0 +1,121 @@
+import { DiffStatus, EdgeType, FlowchartIR, FlowchartNode } from "../ir/ir";
+import { StringProcessor } from "./utils/StringProcessor";
+
+/**
+ * Bumped whenever a field is removed or changes meaning. Adding optional
+ * fields does not change the version. See docs/json-format.md.
+ */
+export const JSON_SCHEMA_VERSION = 1;
+
+export interface JsonSpan {
+  /** Character offsets into the analyzed source, end exclusive. */
+  start: number;
+  end: number;
+  /** 1-based line the node starts on. */
+  line?: number;
+}
+
+export interface JsonNode {
+  id: string;
+  /** A `NodeType` value such as "decision" or "loop_start", or "folded". */
+  kind: string;
+  label: string;
+  /** Null for nodes without source text of their own, such as merge points. */
+  span: JsonSpan | null;
+  depth?: number;
+  defines?: string[];
+  uses?: string[];
+  unreachable?: boolean;
+  diff?: DiffStatus;
+  folded?: number;
+}
+
+export interface JsonEdge {
+  from: string;
+  to: string;
+  label: string | null;
+  kind: EdgeType;
+}
+
+export interface JsonGraph {
+  schema: "visor-flowchart";
+  version: number;
+  title: string | null;
+  modulePath: string[];
+  entry: string | null;
+  exit: string | null;
+  cyclomaticComplexity: number | null;
+  nodes: JsonNode[];
+  edges: JsonEdge[];
+}
+
+/**
+ * Emits the raw graph for other tools: every node and edge of the IR with
+ * its id, kind, plain-text label and source span, in IR order. Ids are the
+ * IR's own, so analyzing the same source twice gives identical output.
+ */
+export class JsonGenerator {
+  public generate(ir: FlowchartIR): string {
+    return JSON.stringify(this.toGraph(ir), null, 2);
+  }
+
+  public toGraph(ir: FlowchartIR): JsonGraph {
+    return {
+      schema: "visor-flowchart",
+      version: JSON_SCHEMA_VERSION,
+      title: ir.title ? StringProcessor.unescapeString(ir.title) : null,
+      modulePath: ir.modulePath || [],
+      entry: ir.entryNodeId || null,
+      exit: ir.exitNodeId || null,
+      cyclomaticComplexity: ir.functionComplexity?.cyclomaticComplexity ?? null,
+      nodes: ir.nodes.map((node) => this.toNode(node)),
+      edges: ir.edges.map((edge) => ({
+        from: edge.from,
+        to: edge.to,
+        label: edge.label ? StringProcessor.unescapeString(edge.label) : null,
+        kind: edge.edgeType || EdgeType.NORMAL,
+      })),
+    };
+  }
+
+  private toNode(node: FlowchartNode): JsonNode {
+    const json: JsonNode = {
+      id: node.id,
+      kind: this.getKind(node),
+      label: StringProcessor.unescapeString(node.label),
+      span: node.location
+        ? { start: node.location.start, end: node.location.end, line: node.line }
+        : null,
+    };
+    // Optional annotations are present only when set, like in the IR
+    if (node.depth !== undefined) {
+      json.depth = node.depth;
+    }
+    if (node.defines && node.defines.length > 0) {
+      json.defines = node.defines;
+    }
+    if (node.uses && node.uses.length > 0) {
+      json.uses = node.uses;
+    }
+    if (node.unreachable) {
+      json.unreachable = true;
+    }
+    if (node.diff) {
+      json.diff = node.diff;
+    }
+    if (node.folded) {
+      json.folded = node.folded;
+    }
+    return json;
+  }
+
+  private getKind(node: FlowchartNode): string {
+    if (node.folded) {
+      return "folded";
+    }
+    if (node.nodeType) {
+      return node.nodeType;
+    }
+    return node.shape === "diamond" ? "decision" : "process";
+  }
+}
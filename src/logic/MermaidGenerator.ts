@@ -1,5 +1,6 @@
-import { FlowchartIR, FlowchartNode } from '../ir/ir';
+import { FlowchartIR, FlowchartNode, EdgeType } from '../ir/ir';
 import { StringProcessor } from './utils/StringProcessor';
+import { IdSanitizer, MERMAID_RESERVED_WORDS } from './utils/IdSanitizer';
 
 // Optimized string building
 class StringBuilder {
@@ -27,6 +28,7 @@ export class MermaidGenerator {
     
     public generate(ir: FlowchartIR): string {
         this.sb.clear();
+        const ids = new IdSanitizer(MERMAID_RESERVED_WORDS);
         this.sb.appendLine('graph TD');
 
         if (ir.title) {
@@ -36,9 +38,12 @@ export class MermaidGenerator {
         // Generate nodes efficiently
         for (const node of ir.nodes) {
             const shape = this.getShape(node);
-            const label = this.escapeString(node.label);
+            const label = node.label
+                .split('\n')
+                .map((line) => this.escapeString(line))
+                .join('<br/>');
             this.sb.append('    ');
-            this.sb.append(node.id);
+            this.sb.append(ids.get(node.id));
             this.sb.append(shape[0]);
             this.sb.append('"');
             this.sb.append(label);
@@ -50,25 +55,26 @@ export class MermaidGenerator {
         // Generate edges efficiently  
         for (const edge of ir.edges) {
             this.sb.append('    ');
-            this.sb.append(edge.from);
+            this.sb.append(ids.get(edge.from));
             
+            const isBackEdge = edge.edgeType === EdgeType.BACK;
             if (edge.label) {
                 const label = this.escapeString(edge.label);
-                this.sb.append(' -- "');
+                this.sb.append(isBackEdge ? ' -. "' : ' -- "');
                 this.sb.append(label);
-                this.sb.append('" --> ');
+                this.sb.append(isBackEdge ? '" .-> ' : '" --> ');
             } else {
-                this.sb.append(' --> ');
+                this.sb.append(isBackEdge ? ' -.-> ' : ' --> ');
             }
             
-            this.sb.append(edge.to);
+            this.sb.append(ids.get(edge.to));
             this.sb.appendLine('');
         }
 
         // Generate click handlers efficiently
         for (const entry of ir.locationMap) {
             this.sb.append('    click ');
-            this.sb.append(entry.nodeId);
+            this.sb.append(ids.get(entry.nodeId));
             this.sb.append(' call onNodeClick(');
             this.sb.append(entry.start.toString());
             this.sb.append(', ');
@@ -0,0 +1,126 @@
+import { EdgeType, FlowchartIR, NodeType } from "../ir/ir";
+import { analyzeFunctionMatches, listFunctions } from "./analyzer";
+import { ComplexityAnalyzer } from "./utils/ComplexityAnalyzer";
+
+/**
+ * Bumped whenever a field is removed or changes meaning. Adding optional
+ * fields does not change the version. See docs/summary-format.md.
+ */
+export const SUMMARY_SCHEMA_VERSION = 1;
+
+/** The counts for one function definition. */
+export interface FunctionMetrics {
+  /** Path as given to the summary, with `/` separators; null for a single source. */
+  file: string | null;
+  name: string;
+  modulePath: string[];
+  /** 1-based, inclusive. */
+  lines: { start: number; end: number };
+  /** Every node of the flowchart, Start and End included. */
+  nodes: number;
+  /** Control-flow edges. */
+  edges: number;
+  cyclomaticComplexity: number;
+  /** Explicit and implicit returns, and early error returns (`?`). */
+  returns: number;
+  loops: number;
+  /** Statements that can panic: `panic!`-like macros and `unwrap`/`expect` calls. */
+  panics: number;
+}
+
+export interface MetricsSummary {
+  schema: "visor-summary";
+  version: number;
+  /** In file order, then source order. */
+  functions: FunctionMetrics[];
+  /** Files that could not be analyzed, with the reason. */
+  skipped: { file: string; reason: string }[];
+}
+
+export function createSummary(): MetricsSummary {
+  return {
+    schema: "visor-summary",
+    version: SUMMARY_SCHEMA_VERSION,
+    functions: [],
+    skipped: [],
+  };
+}
+
+/**
+ * Counts what the flowchart of every function in the source contains,
+ * without emitting any diagram. Every definition of an overloaded name is
+ * listed; functions containing a syntax error are left out, since their
+ * graph is incomplete.
+ */
+export async function summarizeSource(
+  source: string,
+  languageId: string,
+  options: { file?: string; function?: string } = {}
+): Promise<FunctionMetrics[]> {
+  // Anonymous entries cannot be selected by name
+  const names = Array.from(new Set(await listFunctions(source, languageId))).filter(
+    (name) => !name.startsWith("[") && (options.function === undefined || name === options.function)
+  );
+  const functions: FunctionMetrics[] = [];
+  for (const name of names) {
+    for (const ir of await analyzeFunctionMatches(source, languageId, name)) {
+      if (!ir.parseErrors || ir.parseErrors.length === 0) {
+        functions.push(measureFunction(ir, name, source, options.file));
+      }
+    }
+  }
+  // Names are listed by kind (functions, then methods...); report in source order
+  return functions.sort((a, b) => a.lines.start - b.lines.start);
+}
+
+/** The counts for one analyzed function; `source` is the text it was analyzed from. */
+export function measureFunction(
+  ir: FlowchartIR,
+  name: string,
+  source: string,
+  file?: string
+): FunctionMetrics {
+  const range = ir.functionRange ?? { start: 0, end: source.length };
+  const startLine = countLines(source, 0, range.start) + 1;
+  const panicSites = new Set<string>();
+  const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+  for (const node of ir.nodes) {
+    // A shared abort node stands for no statement of its own
+    if (node.nodeType === NodeType.PANIC && node.location) {
+      panicSites.add(node.id);
+    }
+  }
+  for (const edge of ir.edges) {
+    const target = nodeById.get(edge.to);
+    if (target?.nodeType === NodeType.PANIC && !target.location) {
+      panicSites.add(edge.from);
+    }
+  }
+  // Only nodes with source text: the shared `return Err` node is not a return of its own
+  const count = (...types: NodeType[]) =>
+    ir.nodes.filter(
+      (node) => node.location && node.nodeType !== undefined && types.includes(node.nodeType)
+    ).length;
+
+  return {
+    file: file ?? null,
+    name,
+    modulePath: ir.modulePath ?? [],
+    lines: { start: startLine, end: startLine + countLines(source, range.start, range.end) },
+    nodes: ir.nodes.length,
+    edges: ir.edges.filter((edge) => edge.edgeType !== EdgeType.DATAFLOW).length,
+    cyclomaticComplexity:
+      ir.functionComplexity?.cyclomaticComplexity ?? ComplexityAnalyzer.calculateGraphComplexity(ir),
+    returns: count(NodeType.RETURN, NodeType.EARLY_RETURN_ERROR),
+    loops: count(NodeType.LOOP_START),
+    panics: panicSites.size,
+  };
+}
+
+function countLines(source: string, start: number, end: number): number {
+  let lines = 0;
+  for (let index = source.indexOf("\n", start); index !== -1 && index < end; index = source.indexOf("\n", index + 1)) {
+    lines++;
+  }
+  return lines;
+}
@@ -0,0 +1,339 @@
+import { FlowchartIR, FlowchartNode, NodeType, EdgeType } from "../ir/ir";
+import { IdSanitizer, PLANTUML_RESERVED_WORDS } from "./utils/IdSanitizer";
+import { StringProcessor } from "./utils/StringProcessor";
+import {
+  COLD_EDGE_COLOR,
+  DiagramColors,
+  DIFF_COLORS,
+  UNREACHABLE_COLOR,
+} from "./utils/DiagramColors";
+import { groupByScope, ScopeBox } from "./utils/FlowchartTransforms";
+
+// Optimized string building
+class StringBuilder {
+  private parts: string[] = [];
+
+  append(str: string): void {
+    this.parts.push(str);
+  }
+
+  appendLine(str: string): void {
+    this.parts.push(str, "\n");
+  }
+
+  toString(): string {
+    return this.parts.join("");
+  }
+
+  clear(): void {
+    this.parts.length = 0;
+  }
+}
+
+/**
+ * Characters with meaning in PlantUML labels: string delimiters, Creole
+ * markup, colors and escapes. `&` is included so literal text never reads
+ * as an entity.
+ */
+const PLANTUML_SIGNIFICANT_CHARS = /[&"`{}\\#<>]/g;
+
+export interface PlantUMLBundleEntry {
+  /** Package nesting for the function, outermost first; may be empty. */
+  packagePath: string[];
+  ir: FlowchartIR;
+}
+
+/**
+ * Emits a PlantUML description of the flowchart with the same node/edge
+ * structure as the Mermaid output. Nodes are declared once with an alias and
+ * edges reference the aliases, so arbitrary graphs (loops, gotos, early
+ * returns) can be expressed without PlantUML's structured activity syntax.
+ */
+export class PlantUMLGenerator {
+  private sb = new StringBuilder();
+
+  constructor(private colors?: DiagramColors) {}
+
+  public generate(ir: FlowchartIR): string {
+    this.sb.clear();
+    const ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+
+    this.sb.appendLine("@startuml");
+    if (ir.title) {
+      this.sb.appendLine(`title ${this.escapeLabel(ir.title)}`);
+    }
+    if (ir.functionComplexity) {
+      this.sb.appendLine(
+        `' Cyclomatic Complexity: ${ir.functionComplexity.cyclomaticComplexity} (${ir.functionComplexity.rating})`
+      );
+    }
+    if (this.colors) {
+      this.appendSkinParams(this.colors);
+    }
+
+    this.appendGraph(ir, (id) => ids.get(id), "");
+    this.sb.appendLine("@enduml");
+    return this.sb.toString();
+  }
+
+  /**
+   * Emits several flowcharts into one PlantUML document: each function in
+   * a `frame` titled like its own diagram, nested in one `package` per
+   * entry of its package path (outermost first), so functions of the same
+   * module share a package block.
+   */
+  public generateBundle(entries: PlantUMLBundleEntry[]): string {
+    this.sb.clear();
+    const ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+
+    this.sb.appendLine("@startuml");
+    if (this.colors) {
+      this.appendSkinParams(this.colors);
+    }
+
+    let open: string[] = [];
+    entries.forEach((entry, index) => {
+      let shared = 0;
+      while (
+        shared < open.length &&
+        shared < entry.packagePath.length &&
+        open[shared] === entry.packagePath[shared]
+      ) {
+        shared++;
+      }
+      for (let depth = open.length; depth > shared; depth--) {
+        this.sb.appendLine(`${"  ".repeat(depth - 1)}}`);
+      }
+      for (let depth = shared; depth < entry.packagePath.length; depth++) {
+        this.sb.appendLine(
+          `${"  ".repeat(depth)}package "${this.escapeLabel(entry.packagePath[depth])}" {`
+        );
+      }
+      open = entry.packagePath;
+
+      const indent = "  ".repeat(open.length);
+      const title = entry.ir.title || `Function ${index + 1}`;
+      this.sb.appendLine(`${indent}frame "${this.escapeLabel(title)}" {`);
+      if (entry.ir.functionComplexity) {
+        this.sb.appendLine(
+          `${indent}  ' Cyclomatic Complexity: ${entry.ir.functionComplexity.cyclomaticComplexity} (${entry.ir.functionComplexity.rating})`
+        );
+      }
+      // Node IR ids repeat across functions; the prefix keeps aliases apart
+      this.appendGraph(entry.ir, (id) => ids.get(`f${index + 1}_${id}`), `${indent}  `);
+      this.sb.appendLine(`${indent}}`);
+    });
+    for (let depth = open.length; depth > 0; depth--) {
+      this.sb.appendLine(`${"  ".repeat(depth - 1)}}`);
+    }
+
+    this.sb.appendLine("@enduml");
+    return this.sb.toString();
+  }
+
+  private appendGraph(ir: FlowchartIR, alias: (id: string) => string, indent: string): void {
+    this.appendScopeBox(groupByScope(ir), alias, indent);
+
+    for (const edge of ir.edges) {
+      this.sb.append(indent);
+      this.sb.append(alias(edge.from));
+      // Dotted arrows mark loop back-edges; gray dashed ones def-use edges;
+      // bold ones the longest path; gray ones cold paths
+      const color = edge.diff
+        ? DIFF_COLORS[edge.diff].stroke
+        : edge.cold
+          ? COLD_EDGE_COLOR
+          : undefined;
+      const styles = [color, edge.diff || edge.onLongestPath ? "bold" : undefined].filter(
+        (style): style is string => style !== undefined
+      );
+      this.sb.append(
+        edge.edgeType === EdgeType.BACK
+          ? color
+            ? ` -[${color},dotted]-> `
+            : " ..> "
+          : edge.edgeType === EdgeType.DATAFLOW
+            ? " -[#8a8f98,dashed]-> "
+            : styles.length > 0
+              ? ` -[${styles.join(",")}]-> `
+              : " --> "
+      );
+      this.sb.append(alias(edge.to));
+      if (edge.label) {
+        this.sb.append(" : ");
+        this.sb.append(this.escapeLabel(edge.label));
+      }
+      this.sb.appendLine("");
+    }
+  }
+
+  /**
+   * Declares a scope's nodes, then its nested scopes as dashed rectangles.
+   * This is a description diagram, so the box is a `rectangle` container;
+   * `partition` only exists in activity diagrams.
+   */
+  private appendScopeBox(box: ScopeBox, alias: (id: string) => string, indent: string): void {
+    for (const node of box.nodes) {
+      this.appendNode(node, alias, indent);
+    }
+    for (const child of box.children) {
+      this.sb.appendLine(
+        `${indent}rectangle "${this.escapeLabel(child.scope!.label)}" as ${alias(child.scope!.id)} #line.dashed {`
+      );
+      this.appendScopeBox(child, alias, `${indent}  `);
+      this.sb.appendLine(`${indent}}`);
+    }
+  }
+
+  private appendNode(node: FlowchartNode, alias: (id: string) => string, indent: string): void {
+    this.sb.append(indent);
+    this.sb.append(this.getElementKeyword(node));
+    this.sb.append(' "');
+    this.sb.append(
+      node.label
+        .split("\n")
+        .map((line) => this.escapeLabel(line))
+        .join("\\n")
+    );
+    this.sb.append('" as ');
+    this.sb.append(alias(node.id));
+    const style = this.getNodeStyle(node);
+    if (style) {
+      this.sb.append(` #${style}`);
+    }
+    this.sb.appendLine("");
+  }
+
+  /**
+   * Inline element color for diff views and unreachable nodes, without the
+   * leading `#` (e.g. `e8f5e9;line:2e7d32`), or undefined for plain nodes.
+   */
+  private getNodeStyle(node: FlowchartNode): string | undefined {
+    const parts: string[] = [];
+    if (node.diff) {
+      const { fill, stroke } = DIFF_COLORS[node.diff];
+      parts.push(fill.slice(1));
+      if (!node.unreachable) {
+        parts.push(`line:${stroke.slice(1)}`);
+      }
+    }
+    if (node.unreachable) {
+      parts.push(`line:${UNREACHABLE_COLOR.slice(1)}`, "line.dashed");
+    }
+    return parts.length > 0 ? parts.join(";") : undefined;
+  }
+
+  private appendSkinParams(colors: DiagramColors): void {
+    this.sb.appendLine(`skinparam backgroundColor ${this.toPlantUMLColor(colors.background)}`);
+    this.sb.appendLine(`skinparam defaultFontColor ${this.toPlantUMLColor(colors.nodeText)}`);
+    this.sb.appendLine(`skinparam ArrowColor ${this.toPlantUMLColor(colors.edge)}`);
+    this.sb.appendLine(`skinparam ArrowFontColor ${this.toPlantUMLColor(colors.nodeText)}`);
+    for (const element of ["rectangle", "hexagon", "usecase"]) {
+      this.sb.appendLine(`skinparam ${element} {`);
+      this.sb.appendLine(`  BackgroundColor ${this.toPlantUMLColor(colors.nodeBackground)}`);
+      this.sb.appendLine(`  BorderColor ${this.toPlantUMLColor(colors.nodeBorder)}`);
+      this.sb.appendLine(`  FontColor ${this.toPlantUMLColor(colors.nodeText)}`);
+      this.sb.appendLine("}");
+    }
+  }
+
+  /**
+   * PlantUML's skinparam values take hex colors as written and named
+   * colors with a leading `#`.
+   */
+  private toPlantUMLColor(color: string): string {
+    return color.startsWith("#") ? color : `#${color}`;
+  }
+
+  /**
+   * Picks the PlantUML element used for a node: hexagons for branch
+   * conditions, ellipses for entry/exit points and rectangles otherwise.
+   */
+  private getElementKeyword(node: FlowchartNode): string {
+    switch (node.nodeType) {
+      case NodeType.DECISION:
+      case NodeType.LOOP_START:
+        return "hexagon";
+      case NodeType.ENTRY:
+      case NodeType.EXIT:
+        return "usecase";
+    }
+
+    switch (node.shape) {
+      case "diamond":
+        return "hexagon";
+      case "round":
+      case "stadium":
+        return "usecase";
+      case "rect":
+      default:
+        return "rectangle";
+    }
+  }
+
+  /**
+   * Makes a label safe inside a quoted PlantUML string or after an edge's
+   * `:`. IR labels carry Mermaid entity escapes, so those are decoded first;
+   * characters PlantUML gives meaning to are then written as numeric
+   * entities, which PlantUML renders as the literal character.
+   */
+  private escapeLabel(str: string): string {
+    if (!str) {
+      return "";
+    }
+    let text = StringProcessor.unescapeString(str)
+      .replace(/\r?\n/g, " ")
+      .trim();
+
+    // Truncate before encoding so an entity is never cut in half
+    const MAX_LABEL_LENGTH = 80;
+    if (text.length > MAX_LABEL_LENGTH) {
+      text = text.substring(0, MAX_LABEL_LENGTH - 3) + "...";
+    }
+    return text.replace(
+      PLANTUML_SIGNIFICANT_CHARS,
+      (char) => `&#${char.charCodeAt(0)};`
+    );
+  }
+}
+
+/**
+ * Lightweight structural check run before PlantUML text leaves the
+ * extension. Returns a list of problems; an empty list means the text is
+ * well-formed enough for PlantUML to attempt rendering it.
+ */
+export function validatePlantUML(code: string): string[] {
+  const errors: string[] = [];
+  const lines = code.split(/\r?\n/);
+  let depth = 0;
+  let blocks = 0;
+
+  lines.forEach((rawLine, index) => {
+    const line = rawLine.trim();
+    if (line.startsWith("@startuml")) {
+      if (depth > 0) {
+        errors.push(`Line ${index + 1}: @startuml inside an open @startuml block`);
+      }
+      depth++;
+      blocks++;
+    } else if (line.startsWith("@enduml")) {
+      if (depth === 0) {
+        errors.push(`Line ${index + 1}: @enduml without a matching @startuml`);
+      } else {
+        depth--;
+      }
+    } else if (line && !line.startsWith("'")) {
+      if ((line.match(/"/g) || []).length % 2 !== 0) {
+        errors.push(`Line ${index + 1}: unterminated string literal`);
+      }
+    }
+  });
+
+  if (blocks === 0) {
+    errors.push("Missing @startuml");
+  }
+  if (depth > 0) {
+    errors.push("Missing @enduml");
+  }
+  return errors;
+}
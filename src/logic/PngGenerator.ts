@@ -0,0 +1,303 @@
+import { FlowchartIR, NodeType } from "../ir/ir";
+import {
+  DiagramLayout,
+  EdgeRoute,
+  firstLineBaseline,
+  LayoutNode,
+  Point,
+  SvgGenerator,
+} from "./SvgGenerator";
+import {
+  COLD_EDGE_COLOR,
+  DiagramColors,
+  DIAGRAM_COLOR_THEMES,
+  DIFF_COLORS,
+  UNREACHABLE_COLOR,
+} from "./utils/DiagramColors";
+import { encodePng } from "./utils/PngEncoder";
+import { parseColor, Raster, RasterColor } from "./utils/Rasterizer";
+import { FONT_CAP_HEIGHT, measureText, textStrokes } from "./utils/StrokeFont";
+
+export const DEFAULT_PNG_DPI = 192;
+export const MIN_PNG_DPI = 24;
+export const MAX_PNG_DPI = 1200;
+
+// SVG user units are CSS pixels, 96 to the inch
+const CSS_DPI = 96;
+const MAX_PIXELS = 1e8;
+const STROKE_WIDTH = 1.2;
+const COLD_STROKE_WIDTH = 0.8;
+const CURVE_SEGMENTS = 24;
+const CORNER_SEGMENTS = 8;
+const DATAFLOW_COLOR = "#8a8f98";
+// Matches the SVG marker: 7 stroke widths long, tip at 90% of its length
+const ARROW_LENGTH = 7 * STROKE_WIDTH;
+const ARROW_OVERSHOOT = 0.1 * ARROW_LENGTH;
+
+export interface PngOptions {
+  /** Output resolution; 96 renders one pixel per SVG unit. */
+  dpi?: number;
+  /** Leave the canvas transparent instead of painting the background color. */
+  transparent?: boolean;
+}
+
+/**
+ * Rasterizes the built-in SVG layout to a PNG without a browser or any
+ * external renderer. Text uses a stroke font drawn at the target
+ * resolution, so it is as sharp at 300 DPI as the edges are.
+ */
+export class PngGenerator {
+  private readonly dpi: number;
+
+  constructor(
+    private colors?: DiagramColors,
+    private options: PngOptions = {}
+  ) {
+    this.dpi = options.dpi ?? DEFAULT_PNG_DPI;
+    if (!Number.isFinite(this.dpi) || this.dpi < MIN_PNG_DPI || this.dpi > MAX_PNG_DPI) {
+      throw new Error(`DPI must be between ${MIN_PNG_DPI} and ${MAX_PNG_DPI}, got ${this.dpi}`);
+    }
+  }
+
+  public generate(ir: FlowchartIR): Buffer {
+    const layout = new SvgGenerator(this.colors).layout(ir);
+    const scale = this.dpi / CSS_DPI;
+    const width = Math.max(1, Math.ceil(layout.width * scale));
+    const height = Math.max(1, Math.ceil(layout.height * scale));
+    if (width * height > MAX_PIXELS) {
+      throw new Error(
+        `The diagram is too large for a ${this.dpi} DPI PNG (${width}x${height} pixels); lower the DPI`
+      );
+    }
+
+    const palette = this.colors || DIAGRAM_COLOR_THEMES.light;
+    const raster = new Raster(width, height, scale);
+    if (!this.options.transparent) {
+      raster.fill(parseColor(palette.background));
+    }
+
+    this.drawEdges(raster, layout, palette);
+    for (const entry of layout.nodes) {
+      this.drawNode(raster, entry, layout, palette);
+    }
+    for (const label of layout.labels) {
+      const left = label.at.x - label.width / 2;
+      const top = label.at.y - label.height / 2;
+      raster.fillPolygon(
+        this.roundedRect(left, top, label.width, label.height, 3),
+        parseColor(palette.background, 0.85)
+      );
+      this.drawText(
+        raster,
+        label.text,
+        label.at.x,
+        label.at.y + layout.fontSize / 3,
+        label.width - 4,
+        layout.fontSize,
+        parseColor(palette.nodeText)
+      );
+    }
+
+    return encodePng(width, height, raster.toRgba8(), this.dpi);
+  }
+
+  private drawEdges(raster: Raster, layout: DiagramLayout, palette: DiagramColors): void {
+    const edgeColor = parseColor(palette.edge);
+    for (const { edge, kind, route } of layout.edges) {
+      const points = this.flatten(route);
+      const color = edge.diff
+        ? parseColor(DIFF_COLORS[edge.diff].stroke)
+        : kind === "dataflow"
+          ? parseColor(DATAFLOW_COLOR)
+          : edge.cold
+            ? parseColor(COLD_EDGE_COLOR)
+            : edgeColor;
+      const lines =
+        kind === "back"
+          ? this.dash(points, [5, 4])
+          : kind === "dataflow"
+            ? this.dash(points, [2, 3])
+            : [points];
+      const width = edge.onLongestPath ? 3 : edge.diff ? 2 : edge.cold ? COLD_STROKE_WIDTH : STROKE_WIDTH;
+      raster.strokePolylines(lines, width, color);
+      raster.fillPolygon(this.arrowHead(points), edge.diff || edge.cold ? color : edgeColor);
+    }
+  }
+
+  private drawNode(
+    raster: Raster,
+    entry: LayoutNode,
+    layout: DiagramLayout,
+    palette: DiagramColors
+  ): void {
+    const { x, y, width, height } = entry;
+    const left = x - width / 2;
+    let fill = palette.nodeBackground;
+    let border = palette.nodeBorder;
+    let text = palette.nodeText;
+    // Without a color map the kinds keep the SVG's tints
+    if (entry.node.diff) {
+      fill = DIFF_COLORS[entry.node.diff].fill;
+      border = DIFF_COLORS[entry.node.diff].stroke;
+      text = "#222222";
+    } else if (!this.colors && entry.shape === "diamond") {
+      fill = "#fff8e1";
+      border = "#b7791f";
+    } else if (!this.colors && entry.node.nodeType === NodeType.AWAIT) {
+      fill = "#f3e5f5";
+      border = "#7b1fa2";
+    } else if (!this.colors && entry.shape === "round") {
+      fill = "#e6f4ea";
+      border = "#2f855a";
+    }
+
+    const outline =
+      entry.shape === "diamond"
+        ? [
+            { x, y },
+            { x: left + width, y: y + height / 2 },
+            { x, y: y + height },
+            { x: left, y: y + height / 2 },
+          ]
+        : this.roundedRect(left, y, width, height, entry.shape === "round" ? height / 2 : 4);
+    raster.fillPolygon(outline, parseColor(fill));
+    const closed = [...outline, outline[0]];
+    const borderWidth = entry.node.diff ? 2 : STROKE_WIDTH;
+    if (entry.node.unreachable) {
+      raster.strokePolylines(this.dash(closed, [4, 3]), borderWidth, parseColor(UNREACHABLE_COLOR));
+    } else {
+      raster.strokePolylines([closed], borderWidth, parseColor(border));
+    }
+
+    // Diamonds fit their text inside the rhombus (see SvgGenerator.measureNode)
+    const textWidth = entry.shape === "diamond" ? (width - 28) / 1.2 : width - 14;
+    const baseline = firstLineBaseline(entry);
+    entry.lines.forEach((line, index) => {
+      this.drawText(
+        raster,
+        line,
+        x,
+        baseline + index * layout.lineHeight,
+        textWidth,
+        layout.fontSize,
+        parseColor(text)
+      );
+    });
+  }
+
+  /**
+   * Draws one centered line. Text wider than the space the layout reserved
+   * is condensed horizontally rather than allowed to overflow the shape.
+   */
+  private drawText(
+    raster: Raster,
+    text: string,
+    centerX: number,
+    baseline: number,
+    maxWidth: number,
+    fontSize: number,
+    color: RasterColor
+  ): void {
+    if (!text.trim()) {
+      return;
+    }
+    const unit = (fontSize * 0.7) / FONT_CAP_HEIGHT;
+    const naturalWidth = measureText(text) * unit;
+    const squeeze = maxWidth > 0 && naturalWidth > maxWidth ? maxWidth / naturalWidth : 1;
+    const left = centerX - (naturalWidth * squeeze) / 2;
+    const strokes = textStrokes(text).map((stroke) =>
+      stroke.map(([gx, gy]) => ({ x: left + gx * unit * squeeze, y: baseline - gy * unit }))
+    );
+    raster.strokePolylines(strokes, unit * 1.25, color);
+  }
+
+  private flatten(route: EdgeRoute): Point[] {
+    const { start, c1, c2, end } = route;
+    if (!c1 || !c2) {
+      return [start, end];
+    }
+    const points: Point[] = [];
+    for (let i = 0; i <= CURVE_SEGMENTS; i++) {
+      const t = i / CURVE_SEGMENTS;
+      const u = 1 - t;
+      points.push({
+        x: u * u * u * start.x + 3 * u * u * t * c1.x + 3 * u * t * t * c2.x + t * t * t * end.x,
+        y: u * u * u * start.y + 3 * u * u * t * c1.y + 3 * u * t * t * c2.y + t * t * t * end.y,
+      });
+    }
+    return points;
+  }
+
+  /** Splits a polyline into the "on" pieces of a dash pattern. */
+  private dash(points: Point[], pattern: [number, number]): Point[][] {
+    const dashes: Point[][] = [];
+    let on = true;
+    let remaining = pattern[0];
+    let current: Point[] = [points[0]];
+    for (let i = 0; i + 1 < points.length; i++) {
+      let from = points[i];
+      const to = points[i + 1];
+      let length = Math.hypot(to.x - from.x, to.y - from.y);
+      while (length > remaining) {
+        const t = remaining / length;
+        const split = { x: from.x + (to.x - from.x) * t, y: from.y + (to.y - from.y) * t };
+        if (on) {
+          current.push(split);
+          dashes.push(current);
+        }
+        current = [split];
+        on = !on;
+        length -= remaining;
+        remaining = pattern[on ? 0 : 1];
+        from = split;
+      }
+      remaining -= length;
+      if (on) {
+        current.push(to);
+      }
+    }
+    if (on && current.length > 1) {
+      dashes.push(current);
+    }
+    return dashes;
+  }
+
+  private arrowHead(points: Point[]): Point[] {
+    const end = points[points.length - 1];
+    // The direction of the last non-degenerate segment
+    let index = points.length - 2;
+    while (index > 0 && Math.hypot(end.x - points[index].x, end.y - points[index].y) < 0.01) {
+      index--;
+    }
+    const before = points[Math.max(0, index)];
+    const length = Math.hypot(end.x - before.x, end.y - before.y) || 1;
+    const dx = (end.x - before.x) / length;
+    const dy = (end.y - before.y) / length;
+    const tip = { x: end.x + dx * ARROW_OVERSHOOT, y: end.y + dy * ARROW_OVERSHOOT };
+    const base = { x: tip.x - dx * ARROW_LENGTH, y: tip.y - dy * ARROW_LENGTH };
+    const half = ARROW_LENGTH / 2;
+    return [
+      tip,
+      { x: base.x - dy * half, y: base.y + dx * half },
+      { x: base.x + dy * half, y: base.y - dx * half },
+    ];
+  }
+
+  private roundedRect(left: number, top: number, width: number, height: number, radius: number): Point[] {
+    const r = Math.min(radius, width / 2, height / 2);
+    const corners = [
+      { cx: left + width - r, cy: top + r, from: -90 },
+      { cx: left + width - r, cy: top + height - r, from: 0 },
+      { cx: left + r, cy: top + height - r, from: 90 },
+      { cx: left + r, cy: top + r, from: 180 },
+    ];
+    const points: Point[] = [];
+    for (const { cx, cy, from } of corners) {
+      for (let i = 0; i <= CORNER_SEGMENTS; i++) {
+        const angle = ((from + (90 * i) / CORNER_SEGMENTS) * Math.PI) / 180;
+        points.push({ x: cx + r * Math.cos(angle), y: cy + r * Math.sin(angle) });
+      }
+    }
+    return points;
+  }
+}
@@ -0,0 +1,89 @@
+import { CallSite, SequenceDiagramIR, SequenceMessage } from "../ir/ir";
+import { analyzeCode, findFunctionAtPosition, isPlaceholderIR, listCalls } from "./analyzer";
+
+/** Languages with `await`, the only ones a sequence diagram can be drawn for. */
+export const SEQUENCE_DIAGRAM_LANGUAGES: readonly string[] = [
+  "python",
+  "typescript",
+  "javascript",
+  "rust",
+];
+
+// Receivers that name the caller's own object rather than a participant
+const SELF_RECEIVERS: ReadonlySet<string> = new Set(["self", "this", "Self"]);
+
+/**
+ * Collects the awaited calls of one function (selected by name or position
+ * like a flowchart). Returns undefined when no function matches.
+ */
+export async function analyzeSequence(
+  sourceCode: string,
+  languageId: string,
+  functionName?: string,
+  position?: number
+): Promise<SequenceDiagramIR | undefined> {
+  const ir = await analyzeCode(sourceCode, languageId, functionName, position);
+  if (isPlaceholderIR(ir) || !ir.functionRange) {
+    return undefined;
+  }
+  const { start, end } = ir.functionRange;
+  const caller = await findFunctionAtPosition(sourceCode, languageId, start);
+  if (!caller) {
+    return undefined;
+  }
+  const calls = (await listCalls(sourceCode, languageId)).filter(
+    (call) => call.caller === caller && call.position >= start && call.end <= end
+  );
+  return buildSequenceDiagram(caller, calls);
+}
+
+/**
+ * Turns the awaited calls into messages in source order. A call to
+ * `self.client.get` is the message `get` to participant `client`; a plain
+ * `fetch_data()` or `self.fetch_data()` is `fetch_data` to `fetch_data`. An
+ * awaited call inside another one's arguments or receiver becomes a nested
+ * message of that call.
+ */
+export function buildSequenceDiagram(caller: string, calls: CallSite[]): SequenceDiagramIR {
+  const awaited = calls
+    .filter((call) => call.awaited)
+    .sort((a, b) => a.position - b.position || b.end - a.end);
+
+  const messages: SequenceMessage[] = [];
+  const participants: string[] = [];
+  // The calls whose span the next call may still be inside, outermost first
+  const open: { call: CallSite; message: SequenceMessage }[] = [];
+
+  for (const call of awaited) {
+    while (open.length > 0 && open[open.length - 1].call.end < call.end) {
+      open.pop();
+    }
+    const message = toMessage(call.callee);
+    if (message.participant !== caller && !participants.includes(message.participant)) {
+      participants.push(message.participant);
+    }
+    const parent = open[open.length - 1];
+    (parent ? parent.message.nested : messages).push(message);
+    open.push({ call, message });
+  }
+
+  return { caller, participants, messages };
+}
+
+function toMessage(callee: string): SequenceMessage {
+  // Drop argument lists of chained calls and generic arguments:
+  // `client.session(cfg).get` -> `client.session.get`
+  let path = callee;
+  for (let previous = ""; previous !== path; ) {
+    previous = path;
+    path = path.replace(/\([^()]*\)|(::)?<[^<>]*>/g, "");
+  }
+  const segments = path.replace(/\?/g, "").split(/::|\.|->/).filter(Boolean);
+  const label = segments.pop() || callee;
+  while (segments.length > 0 && SELF_RECEIVERS.has(segments[0])) {
+    segments.shift();
+  }
+  // The receiver's last part: `client` in `self.api.client.get`
+  const participant = segments.length > 0 ? segments[segments.length - 1] : label;
+  return { participant, label, nested: [] };
+}
@@ -0,0 +1,64 @@
+import { SequenceDiagramIR, SequenceMessage } from "../ir/ir";
+import { IdSanitizer, PLANTUML_RESERVED_WORDS } from "./utils/IdSanitizer";
+
+const PLANTUML_SIGNIFICANT_CHARS = /[&"`{}\\#<>]/g;
+
+/**
+ * Emits a PlantUML sequence diagram: the function is the first participant,
+ * every awaited call a message to its callee followed by a dashed return,
+ * with the callee activated in between. Nested awaits are sent by the
+ * callee whose call they are part of, inside its activation.
+ */
+export class SequenceDiagramGenerator {
+  private lines: string[] = [];
+  private ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+  private caller = { name: "", id: "" };
+
+  public generate(ir: SequenceDiagramIR): string {
+    this.lines = ["@startuml"];
+    this.ids = new IdSanitizer(PLANTUML_RESERVED_WORDS);
+    if (ir.title) {
+      this.lines.push(`title ${this.escape(ir.title)}`);
+    }
+
+    // The caller gets its own id even when a callee shares its name
+    const caller = this.ids.get(`caller:${ir.caller}`);
+    this.caller = { name: ir.caller, id: caller };
+    this.lines.push(`participant "${this.escape(ir.caller)}" as ${caller}`);
+    for (const participant of ir.participants) {
+      this.lines.push(`participant "${this.escape(participant)}" as ${this.participantId(participant)}`);
+    }
+
+    if (ir.messages.length === 0) {
+      this.lines.push(`note over ${caller} : no awaited calls`);
+    }
+    for (const message of ir.messages) {
+      this.appendMessage(caller, message, "");
+    }
+    this.lines.push("@enduml");
+    return this.lines.join("\n") + "\n";
+  }
+
+  private appendMessage(from: string, message: SequenceMessage, indent: string): void {
+    // A recursive call stays on the caller's lifeline
+    const to =
+      message.participant === this.caller.name
+        ? this.caller.id
+        : this.participantId(message.participant);
+    this.lines.push(`${indent}${from} -> ${to} : ${this.escape(message.label)}`);
+    this.lines.push(`${indent}activate ${to}`);
+    for (const nested of message.nested) {
+      this.appendMessage(to, nested, indent + "  ");
+    }
+    this.lines.push(`${indent}${to} --> ${from}`);
+    this.lines.push(`${indent}deactivate ${to}`);
+  }
+
+  private participantId(name: string): string {
+    return this.ids.get(`participant:${name}`);
+  }
+
+  private escape(text: string): string {
+    return text.replace(PLANTUML_SIGNIFICANT_CHARS, (char) => `&#${char.charCodeAt(0)};`);
+  }
+}
@@ -0,0 +1,624 @@
+import {
+  FlowchartIR,
+  FlowchartEdge,
+  FlowchartNode,
+  NodeType,
+  EdgeType,
+} from "../ir/ir";
+import { StringProcessor } from "./utils/StringProcessor";
+import {
+  COLD_EDGE_COLOR,
+  DiagramColors,
+  DIAGRAM_COLOR_THEMES,
+  DIFF_COLORS,
+  UNREACHABLE_COLOR,
+} from "./utils/DiagramColors";
+
+// Optimized string building
+class StringBuilder {
+  private parts: string[] = [];
+
+  append(str: string): void {
+    this.parts.push(str);
+  }
+
+  appendLine(str: string): void {
+    this.parts.push(str, "\n");
+  }
+
+  toString(): string {
+    return this.parts.join("");
+  }
+
+  clear(): void {
+    this.parts.length = 0;
+  }
+}
+
+const FONT_SIZE = 13;
+const LINE_HEIGHT = 16;
+// Average glyph width for the sans-serif stack at FONT_SIZE
+const CHAR_WIDTH = 7.2;
+const NODE_PADDING_X = 14;
+const NODE_PADDING_Y = 10;
+const NODE_GAP = 32;
+const LAYER_GAP = 56;
+const BACK_EDGE_GAP = 14;
+const MARGIN = 24;
+const ORDERING_SWEEPS = 4;
+
+export type NodeShape = "rect" | "round" | "diamond";
+
+export interface LayoutNode {
+  node: FlowchartNode;
+  lines: string[];
+  shape: NodeShape;
+  layer: number;
+  order: number;
+  x: number; // center
+  y: number; // top
+  width: number;
+  height: number;
+}
+
+export interface Point {
+  x: number;
+  y: number;
+}
+
+/** A straight segment, or a cubic Bezier when the control points are set. */
+export interface EdgeRoute {
+  start: Point;
+  c1?: Point;
+  c2?: Point;
+  end: Point;
+  labelAt: Point;
+}
+
+export type LayoutEdgeKind = "normal" | "back" | "dataflow";
+
+export interface LayoutEdgeLabel {
+  text: string;
+  at: Point;
+  width: number;
+  height: number;
+}
+
+/**
+ * Positioned nodes and routed edges, shared by the SVG document and the PNG
+ * rasterizer so both draw the same picture.
+ */
+export interface DiagramLayout {
+  width: number;
+  height: number;
+  fontSize: number;
+  lineHeight: number;
+  nodes: LayoutNode[];
+  edges: { edge: FlowchartEdge; kind: LayoutEdgeKind; route: EdgeRoute }[];
+  labels: LayoutEdgeLabel[];
+}
+
+/** Baseline of a node's first text line, so the block is vertically centered. */
+export function firstLineBaseline(entry: LayoutNode): number {
+  const { y, height, lines } = entry;
+  return y + height / 2 - ((lines.length - 1) * LINE_HEIGHT) / 2 + FONT_SIZE / 3;
+}
+
+/**
+ * Renders the flowchart to a standalone SVG document without any external
+ * renderer. Nodes are placed with a layered (Sugiyama-style) layout of the
+ * graph's forward edges; loop back-edges are drawn dashed around the right
+ * side. Labels are real `<text>` elements, so they stay selectable and
+ * searchable in the exported file.
+ */
+export class SvgGenerator {
+  private sb = new StringBuilder();
+
+  constructor(private colors?: DiagramColors) {}
+
+  public generate(ir: FlowchartIR): string {
+    this.sb.clear();
+    const { width, height, nodes, edges, labels } = this.layout(ir);
+
+    this.sb.appendLine('<?xml version="1.0" encoding="UTF-8"?>');
+    this.sb.appendLine(
+      `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${height}" viewBox="0 0 ${width} ${height}">`
+    );
+    if (ir.title) {
+      this.sb.appendLine(`  <title>${this.escapeXml(this.plainText(ir.title))}</title>`);
+    }
+    this.appendDefs(
+      ir.nodes.some((node) => node.diff) || ir.edges.some((edge) => edge.diff),
+      ir.edges.some((edge) => edge.onLongestPath),
+      ir.edges.some((edge) => edge.cold)
+    );
+    const background = (this.colors || DIAGRAM_COLOR_THEMES.light).background;
+    this.sb.appendLine(`  <rect width="${width}" height="${height}" fill="${background}"/>`);
+
+    this.sb.appendLine('  <g class="edges">');
+    for (const { edge, kind, route } of edges) {
+      let className = kind === "normal" ? "edge" : `edge ${kind}`;
+      if (edge.diff) {
+        className += ` diff-${edge.diff}`;
+      }
+      if (edge.onLongestPath) {
+        className += " longest";
+      }
+      if (edge.cold) {
+        className += " cold";
+      }
+      this.sb.appendLine(
+        `    <path class="${className}" d="${this.pathData(route)}" marker-end="url(#arrow)"/>`
+      );
+    }
+    this.sb.appendLine("  </g>");
+
+    this.sb.appendLine('  <g class="nodes">');
+    for (const entry of nodes) {
+      this.appendNode(entry);
+    }
+    this.sb.appendLine("  </g>");
+
+    this.sb.appendLine('  <g class="edge-labels">');
+    for (const label of labels) {
+      this.appendEdgeLabel(label);
+    }
+    this.sb.appendLine("  </g>");
+
+    this.sb.appendLine("</svg>");
+    return this.sb.toString();
+  }
+
+  /** Lays the graph out without emitting anything. */
+  public layout(ir: FlowchartIR): DiagramLayout {
+    const layout = new Map<string, LayoutNode>();
+    for (const node of ir.nodes) {
+      layout.set(node.id, this.measureNode(node));
+    }
+    const edges = ir.edges.filter(
+      (edge) => layout.has(edge.from) && layout.has(edge.to)
+    );
+    const backEdges = this.findBackEdges(ir, edges);
+    const forwardEdges = edges.filter((edge) => !backEdges.has(edge));
+
+    const layers = this.assignLayers(ir.nodes, forwardEdges, layout);
+    this.orderLayers(layers, forwardEdges, layout);
+    const size = this.positionNodes(layers, forwardEdges, layout);
+
+    // Back-edges loop around the right of everything they span
+    const contentRight = size.width;
+    const backEdgeCount = edges.filter((edge) => backEdges.has(edge)).length;
+
+    const routed: DiagramLayout["edges"] = [];
+    const labels: LayoutEdgeLabel[] = [];
+    let backEdgeIndex = 0;
+    for (const edge of edges) {
+      const from = layout.get(edge.from)!;
+      const to = layout.get(edge.to)!;
+      const isBack = backEdges.has(edge);
+      const route = isBack
+        ? this.routeBackEdge(
+            from,
+            to,
+            contentRight + MARGIN + ++backEdgeIndex * BACK_EDGE_GAP
+          )
+        : this.routeForwardEdge(from, to);
+      const kind: LayoutEdgeKind =
+        edge.edgeType === EdgeType.DATAFLOW ? "dataflow" : isBack ? "back" : "normal";
+      routed.push({ edge, kind, route });
+      if (edge.label) {
+        const text = this.plainText(edge.label);
+        labels.push({
+          text,
+          at: route.labelAt,
+          width: text.length * CHAR_WIDTH + 8,
+          height: LINE_HEIGHT + 2,
+        });
+      }
+    }
+
+    return {
+      width: contentRight + backEdgeCount * BACK_EDGE_GAP + MARGIN * 2,
+      height: size.height + MARGIN * 2,
+      fontSize: FONT_SIZE,
+      lineHeight: LINE_HEIGHT,
+      nodes: ir.nodes.map((node) => layout.get(node.id)!),
+      edges: routed,
+      labels,
+    };
+  }
+
+  private measureNode(node: FlowchartNode): LayoutNode {
+    const lines = this.plainText(node.label).split("\n");
+    const textWidth = Math.max(...lines.map((line) => line.length)) * CHAR_WIDTH;
+    const textHeight = lines.length * LINE_HEIGHT;
+    const shape = this.getShape(node);
+
+    let width = textWidth + NODE_PADDING_X * 2;
+    let height = textHeight + NODE_PADDING_Y * 2;
+    if (shape === "diamond") {
+      // The text box must fit inside the rhombus
+      width = textWidth * 1.4 + NODE_PADDING_X * 2;
+      height = textHeight * 1.6 + NODE_PADDING_Y * 2;
+    }
+
+    return {
+      node,
+      lines,
+      shape,
+      layer: 0,
+      order: 0,
+      x: 0,
+      y: 0,
+      width: Math.ceil(Math.max(width, 40)),
+      height: Math.ceil(height),
+    };
+  }
+
+  private getShape(node: FlowchartNode): NodeShape {
+    switch (node.nodeType) {
+      case NodeType.DECISION:
+      case NodeType.LOOP_START:
+        return "diamond";
+      case NodeType.ENTRY:
+      case NodeType.EXIT:
+        return "round";
+    }
+
+    switch (node.shape) {
+      case "diamond":
+        return "diamond";
+      case "round":
+      case "stadium":
+        return "round";
+      case "rect":
+      default:
+        return "rect";
+    }
+  }
+
+  /**
+   * Loop back-edges are marked by the parsers; any remaining cycles (gotos,
+   * languages without back-edge marking) are broken at the edges a
+   * depth-first search from the entry finds closing a cycle.
+   */
+  private findBackEdges(
+    ir: FlowchartIR,
+    edges: FlowchartEdge[]
+  ): Set<FlowchartEdge> {
+    // Def-use edges are routed like back-edges so they never shape the layout
+    const backEdges = new Set<FlowchartEdge>(
+      edges.filter(
+        (edge) => edge.edgeType === EdgeType.BACK || edge.edgeType === EdgeType.DATAFLOW
+      )
+    );
+
+    const outgoing = new Map<string, FlowchartEdge[]>();
+    for (const edge of edges) {
+      if (!backEdges.has(edge)) {
+        const list = outgoing.get(edge.from) || [];
+        list.push(edge);
+        outgoing.set(edge.from, list);
+      }
+    }
+
+    const state = new Map<string, "active" | "done">();
+    const visit = (id: string) => {
+      state.set(id, "active");
+      for (const edge of outgoing.get(id) || []) {
+        const targetState = state.get(edge.to);
+        if (targetState === "active") {
+          backEdges.add(edge);
+        } else if (!targetState) {
+          visit(edge.to);
+        }
+      }
+      state.set(id, "done");
+    };
+
+    const roots = ir.entryNodeId ? [ir.entryNodeId] : [];
+    roots.push(...ir.nodes.map((node) => node.id));
+    for (const id of roots) {
+      if (!state.has(id) && ir.nodes.some((node) => node.id === id)) {
+        visit(id);
+      }
+    }
+    return backEdges;
+  }
+
+  /**
+   * Longest-path layering: every node sits one layer below its lowest
+   * predecessor, so forward edges always point down.
+   */
+  private assignLayers(
+    nodes: FlowchartNode[],
+    forwardEdges: FlowchartEdge[],
+    layout: Map<string, LayoutNode>
+  ): LayoutNode[][] {
+    const indegree = new Map<string, number>(nodes.map((node) => [node.id, 0]));
+    for (const edge of forwardEdges) {
+      indegree.set(edge.to, (indegree.get(edge.to) || 0) + 1);
+    }
+
+    const queue = nodes
+      .filter((node) => indegree.get(node.id) === 0)
+      .map((node) => node.id);
+    while (queue.length > 0) {
+      const id = queue.shift()!;
+      const layer = layout.get(id)!.layer;
+      for (const edge of forwardEdges) {
+        if (edge.from !== id) {
+          continue;
+        }
+        const target = layout.get(edge.to)!;
+        target.layer = Math.max(target.layer, layer + 1);
+        const remaining = indegree.get(edge.to)! - 1;
+        indegree.set(edge.to, remaining);
+        if (remaining === 0) {
+          queue.push(edge.to);
+        }
+      }
+    }
+
+    const layers: LayoutNode[][] = [];
+    for (const node of nodes) {
+      const entry = layout.get(node.id)!;
+      (layers[entry.layer] = layers[entry.layer] || []).push(entry);
+    }
+    return layers.filter((layer) => layer && layer.length > 0);
+  }
+
+  /**
+   * Reduces edge crossings with alternating barycenter sweeps: each node
+   * moves toward the average position of its neighbours in the adjacent
+   * layer.
+   */
+  private orderLayers(
+    layers: LayoutNode[][],
+    forwardEdges: FlowchartEdge[],
+    layout: Map<string, LayoutNode>
+  ): void {
+    layers.forEach((layer) => layer.forEach((entry, index) => (entry.order = index)));
+
+    const neighbours = (entry: LayoutNode, downward: boolean): LayoutNode[] =>
+      forwardEdges
+        .filter((edge) =>
+          downward ? edge.to === entry.node.id : edge.from === entry.node.id
+        )
+        .map((edge) => layout.get(downward ? edge.from : edge.to)!);
+
+    for (let sweep = 0; sweep < ORDERING_SWEEPS; sweep++) {
+      const downward = sweep % 2 === 0;
+      const sequence = downward ? layers.slice(1) : layers.slice(0, -1).reverse();
+      for (const layer of sequence) {
+        const barycenter = new Map<LayoutNode, number>();
+        for (const entry of layer) {
+          const adjacent = neighbours(entry, downward);
+          barycenter.set(
+            entry,
+            adjacent.length > 0
+              ? adjacent.reduce((sum, n) => sum + n.order, 0) / adjacent.length
+              : entry.order
+          );
+        }
+        // Stable sort keeps the source order for ties
+        layer.sort((a, b) => barycenter.get(a)! - barycenter.get(b)!);
+        layer.forEach((entry, index) => (entry.order = index));
+      }
+    }
+  }
+
+  /**
+   * Places each node under its predecessors where the layer has room,
+   * then shifts the drawing into the positive quadrant.
+   */
+  private positionNodes(
+    layers: LayoutNode[][],
+    forwardEdges: FlowchartEdge[],
+    layout: Map<string, LayoutNode>
+  ): { width: number; height: number } {
+    let y = MARGIN;
+    for (const layer of layers) {
+      const layerHeight = Math.max(...layer.map((entry) => entry.height));
+      let right = -Infinity;
+      for (const entry of layer) {
+        const predecessors = forwardEdges
+          .filter((edge) => edge.to === entry.node.id)
+          .map((edge) => layout.get(edge.from)!)
+          .filter((pred) => pred.layer < entry.layer);
+        const minX = Number.isFinite(right)
+          ? right + NODE_GAP + entry.width / 2
+          : -Infinity;
+        const desired =
+          predecessors.length > 0
+            ? predecessors.reduce((sum, pred) => sum + pred.x, 0) /
+              predecessors.length
+            : Math.max(minX, 0);
+        entry.x = Math.max(desired, minX);
+        entry.y = y + (layerHeight - entry.height) / 2;
+        right = entry.x + entry.width / 2;
+      }
+      y += layerHeight + LAYER_GAP;
+    }
+
+    const all = layers.flat();
+    if (all.length === 0) {
+      return { width: 0, height: 0 };
+    }
+    const left = Math.min(...all.map((entry) => entry.x - entry.width / 2));
+    for (const entry of all) {
+      entry.x += MARGIN - left;
+    }
+    return {
+      width: Math.max(...all.map((entry) => entry.x + entry.width / 2)) - MARGIN,
+      height: y - LAYER_GAP - MARGIN,
+    };
+  }
+
+  private routeForwardEdge(from: LayoutNode, to: LayoutNode): EdgeRoute {
+    const start = { x: from.x, y: from.y + from.height };
+    const end = { x: to.x, y: to.y };
+    if (Math.abs(start.x - end.x) < 1) {
+      return { start, end, labelAt: { x: start.x, y: (start.y + end.y) / 2 } };
+    }
+    const bend = (end.y - start.y) / 2;
+    const c1 = { x: start.x, y: start.y + bend };
+    const c2 = { x: end.x, y: end.y - bend };
+    return { start, c1, c2, end, labelAt: this.bezierMidpoint(start, c1, c2, end) };
+  }
+
+  private routeBackEdge(from: LayoutNode, to: LayoutNode, laneX: number): EdgeRoute {
+    const start = { x: from.x + from.width / 2, y: from.y + from.height / 2 };
+    const end = { x: to.x + to.width / 2, y: to.y + to.height / 2 };
+    const c1 = { x: laneX, y: start.y };
+    const c2 = { x: laneX, y: end.y };
+    return { start, c1, c2, end, labelAt: this.bezierMidpoint(start, c1, c2, end) };
+  }
+
+  private pathData(route: EdgeRoute): string {
+    return route.c1 && route.c2
+      ? `M ${this.fmt(route.start)} C ${this.fmt(route.c1)} ${this.fmt(route.c2)} ${this.fmt(route.end)}`
+      : `M ${this.fmt(route.start)} L ${this.fmt(route.end)}`;
+  }
+
+  private bezierMidpoint(p0: Point, p1: Point, p2: Point, p3: Point): Point {
+    return {
+      x: (p0.x + 3 * p1.x + 3 * p2.x + p3.x) / 8,
+      y: (p0.y + 3 * p1.y + 3 * p2.y + p3.y) / 8,
+    };
+  }
+
+  private appendDefs(isDiff: boolean, hasLongestPath: boolean, hasColdPaths: boolean): void {
+    // A color map replaces the per-kind tints with a single node palette
+    const colors = this.colors || DIAGRAM_COLOR_THEMES.light;
+    this.sb.appendLine("  <defs>");
+    this.sb.appendLine(
+      '    <marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="7" markerHeight="7" orient="auto">'
+    );
+    this.sb.appendLine(`      <path d="M 0 0 L 10 5 L 0 10 z" fill="${colors.edge}"/>`);
+    this.sb.appendLine("    </marker>");
+    this.sb.appendLine("    <style>");
+    this.sb.appendLine(
+      `      text { font-family: Helvetica, Arial, sans-serif; font-size: ${FONT_SIZE}px; fill: ${colors.nodeText}; }`
+    );
+    this.sb.appendLine(
+      `      .node-shape { fill: ${colors.nodeBackground}; stroke: ${colors.nodeBorder}; stroke-width: 1.2; }`
+    );
+    if (!this.colors) {
+      this.sb.appendLine("      .decision .node-shape { fill: #fff8e1; stroke: #b7791f; }");
+      this.sb.appendLine("      .terminal .node-shape { fill: #e6f4ea; stroke: #2f855a; }");
+      this.sb.appendLine("      .suspend .node-shape { fill: #f3e5f5; stroke: #7b1fa2; }");
+    }
+    if (isDiff) {
+      for (const [status, { fill, stroke }] of Object.entries(DIFF_COLORS)) {
+        this.sb.appendLine(
+          `      .diff-${status} .node-shape { fill: ${fill}; stroke: ${stroke}; stroke-width: 2; }`
+        );
+        this.sb.appendLine(`      .diff-${status} text { fill: #222222; }`);
+      }
+    }
+    this.sb.appendLine(
+      `      .unreachable .node-shape { stroke: ${UNREACHABLE_COLOR}; stroke-dasharray: 4 3; }`
+    );
+    this.sb.appendLine(`      .edge { fill: none; stroke: ${colors.edge}; stroke-width: 1.2; }`);
+    this.sb.appendLine("      .edge.back { stroke-dasharray: 5 4; }");
+    this.sb.appendLine("      .edge.dataflow { stroke: #8a8f98; stroke-dasharray: 2 3; }");
+    if (hasColdPaths) {
+      this.sb.appendLine(`      .edge.cold { stroke: ${COLD_EDGE_COLOR}; stroke-width: 0.8; }`);
+    }
+    if (isDiff) {
+      for (const [status, { stroke }] of Object.entries(DIFF_COLORS)) {
+        this.sb.appendLine(`      .edge.diff-${status} { stroke: ${stroke}; stroke-width: 2; }`);
+      }
+    }
+    if (hasLongestPath) {
+      this.sb.appendLine("      .edge.longest { stroke-width: 3; }");
+    }
+    this.sb.appendLine(`      .edge-label-bg { fill: ${colors.background}; opacity: 0.85; }`);
+    this.sb.appendLine("    </style>");
+    this.sb.appendLine("  </defs>");
+  }
+
+  private appendNode(entry: LayoutNode): void {
+    const { x, y, width, height } = entry;
+    const left = x - width / 2;
+    let className =
+      entry.shape === "diamond"
+        ? "node decision"
+        : entry.node.nodeType === NodeType.AWAIT
+          ? "node suspend"
+          : entry.shape === "round"
+            ? "node terminal"
+            : "node";
+    if (entry.node.diff) {
+      className += ` diff-${entry.node.diff}`;
+    }
+    if (entry.node.unreachable) {
+      className += " unreachable";
+    }
+
+    this.sb.appendLine(
+      `    <g class="${className}" data-node-id="${this.escapeXml(entry.node.id)}">`
+    );
+    if (entry.shape === "diamond") {
+      const points = [
+        `${this.num(x)},${this.num(y)}`,
+        `${this.num(left + width)},${this.num(y + height / 2)}`,
+        `${this.num(x)},${this.num(y + height)}`,
+        `${this.num(left)},${this.num(y + height / 2)}`,
+      ].join(" ");
+      this.sb.appendLine(`      <polygon class="node-shape" points="${points}"/>`);
+    } else {
+      const radius = entry.shape === "round" ? height / 2 : 4;
+      this.sb.appendLine(
+        `      <rect class="node-shape" x="${this.num(left)}" y="${this.num(y)}" width="${width}" height="${height}" rx="${this.num(radius)}"/>`
+      );
+    }
+
+    const firstBaseline = firstLineBaseline(entry);
+    this.sb.append(
+      `      <text x="${this.num(x)}" y="${this.num(firstBaseline)}" text-anchor="middle">`
+    );
+    entry.lines.forEach((line, index) => {
+      this.sb.append(
+        `<tspan x="${this.num(x)}" dy="${index === 0 ? 0 : LINE_HEIGHT}">${this.escapeXml(line)}</tspan>`
+      );
+    });
+    this.sb.appendLine("</text>");
+    this.sb.appendLine("    </g>");
+  }
+
+  private appendEdgeLabel({ text, at, width, height }: LayoutEdgeLabel): void {
+    this.sb.appendLine(
+      `    <rect class="edge-label-bg" x="${this.num(at.x - width / 2)}" y="${this.num(at.y - height / 2)}" width="${this.num(width)}" height="${height}" rx="3"/>`
+    );
+    this.sb.appendLine(
+      `    <text x="${this.num(at.x)}" y="${this.num(at.y + FONT_SIZE / 3)}" text-anchor="middle">${this.escapeXml(text)}</text>`
+    );
+  }
+
+  /**
+   * IR labels carry Mermaid entity escapes; SVG text wants the original
+   * characters, XML-escaped.
+   */
+  private plainText(label: string): string {
+    return StringProcessor.unescapeString(label || "").replace(/\r\n/g, "\n");
+  }
+
+  private escapeXml(str: string): string {
+    return str
+      .replace(/&/g, "&amp;")
+      .replace(/</g, "&lt;")
+      .replace(/>/g, "&gt;")
+      .replace(/"/g, "&quot;")
+      .replace(/'/g, "&apos;");
+  }
+
+  private fmt(point: Point): string {
+    return `${this.num(point.x)} ${this.num(point.y)}`;
+  }
+
+  private num(value: number): string {
+    return String(Math.round(value * 10) / 10);
+  }
+}
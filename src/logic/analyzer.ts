@@ -1,12 +1,90 @@
-import * as vscode from "vscode";
-import { FlowchartIR } from "../ir/ir";
-import { analyzePythonCode } from "./language-services/python";
-import { analyzeTypeScriptCode } from "./language-services/typescript";
-import { analyzeJavaCode } from "./language-services/java";
-import { analyzeCppCode } from "./language-services/cpp";
-import { analyzeCCode } from "./language-services/c";
-import { analyzeRustCode } from "./language-services/rust";
-import { analyzeGoCode } from "./language-services/go";
+import { CallSite, FlowchartIR, ClassDiagramIR, FunctionDefinition, ParseErrorInfo } from "../ir/ir";
+import { AbstractParser } from "./common/AbstractParser";
+import {
+  analyzePythonCode,
+  findPythonFunctionAtPosition,
+  findPythonParseErrors,
+  listPythonCalls,
+  listPythonFunctions,
+  listPythonFunctionDefinitions,
+} from "./language-services/python";
+import {
+  analyzeTypeScriptCode,
+  findTypeScriptFunctionAtPosition,
+  findTypeScriptParseErrors,
+  listTypeScriptCalls,
+  listTypeScriptFunctions,
+  listTypeScriptFunctionDefinitions,
+} from "./language-services/typescript";
+import {
+  analyzeJavaCode,
+  findJavaFunctionAtPosition,
+  findJavaParseErrors,
+  listJavaCalls,
+  listJavaFunctions,
+  listJavaFunctionDefinitions,
+} from "./language-services/java";
+import {
+  analyzeCppCode,
+  findCppFunctionAtPosition,
+  findCppParseErrors,
+  listCppCalls,
+  listCppFunctions,
+  listCppFunctionDefinitions,
+} from "./language-services/cpp";
+import {
+  analyzeCCode,
+  findCFunctionAtPosition,
+  findCParseErrors,
+  listCCalls,
+  listCFunctions,
+  listCFunctionDefinitions,
+} from "./language-services/c";
+import {
+  analyzeRustCode,
+  analyzeRustTypes,
+  findRustFunctionAtPosition,
+  findRustParseErrors,
+  listRustCalls,
+  listRustFunctions,
+  listRustFunctionDefinitions,
+} from "./language-services/rust";
+import {
+  analyzeGoCode,
+  findGoFunctionAtPosition,
+  findGoParseErrors,
+  listGoCalls,
+  listGoFunctions,
+  listGoFunctionDefinitions,
+} from "./language-services/go";
+
+/**
+ * Language identifiers accepted by analyzeCode (VS Code language IDs).
+ */
+export const SUPPORTED_LANGUAGES = [
+  "python",
+  "typescript",
+  "javascript",
+  "java",
+  "cpp",
+  "c",
+  "rust",
+  "go",
+] as const;
+
+export type SupportedLanguage = (typeof SUPPORTED_LANGUAGES)[number];
+
+export function isSupportedLanguage(languageId: string): languageId is SupportedLanguage {
+  return (SUPPORTED_LANGUAGES as readonly string[]).includes(languageId);
+}
+
+/**
+ * Parsers return a single explanatory node instead of throwing when no
+ * function matches the position or name; this detects that placeholder.
+ */
+export function isPlaceholderIR(ir: FlowchartIR): boolean {
+  return !ir.functionRange && !ir.entryNodeId;
+}
 
 /**
  * Analyzes the given source code and generates a flowchart.
@@ -22,14 +100,18 @@ export async function analyzeCode(
   functionName?: string,
   position?: number
 ): Promise<FlowchartIR> {
+  // Without a name, fall back to the start of the file as before
+  const effectivePosition =
+    position === undefined && !functionName ? 0 : position;
+
   switch (languageId) {
     case "python":
-      return await analyzePythonCode(sourceCode, position || 0);
+      return await analyzePythonCode(sourceCode, effectivePosition, functionName);
     case "typescript":
     case "javascript":
-      return await analyzeTypeScriptCode(sourceCode, position || 0);
+      return await analyzeTypeScriptCode(sourceCode, effectivePosition, functionName);
     case "java":
-      return await analyzeJavaCode(sourceCode, position || 0);
+      return await analyzeJavaCode(sourceCode, effectivePosition, functionName);
     case "cpp":
       return analyzeCppCode(sourceCode, functionName, position);
     case "c":
@@ -42,3 +124,253 @@ export async function analyzeCode(
       throw new Error(`Unsupported language: ${languageId}`);
   }
 }
+
+/**
+ * Lists the names of the functions defined in the source code.
+ * @param sourceCode - The source code to scan.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ * @returns Function names in the order the parser reports them.
+ */
+export async function listFunctions(
+  sourceCode: string,
+  languageId: string
+): Promise<string[]> {
+  switch (languageId) {
+    case "python":
+      return await listPythonFunctions(sourceCode);
+    case "typescript":
+    case "javascript":
+      return await listTypeScriptFunctions(sourceCode);
+    case "java":
+      return await listJavaFunctions(sourceCode);
+    case "cpp":
+      return listCppFunctions(sourceCode);
+    case "c":
+      return listCFunctions(sourceCode);
+    case "rust":
+      return listRustFunctions(sourceCode);
+    case "go":
+      return listGoFunctions(sourceCode);
+    default:
+      throw new Error(`Unsupported language: ${languageId}`);
+  }
+}
+
+/**
+ * Lists the functions defined in the source code with their start offsets.
+ * @param sourceCode - The source code to scan.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ * @returns Definitions in the order and under the names listFunctions gives.
+ */
+export async function listFunctionDefinitions(
+  sourceCode: string,
+  languageId: string
+): Promise<FunctionDefinition[]> {
+  switch (languageId) {
+    case "python":
+      return await listPythonFunctionDefinitions(sourceCode);
+    case "typescript":
+    case "javascript":
+      return await listTypeScriptFunctionDefinitions(sourceCode);
+    case "java":
+      return await listJavaFunctionDefinitions(sourceCode);
+    case "cpp":
+      return listCppFunctionDefinitions(sourceCode);
+    case "c":
+      return listCFunctionDefinitions(sourceCode);
+    case "rust":
+      return listRustFunctionDefinitions(sourceCode);
+    case "go":
+      return listGoFunctionDefinitions(sourceCode);
+    default:
+      throw new Error(`Unsupported language: ${languageId}`);
+  }
+}
+
+/**
+ * Lists the calls made inside the functions of the source code.
+ * @param sourceCode - The source code to scan.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ * @returns Call sites in source order, each with the caller as listFunctions names it.
+ */
+export async function listCalls(
+  sourceCode: string,
+  languageId: string
+): Promise<CallSite[]> {
+  switch (languageId) {
+    case "python":
+      return await listPythonCalls(sourceCode);
+    case "typescript":
+    case "javascript":
+      return await listTypeScriptCalls(sourceCode);
+    case "java":
+      return await listJavaCalls(sourceCode);
+    case "cpp":
+      return listCppCalls(sourceCode);
+    case "c":
+      return listCCalls(sourceCode);
+    case "rust":
+      return listRustCalls(sourceCode);
+    case "go":
+      return listGoCalls(sourceCode);
+    default:
+      throw new Error(`Unsupported language: ${languageId}`);
+  }
+}
+
+/**
+ * Returns the name of the function containing the offset, if any.
+ * @param sourceCode - The source code to scan.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ * @param position - Character offset into the source.
+ */
+export async function findFunctionAtPosition(
+  sourceCode: string,
+  languageId: string,
+  position: number
+): Promise<string | undefined> {
+  switch (languageId) {
+    case "python":
+      return await findPythonFunctionAtPosition(sourceCode, position);
+    case "typescript":
+    case "javascript":
+      return await findTypeScriptFunctionAtPosition(sourceCode, position);
+    case "java":
+      return await findJavaFunctionAtPosition(sourceCode, position);
+    case "cpp":
+      return findCppFunctionAtPosition(sourceCode, position);
+    case "c":
+      return findCFunctionAtPosition(sourceCode, position);
+    case "rust":
+      return findRustFunctionAtPosition(sourceCode, position);
+    case "go":
+      return findGoFunctionAtPosition(sourceCode, position);
+    default:
+      throw new Error(`Unsupported language: ${languageId}`);
+  }
+}
+
+/**
+ * Lists the regions of the source that did not parse, in document order.
+ * @param sourceCode - The source code to scan.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ */
+export async function findParseErrors(
+  sourceCode: string,
+  languageId: string
+): Promise<ParseErrorInfo[]> {
+  switch (languageId) {
+    case "python":
+      return await findPythonParseErrors(sourceCode);
+    case "typescript":
+    case "javascript":
+      return await findTypeScriptParseErrors(sourceCode);
+    case "java":
+      return await findJavaParseErrors(sourceCode);
+    case "cpp":
+      return findCppParseErrors(sourceCode);
+    case "c":
+      return findCParseErrors(sourceCode);
+    case "rust":
+      return findRustParseErrors(sourceCode);
+    case "go":
+      return findGoParseErrors(sourceCode);
+    default:
+      throw new Error(`Unsupported language: ${languageId}`);
+  }
+}
+
+/**
+ * Formats a parse error for messages, e.g. `line 12, column 5: near "} else"`.
+ */
+export function describeParseError(error: ParseErrorInfo): string {
+  const where = `line ${error.line}, column ${error.column}`;
+  if (error.snippet.startsWith("missing ")) {
+    return `${where}: ${error.snippet}`;
+  }
+  return error.snippet ? `${where}: near "${error.snippet}"` : where;
+}
+
+/**
+ * Formats the lines covered by parse errors, e.g. `lines 12-14, line 30`.
+ */
+export function describeParseErrorRanges(errors: ParseErrorInfo[]): string {
+  return errors
+    .map((error) =>
+      error.endLine > error.line
+        ? `lines ${error.line}-${error.endLine}`
+        : `line ${error.line}`
+    )
+    .join(", ");
+}
+
+/**
+ * Analyzes every function with the given name, in source order. Overloads
+ * and duplicate definitions each get their own flowchart; an empty result
+ * means no function has that name.
+ * @param sourceCode - The source code to analyze.
+ * @param languageId - The language identifier (e.g., 'python', 'typescript', etc.).
+ * @param functionName - The name as reported by listFunctions.
+ */
+export async function analyzeFunctionMatches(
+  sourceCode: string,
+  languageId: string,
+  functionName: string
+): Promise<FlowchartIR[]> {
+  const matches: FlowchartIR[] = [];
+  const seenStarts = new Set<number>();
+  for (const definition of await listFunctionDefinitions(sourceCode, languageId)) {
+    if (definition.name !== functionName) {
+      continue;
+    }
+    // The name picks the method out of a Rust impl found at the position
+    let ir = await analyzeCode(sourceCode, languageId, functionName, definition.start);
+    const range = ir.functionRange;
+    if (!range || range.start < definition.start || range.end > definition.end) {
+      // Bound lambdas and closures are found by name only; a position on
+      // one gives the function around it
+      ir = await analyzeCode(sourceCode, languageId, functionName);
+    }
+    // A parser may list one definition twice (a Rust method is also a function item)
+    if (isPlaceholderIR(ir) || !ir.functionRange || seenStarts.has(ir.functionRange.start)) {
+      continue;
+    }
+    seenStarts.add(ir.functionRange.start);
+    matches.push(ir);
+  }
+
+  return matches.sort(
+    (a, b) => (a.functionRange?.start ?? 0) - (b.functionRange?.start ?? 0)
+  );
+}
+
+/**
+ * Languages for which a structural (class diagram) view is available.
+ */
+export const CLASS_DIAGRAM_LANGUAGES: readonly string[] = ["rust"];
+
+/**
+ * Extracts the type definitions of a whole file for a class diagram.
+ * @param sourceCode - The source code to analyze.
+ * @param languageId - The language identifier; see CLASS_DIAGRAM_LANGUAGES.
+ * @returns A ClassDiagramIR with the file's types and their relations.
+ */
+export function analyzeTypes(
+  sourceCode: string,
+  languageId: string
+): ClassDiagramIR {
+  switch (languageId) {
+    case "rust":
+      return analyzeRustTypes(sourceCode);
+    default:
+      throw new Error(`Class diagrams are not supported for ${languageId}`);
+  }
+}
+
+/**
+ * Clears the parse-tree and flowchart caches of every language parser, so
+ * the next analysis is a cold run.
+ */
+export function clearAnalysisCache(): void {
+  AbstractParser.clearCaches();
+}
@@ -1,19 +1,45 @@
 import Parser from "web-tree-sitter"; // Changed from "tree-sitter"
+import * as crypto from "crypto";
 import {
+  CallSite,
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FlowchartScope,
+  EdgeType,
+  FunctionDefinition,
   LocationMapEntry,
   NodeType,
+  ParseErrorInfo,
   NodeCategory,
   SemanticNodeInfo,
 } from "../../ir/ir";
 import { StringProcessor } from "../utils/StringProcessor";
+import {
+  analysisDeadline,
+  checkDeadline,
+  checkInputSize,
+  timeoutError,
+} from "../utils/AnalysisLimits";
 import {
   ComplexityAnalyzer,
   ComplexityResult,
 } from "../utils/ComplexityAnalyzer";
 import { ProcessResult, LoopContext } from "./AstParserTypes";
+import { addDefUse } from "./DefUseAnalyzer";
+
+// The statement kind a merge node closes, by a word of its syntax node type
+const MERGE_LABELS: readonly [string, string][] = [
+  ["if", "End If"],
+  ["match", "End Match"],
+  ["switch", "End Switch"],
+  ["try", "End Try"],
+];
+
+// A condition wrapped in a branch-prediction hint: `unlikely(x)`,
+// `std::intrinsics::likely(x)`, `__builtin_expect(x, 0)`
+const LIKELY_HINT = /^\(?\s*(?:\w+::)*(likely|unlikely)\s*\(/;
+const BUILTIN_EXPECT_HINT = /^\(?\s*__builtin_expect\s*\([\s\S]*,\s*([01])L?\s*\)\s*\)?$/;
 
 export abstract class AbstractParser {
   protected nodeIdCounter = 0;
@@ -31,10 +57,94 @@ export abstract class AbstractParser {
   // The parser instance is now required by the constructor
   protected parser: Parser;
 
+  // C and C++ `goto`: the jumps seen while building the graph and the node
+  // each label starts at, joined up once the whole body is processed
+  protected gotoJumps: { from: string; label: string; position: number }[] = [];
+  protected labelTargets = new Map<string, { id: string; position: number }>();
+
+  // When the current analysis must give up; set by parseSource
+  private deadline = Infinity;
+
   // Object pool for ProcessResult to reduce GC pressure
   private static processResultPool: ProcessResult[] = [];
   private static readonly MAX_POOL_SIZE = 50;
 
+  // Last parse: reused as-is for identical source, and as the base tree for
+  // incremental reparsing after an edit
+  private lastParse?: { hash: string; source: string; tree: Parser.Tree };
+  // Finished flowcharts keyed by the analyzed function's type and text, so
+  // unchanged functions skip the CFG builder entirely
+  private flowchartCache = new Map<
+    string,
+    { ir: FlowchartIR; start: number; row: number }
+  >();
+  private static readonly MAX_FLOWCHART_CACHE_SIZE = 100;
+  private static readonly instances = new Set<AbstractParser>();
+  // Rust `mod`, TypeScript `namespace` / `module`, C++ `namespace`
+  private static readonly MODULE_TYPES: ReadonlySet<string> = new Set([
+    "mod_item",
+    "internal_module",
+    "module",
+    "namespace_definition",
+  ]);
+  // Call expressions across the grammars (Python `call`, Java
+  // `method_invocation`, Rust `macro_invocation`, the rest `call_expression`)
+  private static readonly CALL_TYPES = [
+    "call",
+    "call_expression",
+    "method_invocation",
+    "macro_invocation",
+  ];
+  // Python's `await f()`, TypeScript's `await f()` and Rust's `f().await`
+  private static readonly AWAIT_TYPES: ReadonlySet<string> = new Set([
+    "await",
+    "await_expression",
+  ]);
+  // Nodes that wrap a function without being its doc comment's sibling
+  // (`export function`, `@decorator def`, `template <...>`, `const f = () =>`)
+  private static readonly DECLARATION_WRAPPER_TYPES: ReadonlySet<string> = new Set([
+    "export_statement",
+    "decorated_definition",
+    "template_declaration",
+    "variable_declarator",
+    "lexical_declaration",
+    "variable_declaration",
+  ]);
+  private static readonly COMMENT_TYPES: ReadonlySet<string> = new Set([
+    "comment",
+    "line_comment",
+    "block_comment",
+  ]);
+  // Syntax nodes that can be a function of their own in some grammar; a
+  // call's caller is looked up once per innermost one
+  private static readonly FUNCTION_TYPES: ReadonlySet<string> = new Set([
+    "function_definition",
+    "function_declaration",
+    "function_item",
+    "function_expression",
+    "method_definition",
+    "method_declaration",
+    "constructor_declaration",
+    "arrow_function",
+    "lambda",
+    "lambda_expression",
+    "closure_expression",
+    "func_literal",
+  ]);
+  // Statement lists one level inside a branch, loop, try or case
+  private static readonly NESTED_BODY_TYPES: ReadonlySet<string> = new Set([
+    "block",
+    "statement_block",
+    "compound_statement",
+    "match_block",
+    "switch_body",
+    "switch_block",
+    "expression_case",
+    "type_case",
+    "default_case",
+    "communication_case",
+  ]);
+
   protected constructor(
     parser: Parser,
     language:
@@ -48,6 +158,839 @@ export abstract class AbstractParser {
   ) {
     this.parser = parser;
     this.language = language;
+    AbstractParser.instances.add(this);
+  }
+
+  /**
+   * Drops every cached parse tree and flowchart so the next analysis runs
+   * cold. Output is identical either way; this only affects latency.
+   */
+  public static clearCaches(): void {
+    for (const instance of AbstractParser.instances) {
+      instance.lastParse = undefined;
+      instance.flowchartCache.clear();
+    }
+  }
+
+  /**
+   * Parses the source, reusing the previous tree when the text is unchanged
+   * and reparsing incrementally from it otherwise. Starts the analysis
+   * time budget.
+   * @throws AnalysisLimitError when the source has too many lines or
+   * parsing runs out of time.
+   */
+  protected parseSource(sourceCode: string): Parser.Tree {
+    checkInputSize(sourceCode);
+    this.deadline = analysisDeadline();
+    const hash = AbstractParser.hashText(sourceCode);
+    const previous = this.lastParse;
+    if (previous && previous.hash === hash && previous.source === sourceCode) {
+      return previous.tree;
+    }
+
+    let tree: Parser.Tree | null = null;
+    this.parser.setTimeoutMicros(Math.max(1, this.deadline - Date.now()) * 1000);
+    try {
+      if (previous) {
+        // Edit a copy: callers may still hold nodes of the previous tree
+        const base = previous.tree.copy();
+        base.edit(AbstractParser.computeEdit(previous.source, sourceCode));
+        try {
+          tree = this.parser.parse(sourceCode, base);
+        } finally {
+          base.delete();
+        }
+      } else {
+        tree = this.parser.parse(sourceCode);
+      }
+    } catch (error) {
+      // A parse cut short by the timeout fails instead of returning a tree
+      if (Date.now() < this.deadline) {
+        throw error;
+      }
+    } finally {
+      this.parser.setTimeoutMicros(0);
+    }
+    if (!tree) {
+      this.parser.reset();
+      throw timeoutError();
+    }
+
+    this.lastParse = { hash, source: sourceCode, tree };
+    return tree;
+  }
+
+  /**
+   * Every call made inside a function, in source order, with the callee as
+   * written and whether it is awaited. Calls at the top level of the file
+   * have no caller and are left out.
+   */
+  public listCalls(sourceCode: string): CallSite[] {
+    const tree = this.parseSource(sourceCode);
+    const callers = new Map<number, string | undefined>();
+    const calls: CallSite[] = [];
+
+    for (const node of tree.rootNode.descendantsOfType(AbstractParser.CALL_TYPES)) {
+      const callee = AbstractParser.getCallee(node);
+      let scope = node.parent;
+      while (scope && !AbstractParser.FUNCTION_TYPES.has(scope.type)) {
+        scope = scope.parent;
+      }
+      if (!callee || !scope) {
+        continue;
+      }
+      if (!callers.has(scope.startIndex)) {
+        callers.set(
+          scope.startIndex,
+          this.findFunctionAtPosition(sourceCode, node.startIndex)
+        );
+      }
+      const caller = callers.get(scope.startIndex);
+      if (caller) {
+        const call: CallSite = { caller, callee, position: node.startIndex, end: node.endIndex };
+        if (node.parent && AbstractParser.AWAIT_TYPES.has(node.parent.type)) {
+          call.awaited = true;
+        }
+        calls.push(call);
+      }
+    }
+    return calls;
+  }
+
+  private static getCallee(call: Parser.SyntaxNode): string | undefined {
+    switch (call.type) {
+      case "method_invocation": {
+        const name = call.childForFieldName("name")?.text;
+        const object = call.childForFieldName("object")?.text;
+        return name && object ? `${object}.${name}` : name;
+      }
+      case "macro_invocation": {
+        const macro = call.childForFieldName("macro")?.text;
+        return macro ? `${macro}!` : undefined;
+      }
+      default:
+        return call.childForFieldName("function")?.text.replace(/\s+/g, "");
+    }
+  }
+
+  /**
+   * Lists the regions of the source that did not parse, in document order.
+   */
+  public findParseErrors(sourceCode: string): ParseErrorInfo[] {
+    return AbstractParser.collectParseErrors(this.parseSource(sourceCode).rootNode);
+  }
+
+  /**
+   * Collects ERROR nodes (without descending into them) and tokens the
+   * parser inserted as missing under `root`.
+   */
+  protected static collectParseErrors(root: Parser.SyntaxNode): ParseErrorInfo[] {
+    const errors: ParseErrorInfo[] = [];
+    const visit = (node: Parser.SyntaxNode) => {
+      if (node.type === "ERROR" || node.isMissing()) {
+        const firstLine = node.text.split(/\r?\n/)[0].trim();
+        errors.push({
+          line: node.startPosition.row + 1,
+          column: node.startPosition.column + 1,
+          endLine: node.endPosition.row + 1,
+          start: node.startIndex,
+          end: node.endIndex,
+          snippet: node.isMissing()
+            ? `missing "${node.type}"`
+            : firstLine.length > 40
+              ? firstLine.substring(0, 37) + "..."
+              : firstLine,
+        });
+        return;
+      }
+      if (node.hasError()) {
+        node.children.forEach(visit);
+      }
+    };
+    visit(root);
+    return errors;
+  }
+
+  /**
+   * Describes the change between two texts as a single replaced range (the
+   * span between their common prefix and common suffix).
+   */
+  private static computeEdit(oldText: string, newText: string): Parser.Edit {
+    const minLength = Math.min(oldText.length, newText.length);
+    let start = 0;
+    while (
+      start < minLength &&
+      oldText.charCodeAt(start) === newText.charCodeAt(start)
+    ) {
+      start++;
+    }
+
+    let oldEnd = oldText.length;
+    let newEnd = newText.length;
+    while (
+      oldEnd > start &&
+      newEnd > start &&
+      oldText.charCodeAt(oldEnd - 1) === newText.charCodeAt(newEnd - 1)
+    ) {
+      oldEnd--;
+      newEnd--;
+    }
+
+    return {
+      startIndex: start,
+      oldEndIndex: oldEnd,
+      newEndIndex: newEnd,
+      startPosition: AbstractParser.pointAt(newText, start),
+      oldEndPosition: AbstractParser.pointAt(oldText, oldEnd),
+      newEndPosition: AbstractParser.pointAt(newText, newEnd),
+    };
+  }
+
+  private static pointAt(text: string, index: number): Parser.Point {
+    let row = 0;
+    let lineStart = 0;
+    for (let i = 0; i < index; i++) {
+      if (text.charCodeAt(i) === 10) {
+        row++;
+        lineStart = i + 1;
+      }
+    }
+    return { row, column: index - lineStart };
+  }
+
+  private static hashText(text: string): string {
+    return crypto.createHash("sha1").update(text).digest("hex");
+  }
+
+  /**
+   * Returns a copy of the flowchart previously built for a function with
+   * identical text, shifted to the function's current offset.
+   */
+  protected getCachedFlowchart(
+    functionNode: Parser.SyntaxNode
+  ): FlowchartIR | undefined {
+    const key = this.getFlowchartCacheKey(functionNode);
+    const entry = this.flowchartCache.get(key);
+    if (!entry) {
+      return undefined;
+    }
+
+    // Move to end for LRU behavior
+    this.flowchartCache.delete(key);
+    this.flowchartCache.set(key, entry);
+    const ir = AbstractParser.relocateFlowchart(
+      entry.ir,
+      functionNode.startIndex - entry.start,
+      functionNode.startPosition.row - entry.row
+    );
+    this.addParseErrors(ir, functionNode);
+    this.addModulePath(ir, functionNode);
+    this.addTitle(ir, functionNode);
+    this.markColdPaths(ir, functionNode);
+    return ir;
+  }
+
+  /**
+   * Final step of every generateFlowchart: adds the function-level
+   * complexity, line numbers, nesting depths, per-node variable defs/uses
+   * and unreachable flags, and stores a copy of the result for later cache hits.
+   */
+  protected finalizeFlowchart(
+    ir: FlowchartIR,
+    functionNode: Parser.SyntaxNode
+  ): FlowchartIR {
+    AbstractParser.renumberFlowchart(ir);
+    this.addFunctionComplexity(ir, functionNode);
+    this.addLineNumbers(ir, functionNode);
+    this.addNestingDepth(ir, functionNode);
+    addDefUse(ir, functionNode);
+    this.markUnreachableNodes(ir);
+    this.addParseErrors(ir, functionNode);
+    this.addModulePath(ir, functionNode);
+    this.addTitle(ir, functionNode);
+    this.markColdPaths(ir, functionNode);
+    ir.functionSource = functionNode.text;
+
+    if (this.flowchartCache.size >= AbstractParser.MAX_FLOWCHART_CACHE_SIZE) {
+      const firstKey = this.flowchartCache.keys().next().value;
+      if (firstKey !== undefined) {
+        this.flowchartCache.delete(firstKey);
+      }
+    }
+    // Stored as a copy: generators and later passes modify the IR in place
+    this.flowchartCache.set(this.getFlowchartCacheKey(functionNode), {
+      ir: AbstractParser.relocateFlowchart(ir, 0, 0),
+      start: functionNode.startIndex,
+      row: functionNode.startPosition.row,
+    });
+    return ir;
+  }
+
+  /**
+   * Records the source line of every node that maps to code, taken from the
+   * start position of the syntax node at its offset.
+   */
+  protected addLineNumbers(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const root = functionNode.tree.rootNode;
+    const startByNodeId = new Map<string, number>();
+    for (const entry of ir.locationMap) {
+      if (!startByNodeId.has(entry.nodeId)) {
+        startByNodeId.set(entry.nodeId, entry.start);
+      }
+    }
+
+    for (const node of ir.nodes) {
+      const start = node.location?.start ?? startByNodeId.get(node.id);
+      if (start !== undefined) {
+        node.line = root.descendantForIndex(start).startPosition.row + 1;
+      }
+    }
+  }
+
+  /**
+   * Records how deeply each node that maps to code is nested: 1 for
+   * statements directly in the function body, one more for every enclosing
+   * branch, loop, try or case body. A branch or loop header counts at the
+   * level of its statement, not of its body. `else if` chains stay on one
+   * level since the grammars attach them without a body in between. The
+   * body of a nested closure or lambda counts as well, even when it is a
+   * bare expression. The enclosing bodies themselves become the IR's scopes.
+   */
+  protected addNestingDepth(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const root = functionNode.tree.rootNode;
+    const body = functionNode.childForFieldName("body");
+    const scopes: FlowchartScope[] = [];
+    const scopeIds = new Map<number, string>();
+    for (const node of ir.nodes) {
+      delete node.scope;
+      if (!node.location || node.nodeType === NodeType.ENTRY || node.nodeType === NodeType.EXIT) {
+        continue;
+      }
+      const { start, end } = node.location;
+      const bodies: Parser.SyntaxNode[] = [];
+      // The located node itself may be a case body; only enclosing ones count
+      let ancestor = root.descendantForIndex(start, Math.max(start, end - 1)).parent;
+      while (
+        ancestor &&
+        ancestor.id !== functionNode.id &&
+        ancestor.id !== body?.id &&
+        ancestor.startIndex >= functionNode.startIndex
+      ) {
+        if (AbstractParser.NESTED_BODY_TYPES.has(ancestor.type) || AbstractParser.hasExpressionBody(ancestor)) {
+          bodies.push(ancestor);
+        }
+        ancestor = ancestor.parent;
+      }
+      node.depth = bodies.length + 1;
+
+      // Outermost first, so a scope is always listed after its parent
+      let parent: string | undefined;
+      for (const scopeNode of bodies.reverse()) {
+        let id = scopeIds.get(scopeNode.id);
+        if (!id) {
+          id = `scope_${scopes.length + 1}`;
+          scopeIds.set(scopeNode.id, id);
+          scopes.push({ id, label: this.escapeString(AbstractParser.getScopeLabel(scopeNode)), parent });
+        }
+        parent = id;
+      }
+      if (parent) {
+        node.scope = parent;
+      }
+    }
+
+    // Merge points and other synthetic nodes sit in the innermost body
+    // shared by everything leading into them
+    const parentOf = new Map(scopes.map((scope) => [scope.id, scope.parent]));
+    const chainOf = (scope: string | undefined): string[] => {
+      const chain: string[] = [];
+      for (let id = scope; id; id = parentOf.get(id)) {
+        chain.unshift(id);
+      }
+      return chain;
+    };
+    const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+    for (const node of ir.nodes) {
+      if (node.location || node.nodeType === NodeType.ENTRY || node.nodeType === NodeType.EXIT) {
+        continue;
+      }
+      let shared: string[] | undefined;
+      for (const edge of ir.edges) {
+        const from = edge.to === node.id && edge.edgeType !== EdgeType.BACK ? nodeById.get(edge.from) : undefined;
+        if (!from) {
+          continue;
+        }
+        const chain = chainOf(from.scope);
+        let common = 0;
+        while (shared && common < shared.length && shared[common] === chain[common]) {
+          common++;
+        }
+        shared = shared ? shared.slice(0, common) : chain;
+      }
+      if (shared && shared.length > 0) {
+        node.scope = shared[shared.length - 1];
+      }
+    }
+
+    for (const scope of scopes) {
+      if (!scope.parent) {
+        delete scope.parent;
+      }
+    }
+    if (scopes.length > 0) {
+      ir.scopes = scopes;
+    } else {
+      delete ir.scopes;
+    }
+  }
+
+  /** A closure or lambda whose body is an expression rather than a block (`|x| x > 2`). */
+  private static hasExpressionBody(node: Parser.SyntaxNode): boolean {
+    const body = AbstractParser.FUNCTION_TYPES.has(node.type) ? node.childForFieldName("body") : null;
+    return !!body && !AbstractParser.NESTED_BODY_TYPES.has(body.type);
+  }
+
+  /**
+   * Names a body after the statement it belongs to: the text before it
+   * (`if x % 2 == 0`, `else`, `Some(n) if n > 0 =>`, `|x|`), or a case's
+   * own first line.
+   */
+  private static getScopeLabel(scopeNode: Parser.SyntaxNode): string {
+    const owner = scopeNode.parent;
+    const body = AbstractParser.hasExpressionBody(scopeNode) ? scopeNode.childForFieldName("body") : null;
+    const header = body
+      ? scopeNode.text.slice(0, body.startIndex - scopeNode.startIndex)
+      : owner && !scopeNode.type.endsWith("_case")
+        ? owner.text.slice(0, scopeNode.startIndex - owner.startIndex)
+        : scopeNode.text.split("\n")[0];
+    const label = header
+      .replace(/\s+/g, " ")
+      .replace(/\s*(\{|:|=>)\s*$/, "")
+      .trim();
+    if (!label) {
+      return "block";
+    }
+    return label.length > 40 ? `${label.slice(0, 37)}...` : label;
+  }
+
+  /**
+   * Flags statements no control-flow path from the entry reaches, such as
+   * code after a return or an infinite loop. Start, End and synthetic merge
+   * points stand for no statement of their own and are never flagged.
+   */
+  protected markUnreachableNodes(ir: FlowchartIR): void {
+    const reachable = ComplexityAnalyzer.findReachableNodeIds(ir);
+    if (reachable.size === 0) {
+      return;
+    }
+    for (const node of ir.nodes) {
+      if (
+        !reachable.has(node.id) &&
+        node.nodeType !== NodeType.ENTRY &&
+        node.nodeType !== NodeType.EXIT &&
+        node.nodeType !== NodeType.MERGE
+      ) {
+        node.unreachable = true;
+      }
+    }
+  }
+
+  /**
+   * Flags the edges of paths the code itself marks as unlikely: into a
+   * panic, a call to a cold function (see getColdFunctionNames), or the
+   * branch a `likely()`/`unlikely()` hint argues against. Statements that
+   * only lead into such a path, or only run after entering one, are cold
+   * too, so the whole detour is drawn de-emphasized up to where it rejoins
+   * the hot path. Which functions are cold depends on the rest of the file,
+   * so cache hits recompute it.
+   */
+  protected markColdPaths(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const edges = ir.edges.filter((edge) => edge.edgeType !== EdgeType.DATAFLOW);
+    for (const edge of edges) {
+      delete edge.cold;
+    }
+    const root = functionNode.tree.rootNode;
+    const source = root.text;
+    const coldFunctions = Array.from(this.getColdFunctionNames(root));
+    const coldCall =
+      coldFunctions.length > 0
+        ? new RegExp(`\\b(?:${coldFunctions.map((name) => name.replace(/[^\w]/g, "\\$&")).join("|")})\\s*\\(`)
+        : undefined;
+
+    const coldNodes = new Set<string>();
+    const coldEdges = new Set<FlowchartEdge>();
+    const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+    for (const node of ir.nodes) {
+      if (node.nodeType === NodeType.PANIC) {
+        coldNodes.add(node.id);
+        continue;
+      }
+      if (!node.location) {
+        continue;
+      }
+      const text = source.slice(node.location.start, node.location.end);
+      if (node.nodeType === NodeType.DECISION) {
+        const unlikely = AbstractParser.getBranchHint(text);
+        if (unlikely !== undefined) {
+          this.addHintedEdges(node, unlikely, edges, nodeById, root, coldEdges);
+        }
+      } else if (node.nodeType !== NodeType.LOOP_START && coldCall?.test(text)) {
+        coldNodes.add(node.id);
+      }
+    }
+    if (coldNodes.size === 0 && coldEdges.size === 0) {
+      return;
+    }
+
+    const outgoing = new Map<string, FlowchartEdge[]>();
+    const incoming = new Map<string, FlowchartEdge[]>();
+    for (const edge of edges) {
+      if (edge.edgeType !== EdgeType.BACK) {
+        outgoing.set(edge.from, [...(outgoing.get(edge.from) ?? []), edge]);
+        incoming.set(edge.to, [...(incoming.get(edge.to) ?? []), edge]);
+      }
+    }
+    const isCold = (edge: FlowchartEdge): boolean =>
+      coldEdges.has(edge) || coldNodes.has(edge.from) || coldNodes.has(edge.to);
+    for (let changed = true; changed; ) {
+      changed = false;
+      for (const node of ir.nodes) {
+        if (
+          coldNodes.has(node.id) ||
+          node.nodeType === NodeType.ENTRY ||
+          node.nodeType === NodeType.EXIT
+        ) {
+          continue;
+        }
+        const after = outgoing.get(node.id) ?? [];
+        const before = incoming.get(node.id) ?? [];
+        if (
+          (after.length > 0 && after.every((edge) => coldNodes.has(edge.to))) ||
+          (before.length > 0 && before.every(isCold))
+        ) {
+          coldNodes.add(node.id);
+          changed = true;
+        }
+      }
+    }
+
+    // When every way out of the entry is cold there is no hot path to set off
+    const entryEdges = outgoing.get(ir.entryNodeId) ?? [];
+    if (entryEdges.length === 0 || entryEdges.every(isCold)) {
+      return;
+    }
+    for (const edge of edges) {
+      if (isCold(edge)) {
+        edge.cold = true;
+      }
+    }
+  }
+
+  /**
+   * Functions whose calls count as cold, e.g. ones the file declares
+   * `#[cold]`. None by default.
+   */
+  protected getColdFunctionNames(_root: Parser.SyntaxNode): ReadonlySet<string> {
+    return new Set();
+  }
+
+  /**
+   * Whether a decision's condition is hinted unlikely (true) or likely
+   * (false); undefined without a hint.
+   */
+  private static getBranchHint(condition: string): boolean | undefined {
+    const hint = LIKELY_HINT.exec(condition);
+    if (hint) {
+      return hint[1] === "unlikely";
+    }
+    const expect = BUILTIN_EXPECT_HINT.exec(condition);
+    return expect ? expect[1] === "0" : undefined;
+  }
+
+  /**
+   * Adds the decision's edges into its consequence when the condition is
+   * unlikely, or its other edges when it is likely.
+   */
+  private addHintedEdges(
+    decision: FlowchartNode,
+    unlikely: boolean,
+    edges: FlowchartEdge[],
+    nodeById: Map<string, FlowchartNode>,
+    root: Parser.SyntaxNode,
+    coldEdges: Set<FlowchartEdge>
+  ): void {
+    const { start, end } = decision.location!;
+    let statement: Parser.SyntaxNode | null = root.descendantForIndex(start, Math.max(start, end - 1));
+    while (statement && !statement.childForFieldName("consequence")) {
+      statement = statement.parent;
+    }
+    const consequence = statement?.childForFieldName("consequence");
+    if (!consequence) {
+      return;
+    }
+    const branches = edges.filter(
+      (edge) => edge.from === decision.id && edge.edgeType !== EdgeType.BACK
+    );
+    const intoConsequence = (edge: FlowchartEdge): boolean => {
+      const location = nodeById.get(edge.to)?.location;
+      return (
+        !!location &&
+        location.start >= consequence.startIndex &&
+        location.end <= consequence.endIndex
+      );
+    };
+    // Without a located target the branches cannot be told apart
+    if (!branches.some(intoConsequence)) {
+      return;
+    }
+    for (const edge of branches) {
+      if (intoConsequence(edge) === unlikely) {
+        coldEdges.add(edge);
+      }
+    }
+  }
+
+  /**
+   * Records syntax errors inside the function. Positions are taken from the
+   * current tree, so a relocated cache hit reports them where they are now.
+   */
+  protected addParseErrors(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const errors = functionNode.hasError()
+      ? AbstractParser.collectParseErrors(functionNode)
+      : [];
+    if (errors.length > 0) {
+      ir.parseErrors = errors;
+    } else {
+      delete ir.parseErrors;
+    }
+  }
+
+  /**
+   * Records the named modules around the function. Like parse errors this
+   * depends on where the function sits, so cache hits recompute it.
+   */
+  protected addModulePath(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const path: string[] = [];
+    for (let node = functionNode.parent; node; node = node.parent) {
+      if (!AbstractParser.MODULE_TYPES.has(node.type)) {
+        continue;
+      }
+      const name = node.childForFieldName("name");
+      if (name) {
+        // Nested names (`namespace a.b`, `namespace a::b`) contribute each part
+        const parts = name.text.replace(/^["']|["']$/g, "").split(/\.|::/);
+        path.unshift(...parts.filter(Boolean));
+      }
+    }
+    if (path.length > 0) {
+      ir.modulePath = path;
+    } else {
+      delete ir.modulePath;
+    }
+  }
+
+  /**
+   * Titles the diagram with the first line of the function's doc comment
+   * (`///` lines, a `/**` block, a Python docstring, or the `//` lines
+   * above a Go function), or else with its signature: the text before the
+   * body, with the module path in front of the name. Functions without a
+   * name of their own (lambdas, closures) keep the title their parser gave
+   * them.
+   */
+  protected addTitle(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+    const doc = this.getDocSummary(functionNode);
+    if (doc) {
+      ir.title = this.escapeString(doc);
+      return;
+    }
+    const body = functionNode.childForFieldName("body");
+    // C and C++ name the function inside its declarator
+    const name =
+      functionNode.childForFieldName("name") ??
+      functionNode.childForFieldName("declarator")?.childForFieldName("declarator");
+    if (!body || !name) {
+      return;
+    }
+    const start = functionNode.startIndex;
+    let header = functionNode.text.slice(0, body.startIndex - start);
+    if (ir.modulePath) {
+      const separator = this.language === "typescript" || this.language === "javascript" ? "." : "::";
+      header =
+        header.slice(0, name.startIndex - start) +
+        [...ir.modulePath, name.text].join(separator) +
+        header.slice(name.endIndex - start);
+    }
+    const signature = header.replace(/\s+/g, " ").replace(/\s*(:|=>)?\s*$/, "");
+    ir.title = this.escapeString(
+      signature.length > 120 ? `${signature.slice(0, 117)}...` : signature
+    );
+  }
+
+  /** The first non-empty line of the function's doc comment, without tags. */
+  private getDocSummary(functionNode: Parser.SyntaxNode): string | undefined {
+    if (this.language === "python") {
+      const first = functionNode.childForFieldName("body")?.namedChild(0);
+      const docstring = first?.type === "expression_statement" ? first.namedChild(0) : null;
+      if (docstring?.type !== "string") {
+        return undefined;
+      }
+      return AbstractParser.firstDocLine(docstring.text.replace(/^[rRuU]?("""|'''|"|')|("""|'''|"|')$/g, ""));
+    }
+
+    let anchor = functionNode;
+    while (anchor.parent && AbstractParser.DECLARATION_WRAPPER_TYPES.has(anchor.parent.type)) {
+      anchor = anchor.parent;
+    }
+    // The comments directly above, skipping Rust attributes in between
+    const comments: string[] = [];
+    let below = anchor;
+    for (let node = anchor.previousNamedSibling; node; node = node.previousNamedSibling) {
+      if (node.endPosition.row < below.startPosition.row - 1) {
+        break;
+      }
+      if (node.type === "attribute_item") {
+        below = node;
+        continue;
+      }
+      if (!AbstractParser.COMMENT_TYPES.has(node.type)) {
+        break;
+      }
+      comments.unshift(node.text);
+      below = node;
+    }
+
+    const isDoc = (text: string): boolean =>
+      (text.startsWith("///") && !text.startsWith("////")) ||
+      (text.startsWith("/**") && text !== "/**/") ||
+      (this.language === "go" && text.startsWith("//"));
+    const docComments = comments.filter(isDoc);
+    if (docComments.length === 0) {
+      return undefined;
+    }
+    const text = docComments
+      .map((comment) =>
+        comment.startsWith("/*")
+          ? comment
+              .replace(/^\/\*\*|\*\/$/g, "")
+              .split("\n")
+              .map((line) => line.replace(/^\s*\*?/, ""))
+              .join("\n")
+          : comment.replace(/^\/\/\/?/, "")
+      )
+      .join("\n");
+    return AbstractParser.firstDocLine(text);
+  }
+
+  private static firstDocLine(text: string): string | undefined {
+    return text
+      .split("\n")
+      .map((line) => line.trim())
+      .find((line) => line !== "" && !line.startsWith("@"));
+  }
+
+  private getFlowchartCacheKey(functionNode: Parser.SyntaxNode): string {
+    return `${functionNode.type}:${AbstractParser.hashText(functionNode.text)}`;
+  }
+
+  /**
+   * Gives the nodes ids numbered in source order and sorts nodes and edges
+   * to match, so the output only depends on the code, not on the order the
+   * walk happened to create things in. Start comes first and End last; a
+   * node without source text of its own (a merge point, a shared exit)
+   * sorts right after the first node leading into it.
+   */
+  private static renumberFlowchart(ir: FlowchartIR): void {
+    const order = new Map(ir.nodes.map((node, index) => [node.id, index]));
+    const keys = new Map<string, number>();
+    for (const node of ir.nodes) {
+      if (node.nodeType === NodeType.ENTRY) {
+        keys.set(node.id, -Infinity);
+      } else if (node.nodeType === NodeType.EXIT) {
+        keys.set(node.id, Infinity);
+      } else if (node.location) {
+        keys.set(node.id, node.location.start);
+      }
+    }
+    // Chains of merge nodes need one pass per link
+    for (let changed = true; changed; ) {
+      changed = false;
+      for (const edge of ir.edges) {
+        const from = keys.get(edge.from);
+        if (!keys.has(edge.to) && from !== undefined && order.has(edge.to)) {
+          keys.set(edge.to, from + 0.5);
+          changed = true;
+        }
+      }
+    }
+    const key = (id: string): number => keys.get(id) ?? Infinity;
+    ir.nodes.sort((a, b) => key(a.id) - key(b.id) || order.get(a.id)! - order.get(b.id)!);
+
+    const ids = new Map<string, string>();
+    ir.nodes.forEach((node, index) => {
+      const prefix = /^(.+)_\d+$/.exec(node.id);
+      ids.set(node.id, prefix ? `${prefix[1]}_${index}` : node.id);
+    });
+    const rename = (id: string): string => ids.get(id) ?? id;
+    for (const node of ir.nodes) {
+      node.id = rename(node.id);
+    }
+    for (const edge of ir.edges) {
+      edge.from = rename(edge.from);
+      edge.to = rename(edge.to);
+    }
+    for (const entry of ir.locationMap) {
+      entry.nodeId = rename(entry.nodeId);
+    }
+    if (ir.entryNodeId) {
+      ir.entryNodeId = rename(ir.entryNodeId);
+    }
+    if (ir.exitNodeId) {
+      ir.exitNodeId = rename(ir.exitNodeId);
+    }
+
+    const position = new Map(ir.nodes.map((node, index) => [node.id, index]));
+    const rank = (id: string): number => position.get(id) ?? ir.nodes.length;
+    ir.edges.sort(
+      (a, b) =>
+        rank(a.from) - rank(b.from) ||
+        rank(a.to) - rank(b.to) ||
+        (a.label ?? "").localeCompare(b.label ?? "") ||
+        (a.edgeType ?? "").localeCompare(b.edgeType ?? "")
+    );
+  }
+
+  /**
+   * Deep-copies an IR and moves every source offset by `delta` and every
+   * line number by `rowDelta`.
+   */
+  private static relocateFlowchart(
+    ir: FlowchartIR,
+    delta: number,
+    rowDelta: number
+  ): FlowchartIR {
+    const copy: FlowchartIR = JSON.parse(JSON.stringify(ir));
+    if (delta === 0 && rowDelta === 0) {
+      return copy;
+    }
+
+    for (const node of copy.nodes) {
+      if (node.location) {
+        node.location.start += delta;
+        node.location.end += delta;
+      }
+      if (node.line !== undefined) {
+        node.line += rowDelta;
+      }
+    }
+    for (const entry of copy.locationMap) {
+      entry.start += delta;
+      entry.end += delta;
+    }
+    if (copy.functionRange) {
+      copy.functionRange.start += delta;
+      copy.functionRange.end += delta;
+    }
+    return copy;
   }
 
   protected readonly nodeStyles = {
@@ -118,6 +1061,10 @@ export abstract class AbstractParser {
   }
 
   protected generateNodeId(prefix: string): string {
+    // Nodes are created throughout the walk, so this bounds its running time
+    if ((this.nodeIdCounter & 0xff) === 0) {
+      checkDeadline(this.deadline);
+    }
     return `${prefix}_${this.nodeIdCounter++}`;
   }
 
@@ -125,6 +1072,29 @@ export abstract class AbstractParser {
     return StringProcessor.escapeString(str);
   }
 
+  /**
+   * Returns the innermost node whose range contains the position, so a
+   * nested function or method wins over the definition that encloses it.
+   */
+  protected findInnermostContaining(
+    candidates: Parser.SyntaxNode[],
+    position: number
+  ): Parser.SyntaxNode | undefined {
+    let innermost: Parser.SyntaxNode | undefined;
+    for (const candidate of candidates) {
+      if (
+        position >= candidate.startIndex &&
+        position <= candidate.endIndex &&
+        (!innermost ||
+          candidate.endIndex - candidate.startIndex <
+            innermost.endIndex - innermost.startIndex)
+      ) {
+        innermost = candidate;
+      }
+    }
+    return innermost;
+  }
+
   // Object pooling for ProcessResult
   protected createProcessResult(
     nodes: FlowchartNode[] = [],
@@ -283,6 +1253,7 @@ export abstract class AbstractParser {
       case NodeType.LOOP_END:
       case NodeType.EXCEPTION:
       case NodeType.RETURN:
+      case NodeType.AWAIT:
         return "stadium";
 
       default:
@@ -560,24 +1531,25 @@ export abstract class AbstractParser {
   }
 
   /**
-   * Calculate function-level complexity and add it to the FlowchartIR
+   * Calculate function-level complexity from the finished graph and add it
+   * to the FlowchartIR. Falls back to counting decision points in the AST
+   * when the graph has no entry node to measure from.
    */
   protected addFunctionComplexity(
     ir: FlowchartIR,
     functionNode: Parser.SyntaxNode
   ): void {
     try {
-      const functionComplexityInfo =
-        ComplexityAnalyzer.calculateFunctionComplexity(
-          functionNode,
-          this.language
-        );
+      const cyclomaticComplexity = ir.entryNodeId
+        ? ComplexityAnalyzer.calculateGraphComplexity(ir)
+        : ComplexityAnalyzer.calculateFunctionComplexity(
+            functionNode,
+            this.language
+          ).complexity.cyclomaticComplexity;
 
       ir.functionComplexity = {
-        cyclomaticComplexity:
-          functionComplexityInfo.complexity.cyclomaticComplexity,
-        rating: functionComplexityInfo.complexity.rating,
-        description: functionComplexityInfo.complexity.description,
+        cyclomaticComplexity,
+        ...ComplexityAnalyzer.rateComplexity(cyclomaticComplexity),
       };
     } catch (error) {
       console.warn("Failed to calculate function complexity:", error);
@@ -590,7 +1562,15 @@ export abstract class AbstractParser {
     }
   }
 
-  abstract listFunctions(sourceCode: string): string[];
+  /**
+   * The names of the functions defined in the source, in the order
+   * listFunctionDefinitions reports them.
+   */
+  public listFunctions(sourceCode: string): string[] {
+    return this.listFunctionDefinitions(sourceCode).map((definition) => definition.name);
+  }
+
+  abstract listFunctionDefinitions(sourceCode: string): FunctionDefinition[];
   abstract findFunctionAtPosition(
     sourceCode: string,
     position: number
@@ -657,7 +1637,7 @@ export abstract class AbstractParser {
           });
         }
       }
-      lastExitPoints = result.exitPoints;
+      lastExitPoints = this.joinExitPoints(statement, result.exitPoints, nodes, edges);
     }
 
     return this.createProcessResult(
@@ -669,6 +1649,37 @@ export abstract class AbstractParser {
     );
   }
 
+  /**
+   * Sends the paths a branching statement continues along (both sides of an
+   * `if`, every arm of a `match`) into one merge node, so the next statement
+   * or the function exit is entered once instead of from every branch. The
+   * branch labels move to the edges into the merge node.
+   */
+  protected joinExitPoints(
+    statement: Parser.SyntaxNode,
+    exitPoints: { id: string; label?: string }[],
+    nodes: FlowchartNode[],
+    edges: FlowchartEdge[]
+  ): { id: string; label?: string }[] {
+    if (exitPoints.length < 2) {
+      return exitPoints;
+    }
+    const mergeId = this.generateNodeId("merge");
+    nodes.push(this.createMergeNode(mergeId, statement));
+    for (const exitPoint of exitPoints) {
+      edges.push({ from: exitPoint.id, to: mergeId, label: exitPoint.label });
+    }
+    return [{ id: mergeId }];
+  }
+
+  /** A merge node has no source text of its own; its label names what it closes. */
+  protected createMergeNode(id: string, statement: Parser.SyntaxNode): FlowchartNode {
+    const words = statement.type.split("_");
+    const closes = MERGE_LABELS.find(([word]) => words.includes(word));
+    const label = closes ? closes[1] : "Merge";
+    return this.createSemanticNode(id, label, NodeType.MERGE, undefined, "round");
+  }
+
   protected abstract processStatement(
     statement: Parser.SyntaxNode,
     exitId: string,
@@ -750,7 +1761,28 @@ export abstract class AbstractParser {
   // Memory management utilities
   protected resetState(): void {
     this.nodeIdCounter = 0;
-    this.locationMap.length = 0;
+    // Start a fresh array so previously returned IRs keep their own map
+    this.locationMap = [];
+    this.gotoJumps = [];
+    this.labelTargets = new Map();
+  }
+
+  /**
+   * Adds an edge from every recorded `goto` to its label. A jump to a label
+   * earlier in the function closes a loop and is marked as a back edge; a
+   * label that does not exist in the function sends the jump to the exit.
+   */
+  protected resolveGotoJumps(edges: FlowchartEdge[], exitId: string): void {
+    for (const jump of this.gotoJumps) {
+      const target = this.labelTargets.get(jump.label);
+      if (!target) {
+        edges.push({ from: jump.from, to: exitId });
+      } else if (target.position < jump.position) {
+        edges.push({ from: jump.from, to: target.id, edgeType: EdgeType.BACK });
+      } else {
+        edges.push({ from: jump.from, to: target.id });
+      }
+    }
   }
 
   // Performance monitoring
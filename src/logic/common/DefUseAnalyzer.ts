@@ -0,0 +1,227 @@
+import Parser from "web-tree-sitter";
+import { FlowchartIR, FlowchartNode, NodeType } from "../../ir/ir";
+
+/**
+ * Fields whose identifiers are written, per parent node type, across the
+ * supported grammars. Entries marked `readWrite` also read the old value
+ * (`x += 1`, `i++`).
+ */
+const DEFINITION_FIELDS: Readonly<
+  Record<string, { field?: string; readWrite?: boolean }>
+> = {
+  // Python
+  assignment: { field: "left" },
+  augmented_assignment: { field: "left", readWrite: true },
+  for_statement: { field: "left" },
+  for_in_clause: { field: "left" },
+  as_pattern_target: {},
+  parameters: {},
+  lambda_parameters: {},
+  default_parameter: { field: "name" },
+  typed_default_parameter: { field: "name" },
+  // TypeScript / JavaScript, Java, C, C++, Rust
+  assignment_expression: { field: "left" },
+  augmented_assignment_expression: { field: "left", readWrite: true },
+  compound_assignment_expr: { field: "left", readWrite: true },
+  update_expression: { readWrite: true },
+  variable_declarator: { field: "name" },
+  for_in_statement: { field: "left" },
+  required_parameter: { field: "pattern" },
+  optional_parameter: { field: "pattern" },
+  formal_parameter: { field: "name" },
+  enhanced_for_statement: { field: "name" },
+  catch_formal_parameter: { field: "name" },
+  init_declarator: { field: "declarator" },
+  declaration: { field: "declarator" },
+  let_declaration: { field: "pattern" },
+  for_expression: { field: "pattern" },
+  closure_parameters: {},
+  // Go
+  short_var_declaration: { field: "left" },
+  assignment_statement: { field: "left" },
+  inc_statement: { readWrite: true },
+  dec_statement: { readWrite: true },
+  var_spec: { field: "name" },
+  const_spec: { field: "name" },
+  range_clause: { field: "left" },
+  // Rust and Go parameters; C/C++ parameter declarations
+  parameter: { field: "pattern" },
+  parameter_declaration: {},
+};
+
+/**
+ * Nodes that can sit between a definition field and the identifiers it
+ * binds (destructuring, declarators, `mut`).
+ */
+const BINDING_WRAPPERS: ReadonlySet<string> = new Set([
+  "pattern_list",
+  "tuple_pattern",
+  "list_pattern",
+  "tuple",
+  "list",
+  "parenthesized_expression",
+  "parenthesized_pattern",
+  "expression_list",
+  "array_pattern",
+  "object_pattern",
+  "pair_pattern",
+  "assignment_pattern",
+  "rest_pattern",
+  "list_splat_pattern",
+  "mut_pattern",
+  "ref_pattern",
+  "reference_pattern",
+  "tuple_struct_pattern",
+  "struct_pattern",
+  "field_pattern",
+  "pointer_declarator",
+  "reference_declarator",
+  "array_declarator",
+  "typed_parameter",
+  "identifier_list",
+]);
+
+/** Fields whose identifiers are names of other things, not variables. */
+const NON_VARIABLE_FIELDS: Readonly<Record<string, string[]>> = {
+  call: ["function"],
+  call_expression: ["function"],
+  attribute: ["attribute"],
+  keyword_argument: ["name"],
+  method_invocation: ["name"],
+  field_access: ["field"],
+  macro_invocation: ["macro"],
+  labeled_statement: ["label"],
+  break_statement: ["label"],
+  continue_statement: ["label"],
+  function_definition: ["name"],
+  function_declaration: ["name"],
+  function_item: ["name"],
+  method_declaration: ["name"],
+};
+
+interface NodeRange {
+  node: FlowchartNode;
+  start: number;
+  end: number;
+}
+
+/**
+ * Records the variables each flowchart node defines and uses. Every
+ * identifier in the function is credited to the innermost node whose source
+ * range contains it, so a branch condition keeps its own reads while the
+ * statements of its body keep theirs. Parameters are defined by Start.
+ */
+export function addDefUse(ir: FlowchartIR, functionNode: Parser.SyntaxNode): void {
+  const ranges = collectNodeRanges(ir);
+  const entry = ir.nodes.find((node) => node.nodeType === NodeType.ENTRY);
+  const defines = new Map<FlowchartNode, Set<string>>();
+  const uses = new Map<FlowchartNode, Set<string>>();
+  const add = (map: Map<FlowchartNode, Set<string>>, node: FlowchartNode, name: string) => {
+    const names = map.get(node) || new Set<string>();
+    names.add(name);
+    map.set(node, names);
+  };
+
+  for (const identifier of functionNode.descendantsOfType("identifier")) {
+    if (isNonVariable(identifier)) {
+      continue;
+    }
+    const access = classifyAccess(identifier);
+    const owner = findOwner(ranges, identifier.startIndex, identifier.endIndex);
+    const target = owner || (access !== "use" ? entry : undefined);
+    if (!target) {
+      continue;
+    }
+    if (access !== "use") {
+      add(defines, target, identifier.text);
+    }
+    if (access !== "def" && owner) {
+      add(uses, target, identifier.text);
+    }
+  }
+
+  for (const node of ir.nodes) {
+    const defined = defines.get(node);
+    const used = uses.get(node);
+    if (defined) {
+      node.defines = Array.from(defined);
+    }
+    if (used) {
+      node.uses = Array.from(used);
+    }
+  }
+}
+
+function collectNodeRanges(ir: FlowchartIR): NodeRange[] {
+  const firstEntry = new Map<string, { start: number; end: number }>();
+  for (const entry of ir.locationMap) {
+    if (!firstEntry.has(entry.nodeId)) {
+      firstEntry.set(entry.nodeId, entry);
+    }
+  }
+
+  const ranges: NodeRange[] = [];
+  for (const node of ir.nodes) {
+    if (node.nodeType === NodeType.ENTRY || node.nodeType === NodeType.EXIT) {
+      continue;
+    }
+    const range = node.location || firstEntry.get(node.id);
+    if (range) {
+      ranges.push({ node, start: range.start, end: range.end });
+    }
+  }
+  return ranges;
+}
+
+function findOwner(ranges: NodeRange[], start: number, end: number): FlowchartNode | undefined {
+  let owner: NodeRange | undefined;
+  for (const range of ranges) {
+    if (range.start <= start && end <= range.end) {
+      if (!owner || range.end - range.start < owner.end - owner.start) {
+        owner = range;
+      }
+    }
+  }
+  return owner?.node;
+}
+
+function isNonVariable(identifier: Parser.SyntaxNode): boolean {
+  const parent = identifier.parent;
+  if (!parent) {
+    return true;
+  }
+  const fields = NON_VARIABLE_FIELDS[parent.type] || [];
+  if (fields.some((field) => parent.childForFieldName(field)?.id === identifier.id)) {
+    return true;
+  }
+  // Type annotations name types, even where the grammar uses `identifier`
+  let node: Parser.SyntaxNode = identifier;
+  let ancestor = node.parent;
+  while (ancestor) {
+    if (ancestor.childForFieldName("type")?.id === node.id) {
+      return true;
+    }
+    node = ancestor;
+    ancestor = ancestor.parent;
+  }
+  return false;
+}
+
+function classifyAccess(identifier: Parser.SyntaxNode): "def" | "use" | "readWrite" {
+  let node: Parser.SyntaxNode = identifier;
+  while (node.parent) {
+    const parent: Parser.SyntaxNode = node.parent;
+    const definition = DEFINITION_FIELDS[parent.type];
+    if (
+      definition &&
+      (!definition.field || parent.childForFieldName(definition.field)?.id === node.id)
+    ) {
+      return definition.readWrite ? "readWrite" : "def";
+    }
+    if (!BINDING_WRAPPERS.has(parent.type)) {
+      return "use";
+    }
+    node = parent;
+  }
+  return "use";
+}
@@ -4,6 +4,7 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
@@ -28,19 +29,23 @@ export class CAstParser extends AbstractParser {
     return new CAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
 
       // Get function definitions
-      const funcNames = tree.rootNode
+      const functions = tree.rootNode
         .descendantsOfType("function_definition")
         .map((f: Parser.SyntaxNode) => {
           const declarator = f.childForFieldName("declarator");
-          return this.extractFunctionName(declarator) || "[anonymous]";
+          return {
+            name: this.extractFunctionName(declarator) || "[anonymous]",
+            start: f.startIndex,
+            end: f.endIndex,
+          };
         });
 
-      return funcNames;
+      return functions;
     });
   }
 
@@ -48,7 +53,7 @@ export class CAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
 
     // Check function definitions
     const func = tree.rootNode
@@ -68,7 +73,7 @@ export class CAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
     let targetNode: Parser.SyntaxNode | undefined;
@@ -124,6 +129,11 @@ export class CAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -153,6 +163,8 @@ export class CAstParser extends AbstractParser {
       }
     });
 
+    this.resolveGotoJumps(edges, exitId);
+
     const nodeIdSet = new Set(nodes.map((n) => n.id));
     const validEdges = edges.filter(
       (e) => nodeIdSet.has(e.from) && nodeIdSet.has(e.to)
@@ -168,10 +180,7 @@ export class CAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
 
-    // Add function complexity analysis
-    this.addFunctionComplexity(ir, targetNode);
-
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   protected processStatement(
@@ -344,10 +353,10 @@ export class CAstParser extends AbstractParser {
       edges.push({
         from: ifConditionId,
         to: ifConsequenceResult.entryNodeId,
-        label: "True",
+        label: "true",
       });
     } else {
-      allExitPoints.push({ id: ifConditionId, label: "True" });
+      allExitPoints.push({ id: ifConditionId, label: "true" });
     }
 
     // Handle else clause - could be another if statement (else if) or a block
@@ -370,15 +379,15 @@ export class CAstParser extends AbstractParser {
         edges.push({
           from: ifConditionId,
           to: elseResult.entryNodeId,
-          label: "False",
+          label: "false",
         });
       } else {
-        allExitPoints.push({ id: ifConditionId, label: "False" });
+        allExitPoints.push({ id: ifConditionId, label: "false" });
       }
 
       allExitPoints.push(...elseResult.exitPoints);
     } else {
-      allExitPoints.push({ id: ifConditionId, label: "False" });
+      allExitPoints.push({ id: ifConditionId, label: "false" });
     }
 
     return this.createProcessResult(
@@ -507,7 +516,7 @@ export class CAstParser extends AbstractParser {
         edges.push({
           from: conditionId,
           to: bodyResult.entryNodeId,
-          label: conditionNode ? "True" : "Loop",
+          label: conditionNode ? "true" : "loop",
         });
       }
 
@@ -525,7 +534,7 @@ export class CAstParser extends AbstractParser {
     edges.push({
       from: conditionId,
       to: loopExitId,
-      label: conditionNode ? "False" : "End",
+      label: conditionNode ? "false" : "end",
     });
 
     return this.createProcessResult(nodes, edges, currentId, [
@@ -587,7 +596,7 @@ export class CAstParser extends AbstractParser {
         edges.push({
           from: conditionId,
           to: bodyResult.entryNodeId,
-          label: "True",
+          label: "true",
         });
       }
 
@@ -596,7 +605,7 @@ export class CAstParser extends AbstractParser {
       });
     }
 
-    edges.push({ from: conditionId, to: loopExitId, label: "False" });
+    edges.push({ from: conditionId, to: loopExitId, label: "false" });
 
     return this.createProcessResult(nodes, edges, conditionId, [
       { id: loopExitId },
@@ -665,11 +674,11 @@ export class CAstParser extends AbstractParser {
       edges.push({
         from: conditionId,
         to: bodyResult.entryNodeId,
-        label: "True",
+        label: "true",
       });
     }
 
-    edges.push({ from: conditionId, to: loopExitId, label: "False" });
+    edges.push({ from: conditionId, to: loopExitId, label: "false" });
 
     return this.createProcessResult(nodes, edges, bodyResult.entryNodeId, [
       { id: loopExitId },
@@ -690,6 +699,7 @@ export class CAstParser extends AbstractParser {
     }
 
     const switchId = this.generateNodeId("switch");
+    const endId = this.generateNodeId("switch_end");
     const nodes: FlowchartNode[] = [
       this.createSemanticNode(
         switchId,
@@ -697,6 +707,7 @@ export class CAstParser extends AbstractParser {
         NodeType.DECISION,
         conditionNode
       ),
+      this.createSemanticNode(endId, "End Switch", NodeType.MERGE, switchNode),
     ];
 
     this.locationMap.push({
@@ -707,116 +718,114 @@ export class CAstParser extends AbstractParser {
 
     const edges: FlowchartEdge[] = [];
     const nodesConnectedToExit = new Set<string>();
-    const allExitPoints: { id: string; label?: string }[] = [];
 
-    // Get case statements from the switch body
+    // `break` anywhere in a case body leaves the switch; `continue` still
+    // belongs to the enclosing loop
+    const switchContext: LoopContext = {
+      breakTargetId: endId,
+      continueTargetId: loopContext?.continueTargetId || exitId,
+    };
+
+    // `default` is a case_statement without a value
     const cases = bodyNode.namedChildren.filter(
-      (child) => child.type === "case_statement" || child.type === "default"
+      (child) => child.type === "case_statement"
     );
 
-    let lastCaseExitPoints: { id: string; label?: string }[] = [
-      { id: switchId },
-    ];
+    // The values are tested in source order with default last, but the
+    // bodies run in source order, so a case without a break falls through
+    // into the next case's body
+    const tests: string[] = [];
+    let defaultId: string | undefined;
+    let pending: { id: string; label?: string }[] = [];
 
     for (const caseNode of cases) {
-      const isDefault = caseNode.type === "default";
       const valueNode = caseNode.childForFieldName("value");
-      const caseLabel = isDefault
-        ? "default"
-        : `case ${this.escapeString(valueNode?.text || "")}`;
-
       const caseId = this.generateNodeId("case");
       nodes.push(
-        this.createSemanticNode(caseId, caseLabel, NodeType.DECISION, caseNode)
+        this.createSemanticNode(
+          caseId,
+          valueNode ? `case ${this.escapeString(valueNode.text)}` : "default",
+          NodeType.DECISION,
+          caseNode
+        )
       );
 
       this.locationMap.push({
         start: caseNode.startIndex,
-        end: caseNode.endIndex,
+        end: valueNode ? valueNode.endIndex : caseNode.endIndex,
         nodeId: caseId,
       });
 
-      // Connect from previous case or switch
-      lastCaseExitPoints.forEach((ep) => {
-        edges.push({ from: ep.id, to: caseId, label: ep.label || "no match" });
-      });
+      if (valueNode) {
+        tests.push(caseId);
+      } else {
+        defaultId = caseId;
+      }
 
-      // Process case body - get all statements that belong to this case
-      const caseBody = caseNode.namedChildren.filter(
-        (child) => child.type !== "case_statement" && child.type !== "default"
+      // Fall-through from the previous body first, then this case's match
+      let caseExitPoints: { id: string; label?: string }[] = [
+        ...pending.map((ep) => ({
+          id: ep.id,
+          label: ep.label || "fall through",
+        })),
+        { id: caseId, label: valueNode ? "match" : undefined },
+      ];
+
+      const statements = caseNode.namedChildren.filter(
+        (child) => child.id !== valueNode?.id && child.type !== "comment"
       );
+      for (const stmt of statements) {
+        const stmtResult = this.processStatementOrBlock(
+          stmt,
+          exitId,
+          switchContext,
+          finallyContext
+        );
+        nodes.push(...stmtResult.nodes);
+        edges.push(...stmtResult.edges);
+        stmtResult.nodesConnectedToExit.forEach((n) =>
+          nodesConnectedToExit.add(n)
+        );
 
-      if (caseBody.length > 0) {
-        let caseExitPoints: { id: string; label?: string }[] = [
-          { id: caseId, label: "match" },
-        ];
-
-        for (const stmt of caseBody) {
-          // Handle break statements specially in switch context
-          if (stmt.type === "break_statement") {
-            const breakResult = this.processBreakStatement(stmt, {
-              breakTargetId: exitId,
-              continueTargetId: loopContext?.continueTargetId || exitId,
+        if (stmtResult.entryNodeId) {
+          caseExitPoints.forEach((ep) => {
+            edges.push({
+              from: ep.id,
+              to: stmtResult.entryNodeId!,
+              label: ep.label,
             });
-            nodes.push(...breakResult.nodes);
-            edges.push(...breakResult.edges);
-            caseExitPoints.forEach((ep) => {
-              if (breakResult.entryNodeId) {
-                edges.push({
-                  from: ep.id,
-                  to: breakResult.entryNodeId,
-                  label: ep.label,
-                });
-              }
-            });
-            caseExitPoints = [];
-            breakResult.nodesConnectedToExit.forEach((n) =>
-              nodesConnectedToExit.add(n)
-            );
-            break; // No more statements after break
-          }
-
-          const stmtResult = this.processStatementOrBlock(
-            stmt,
-            exitId,
-            loopContext,
-            finallyContext
-          );
-          nodes.push(...stmtResult.nodes);
-          edges.push(...stmtResult.edges);
-          stmtResult.nodesConnectedToExit.forEach((n) =>
-            nodesConnectedToExit.add(n)
-          );
-
-          if (stmtResult.entryNodeId) {
-            caseExitPoints.forEach((ep) => {
-              edges.push({
-                from: ep.id,
-                to: stmtResult.entryNodeId!,
-                label: ep.label,
-              });
-            });
-            caseExitPoints = stmtResult.exitPoints;
-          }
+          });
+          caseExitPoints = stmtResult.exitPoints;
         }
-
-        allExitPoints.push(...caseExitPoints);
-        lastCaseExitPoints =
-          caseExitPoints.length > 0 ? [] : [{ id: caseId, label: "no match" }];
-      } else {
-        lastCaseExitPoints = [{ id: caseId, label: "no match" }];
-        allExitPoints.push({ id: caseId, label: "match" });
       }
+
+      // An empty case (`case 1: case 2: ...`) shares the next case's body
+      pending = caseExitPoints;
     }
 
-    // Handle cases with no match
-    allExitPoints.push(...lastCaseExitPoints);
+    // Chain the tests: each "no match" moves on to the next value, the last
+    // one to default, or past the switch when there is none
+    let previous: { id: string; label?: string } = { id: switchId };
+    for (const caseId of tests) {
+      edges.push({ from: previous.id, to: caseId, label: previous.label });
+      previous = { id: caseId, label: "no match" };
+    }
+    edges.push({
+      from: previous.id,
+      to: defaultId || endId,
+      label: previous.label,
+    });
+
+    // The last body runs off the end of the switch
+    pending.forEach((ep) => {
+      edges.push({ from: ep.id, to: endId, label: ep.label });
+    });
 
     return this.createProcessResult(
       nodes,
       edges,
       switchId,
-      allExitPoints,
+      [{ id: endId }],
       nodesConnectedToExit
     );
   }
@@ -940,8 +949,17 @@ export class CAstParser extends AbstractParser {
       gotoNode
     );
 
-    // For now, connect goto to exit (proper label handling would require more complex analysis)
-    const edges: FlowchartEdge[] = [{ from: nodeId, to: exitId }];
+    // The edge to the label is added once every label has been seen
+    if (labelNode) {
+      this.gotoJumps.push({
+        from: nodeId,
+        label: labelNode.text,
+        position: gotoNode.startIndex,
+      });
+    }
+    const edges: FlowchartEdge[] = labelNode
+      ? []
+      : [{ from: nodeId, to: exitId }];
 
     this.locationMap.push({
       start: gotoNode.startIndex,
@@ -989,6 +1007,13 @@ export class CAstParser extends AbstractParser {
       nodeId: labelId,
     });
 
+    if (labelName) {
+      this.labelTargets.set(labelName.text, {
+        id: labelId,
+        position: labeledNode.startIndex,
+      });
+    }
+
     const stmtResult = this.processStatement(
       statement,
       exitId,
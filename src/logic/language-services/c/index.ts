@@ -1,12 +1,11 @@
 import { CAstParser } from "./CAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let cParser: CAstParser | null = null;
 
 export async function initCLanguageService(wasmPath: string): Promise<void> {
   try {
     cParser = await CAstParser.create(wasmPath);
-    console.log("C language service initialized successfully");
   } catch (error) {
     console.error("Failed to initialize C language service:", error);
     throw error;
@@ -37,6 +36,26 @@ export function listCFunctions(sourceCode: string): string[] {
   return cParser.listFunctions(sourceCode);
 }
 
+export function listCFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+  if (!cParser) {
+    throw new Error(
+      "C language service not initialized. Call initCLanguageService first."
+    );
+  }
+
+  return cParser.listFunctionDefinitions(sourceCode);
+}
+
+export function listCCalls(sourceCode: string): CallSite[] {
+  if (!cParser) {
+    throw new Error(
+      "C language service not initialized. Call initCLanguageService first."
+    );
+  }
+
+  return cParser.listCalls(sourceCode);
+}
+
 export function findCFunctionAtPosition(
   sourceCode: string,
   position: number
@@ -49,3 +68,13 @@ export function findCFunctionAtPosition(
 
   return cParser.findFunctionAtPosition(sourceCode, position);
 }
+
+export function findCParseErrors(sourceCode: string): ParseErrorInfo[] {
+  if (!cParser) {
+    throw new Error(
+      "C language service not initialized. Call initCLanguageService first."
+    );
+  }
+
+  return cParser.findParseErrors(sourceCode);
+}
@@ -18,7 +18,6 @@ export async function ensureParserInit(): Promise<void> {
 
   parserInitPromise = Parser.init().then(() => {
     parserInitialized = true;
-    console.log("web-tree-sitter initialized successfully");
   });
 
   return parserInitPromise;
@@ -4,6 +4,7 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
@@ -30,20 +31,24 @@ export class CppAstParser extends AbstractParser {
     return new CppAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
 
       // Get function definitions
-      const funcNames = tree.rootNode
+      const functions = tree.rootNode
         .descendantsOfType("function_definition")
         .map((f: Parser.SyntaxNode) => {
           const declarator = f.childForFieldName("declarator");
-          return this.extractFunctionName(declarator) || "[anonymous]";
+          return {
+            name: this.extractFunctionName(declarator) || "[anonymous]",
+            start: f.startIndex,
+            end: f.endIndex,
+          };
         });
 
       // Get method definitions (inside classes)
-      const methodNames = tree.rootNode
+      const methods = tree.rootNode
         .descendantsOfType("function_definition")
         .filter((f: Parser.SyntaxNode) => {
           // Check if this function is inside a class
@@ -62,11 +67,15 @@ export class CppAstParser extends AbstractParser {
         .map((m: Parser.SyntaxNode) => {
           const declarator = m.childForFieldName("declarator");
           const funcName = this.extractFunctionName(declarator);
-          return funcName ? `${funcName} (method)` : "[anonymous method]";
+          return {
+            name: funcName ? `${funcName} (method)` : "[anonymous method]",
+            start: m.startIndex,
+            end: m.endIndex,
+          };
         });
 
       // Get lambda expressions assigned to variables
-      const lambdaNames = tree.rootNode
+      const lambdas = tree.rootNode
         .descendantsOfType("init_declarator")
         .filter((v) => {
           const value = v.childForFieldName("value");
@@ -74,10 +83,14 @@ export class CppAstParser extends AbstractParser {
         })
         .map((v) => {
           const declarator = v.childForFieldName("declarator");
-          return this.extractVariableName(declarator) || "[anonymous lambda]";
+          return {
+            name: this.extractVariableName(declarator) || "[anonymous lambda]",
+            start: v.startIndex,
+            end: v.endIndex,
+          };
         });
 
-      return [...funcNames, ...methodNames, ...lambdaNames];
+      return [...functions, ...methods, ...lambdas];
     });
   }
 
@@ -85,7 +98,7 @@ export class CppAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
 
     // Check function definitions
     let func = tree.rootNode
@@ -120,7 +133,7 @@ export class CppAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
     let targetNode: Parser.SyntaxNode | undefined;
@@ -236,6 +249,11 @@ export class CppAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -270,6 +288,8 @@ export class CppAstParser extends AbstractParser {
       }
     });
 
+    this.resolveGotoJumps(edges, exitId);
+
     const nodeIdSet = new Set(nodes.map((n) => n.id));
     const validEdges = edges.filter(
       (e) => nodeIdSet.has(e.from) && nodeIdSet.has(e.to)
@@ -285,10 +305,7 @@ export class CppAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
 
-    // Add function complexity analysis
-    this.addFunctionComplexity(ir, targetNode);
-
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   protected processStatement(
@@ -1100,9 +1117,13 @@ export class CppAstParser extends AbstractParser {
     finallyContext?: { finallyEntryId: string }
   ): ProcessResult {
     const conditionNode = switchNode.childForFieldName("condition");
-    if (!conditionNode) return this.processDefaultStatement(switchNode);
+    const bodyNode = switchNode.childForFieldName("body");
+    if (!conditionNode || !bodyNode) {
+      return this.processDefaultStatement(switchNode);
+    }
 
     const switchId = this.generateNodeId("switch");
+    const endId = this.generateNodeId("switch_end");
     const nodes: FlowchartNode[] = [
       this.createSemanticNode(
         switchId,
@@ -1110,7 +1131,9 @@ export class CppAstParser extends AbstractParser {
         NodeType.DECISION,
         switchNode
       ),
+      this.createSemanticNode(endId, "End Switch", NodeType.MERGE, switchNode),
     ];
+
     this.locationMap.push({
       start: conditionNode.startIndex,
       end: conditionNode.endIndex,
@@ -1119,118 +1142,117 @@ export class CppAstParser extends AbstractParser {
 
     const edges: FlowchartEdge[] = [];
     const nodesConnectedToExit = new Set<string>();
-    const allExitPoints: { id: string; label?: string }[] = [];
 
-    const body = switchNode.childForFieldName("body");
-    const cases =
-      body?.namedChildren.filter(
-        (child) =>
-          child.type === "case_statement" || child.type === "default_statement"
-      ) || [];
+    // `break` anywhere in a case body leaves the switch; `continue` still
+    // belongs to the enclosing loop
+    const switchContext: LoopContext = {
+      breakTargetId: endId,
+      continueTargetId: loopContext?.continueTargetId || exitId,
+    };
 
-    let lastCaseExitPoints: { id: string; label?: string }[] = [
-      { id: switchId },
-    ];
+    // `default` is a case_statement without a value
+    const cases = bodyNode.namedChildren.filter(
+      (child) => child.type === "case_statement"
+    );
+
+    // The values are tested in source order with default last, but the
+    // bodies run in source order, so a case without a break falls through
+    // into the next case's body
+    const tests: string[] = [];
+    let defaultId: string | undefined;
+    let pending: { id: string; label?: string }[] = [];
 
     for (const caseNode of cases) {
-      const isDefault = caseNode.type === "default_statement";
       const valueNode = caseNode.childForFieldName("value");
-      const caseLabel = isDefault
-        ? "default"
-        : `case ${this.escapeString(valueNode?.text || "")}`;
-
       const caseId = this.generateNodeId("case");
       nodes.push(
-        this.createSemanticNode(caseId, caseLabel, NodeType.DECISION, caseNode)
+        this.createSemanticNode(
+          caseId,
+          valueNode ? `case ${this.escapeString(valueNode.text)}` : "default",
+          NodeType.DECISION,
+          caseNode
+        )
       );
+
       this.locationMap.push({
         start: caseNode.startIndex,
-        end: caseNode.endIndex,
+        end: valueNode ? valueNode.endIndex : caseNode.endIndex,
         nodeId: caseId,
       });
 
-      // Connect from previous case or switch
-      lastCaseExitPoints.forEach((ep) => {
-        edges.push({ from: ep.id, to: caseId, label: ep.label || "no match" });
-      });
+      if (valueNode) {
+        tests.push(caseId);
+      } else {
+        defaultId = caseId;
+      }
 
-      // Process case body
-      const caseBody = caseNode.namedChildren.filter(
-        (child) =>
-          child.type !== "case_statement" && child.type !== "default_statement"
+      // Fall-through from the previous body first, then this case's match
+      let caseExitPoints: { id: string; label?: string }[] = [
+        ...pending.map((ep) => ({
+          id: ep.id,
+          label: ep.label || "fall through",
+        })),
+        { id: caseId, label: valueNode ? "match" : undefined },
+      ];
+
+      const statements = caseNode.namedChildren.filter(
+        (child) => child.id !== valueNode?.id && child.type !== "comment"
       );
+      for (const stmt of statements) {
+        const stmtResult =
+          stmt.type === "compound_statement"
+            ? this.processBlock(stmt, exitId, switchContext, finallyContext)
+            : this.processStatement(
+                stmt,
+                exitId,
+                switchContext,
+                finallyContext
+              );
+        nodes.push(...stmtResult.nodes);
+        edges.push(...stmtResult.edges);
+        stmtResult.nodesConnectedToExit.forEach((n) =>
+          nodesConnectedToExit.add(n)
+        );
 
-      if (caseBody.length > 0) {
-        let caseExitPoints: { id: string; label?: string }[] = [
-          { id: caseId, label: "match" },
-        ];
-
-        for (const stmt of caseBody) {
-          // Handle break statements specially in switch context
-          if (stmt.type === "break_statement") {
-            const breakResult = this.processBreakStatement(stmt, {
-              breakTargetId: exitId,
-              continueTargetId: loopContext?.continueTargetId || exitId,
-            });
-            nodes.push(...breakResult.nodes);
-            edges.push(...breakResult.edges);
-            caseExitPoints.forEach((ep) => {
-              if (breakResult.entryNodeId) {
-                edges.push({
-                  from: ep.id,
-                  to: breakResult.entryNodeId,
-                  label: ep.label,
-                });
-              }
-            });
-            caseExitPoints = [];
-            breakResult.nodesConnectedToExit.forEach((n) =>
-              nodesConnectedToExit.add(n)
-            );
-            break; // No more statements after break
-          }
-
-          const stmtResult = this.processStatement(
-            stmt,
-            exitId,
-            loopContext,
-            finallyContext
-          );
-          nodes.push(...stmtResult.nodes);
-          edges.push(...stmtResult.edges);
-          stmtResult.nodesConnectedToExit.forEach((n) =>
-            nodesConnectedToExit.add(n)
-          );
-
-          if (stmtResult.entryNodeId) {
-            caseExitPoints.forEach((ep) => {
-              edges.push({
-                from: ep.id,
-                to: stmtResult.entryNodeId!,
-                label: ep.label,
-              });
+        if (stmtResult.entryNodeId) {
+          caseExitPoints.forEach((ep) => {
+            edges.push({
+              from: ep.id,
+              to: stmtResult.entryNodeId!,
+              label: ep.label,
             });
-            caseExitPoints = stmtResult.exitPoints;
-          }
+          });
+          caseExitPoints = stmtResult.exitPoints;
         }
-
-        allExitPoints.push(...caseExitPoints);
-        lastCaseExitPoints =
-          caseExitPoints.length > 0 ? [] : [{ id: caseId, label: "no match" }];
-      } else {
-        lastCaseExitPoints = [{ id: caseId, label: "no match" }];
-        allExitPoints.push({ id: caseId, label: "match" });
       }
+
+      // An empty case (`case 1: case 2: ...`) shares the next case's body
+      pending = caseExitPoints;
+    }
+
+    // Chain the tests: each "no match" moves on to the next value, the last
+    // one to default, or past the switch when there is none
+    let previous: { id: string; label?: string } = { id: switchId };
+    for (const caseId of tests) {
+      edges.push({ from: previous.id, to: caseId, label: previous.label });
+      previous = { id: caseId, label: "no match" };
     }
+    edges.push({
+      from: previous.id,
+      to: defaultId || endId,
+      label: previous.label,
+    });
 
-    // Handle cases with no match
-    allExitPoints.push(...lastCaseExitPoints);
+    // The last body runs off the end of the switch
+    pending.forEach((ep) => {
+      edges.push({ from: ep.id, to: endId, label: ep.label });
+    });
 
     return this.createProcessResult(
       nodes,
       edges,
       switchId,
-      allExitPoints,
+      [{ id: endId }],
       nodesConnectedToExit
     );
   }
@@ -1411,8 +1433,16 @@ export class CppAstParser extends AbstractParser {
       end: gotoNode.endIndex,
       nodeId,
     });
-    // Note: Proper goto handling would require label tracking, simplified here
-    return this.createProcessResult([node], [], nodeId, [{ id: nodeId }]);
+    if (!labelNode) {
+      return this.createProcessResult([node], [], nodeId, [{ id: nodeId }]);
+    }
+    // The edge to the label is added once every label has been seen
+    this.gotoJumps.push({
+      from: nodeId,
+      label: labelNode.text,
+      position: gotoNode.startIndex,
+    });
+    return this.createProcessResult([node], [], nodeId, [], new Set([nodeId]));
   }
 
   private processLabeledStatement(
@@ -1441,6 +1471,10 @@ export class CppAstParser extends AbstractParser {
       end: labelNode.endIndex,
       nodeId: labelId,
     });
+    this.labelTargets.set(labelNode.text, {
+      id: labelId,
+      position: labeledNode.startIndex,
+    });
 
     const statementResult = this.processStatement(
       statementNode,
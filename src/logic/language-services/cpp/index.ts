@@ -1,12 +1,11 @@
 import { CppAstParser } from "./CppAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let cppParser: CppAstParser | null = null;
 
 export async function initCppLanguageService(wasmPath: string): Promise<void> {
   try {
     cppParser = await CppAstParser.create(wasmPath);
-    console.log("C++ language service initialized successfully");
   } catch (error) {
     console.error("Failed to initialize C++ language service:", error);
     throw error;
@@ -37,6 +36,26 @@ export function listCppFunctions(sourceCode: string): string[] {
   return cppParser.listFunctions(sourceCode);
 }
 
+export function listCppFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+  if (!cppParser) {
+    throw new Error(
+      "C++ language service not initialized. Call initCppLanguageService first."
+    );
+  }
+
+  return cppParser.listFunctionDefinitions(sourceCode);
+}
+
+export function listCppCalls(sourceCode: string): CallSite[] {
+  if (!cppParser) {
+    throw new Error(
+      "C++ language service not initialized. Call initCppLanguageService first."
+    );
+  }
+
+  return cppParser.listCalls(sourceCode);
+}
+
 export function findCppFunctionAtPosition(
   sourceCode: string,
   position: number
@@ -49,3 +68,13 @@ export function findCppFunctionAtPosition(
 
   return cppParser.findFunctionAtPosition(sourceCode, position);
 }
+
+export function findCppParseErrors(sourceCode: string): ParseErrorInfo[] {
+  if (!cppParser) {
+    throw new Error(
+      "C++ language service not initialized. Call initCppLanguageService first."
+    );
+  }
+
+  return cppParser.findParseErrors(sourceCode);
+}
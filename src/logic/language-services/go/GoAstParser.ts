@@ -4,13 +4,18 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
+  EdgeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
 import { ensureParserInit } from "../common/ParserInit";
 
-// Minimal Go parser: functions, if, for, return, break/continue, basic statements.
+// Minimal Go parser: functions, if, for, switch, select, defer, return, break/continue, basic statements.
 export class GoAstParser extends AbstractParser {
+  // Calls deferred by the function being built, by the id of their defer node
+  private deferredCalls = new Map<string, Parser.SyntaxNode>();
+
   private constructor(parser: Parser) {
     super(parser, "go");
   }
@@ -23,14 +28,16 @@ export class GoAstParser extends AbstractParser {
     return new GoAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
       return tree.rootNode
         .descendantsOfType(["function_declaration", "method_declaration"])
-        .map(
-          (f: Parser.SyntaxNode) => this.extractFunctionName(f) || "[anonymous]"
-        );
+        .map((f: Parser.SyntaxNode) => ({
+          name: this.extractFunctionName(f) || "[anonymous]",
+          start: f.startIndex,
+          end: f.endIndex,
+        }));
     });
   }
 
@@ -38,7 +45,7 @@ export class GoAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     const func = tree.rootNode
       .descendantsOfType(["function_declaration", "method_declaration"])
       .find((f) => position >= f.startIndex && position <= f.endIndex);
@@ -50,7 +57,7 @@ export class GoAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
     let targetNode: Parser.SyntaxNode | undefined;
@@ -80,6 +87,11 @@ export class GoAstParser extends AbstractParser {
     const title = `Flowchart for function: ${funcNameStr}`;
     const body = targetNode.childForFieldName("body");
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -101,10 +113,10 @@ export class GoAstParser extends AbstractParser {
             // --- FIX: ADDED functionRange ---
             functionRange: { start: targetNode.startIndex, end: targetNode.endIndex },
         };
-        this.addFunctionComplexity(ir, targetNode);
-        return ir;
+        return this.finalizeFlowchart(ir, targetNode);
     }
 
+    this.deferredCalls.clear();
     const bodyRes = this.processBlock(body, exitId);
     nodes.push(...bodyRes.nodes);
     edges.push(...bodyRes.edges);
@@ -113,6 +125,7 @@ export class GoAstParser extends AbstractParser {
         if (!bodyRes.nodesConnectedToExit.has(ep.id))
             edges.push({ from: ep.id, to: exitId, label: ep.label });
     });
+    this.routeExitsThroughDeferred(nodes, edges, entryId, exitId);
 
     const idSet = new Set(nodes.map((n) => n.id));
     const validEdges = edges.filter((e) => idSet.has(e.from) && idSet.has(e.to));
@@ -128,8 +141,7 @@ export class GoAstParser extends AbstractParser {
         // --- FIX: ADDED functionRange ---
         functionRange: { start: targetNode.startIndex, end: targetNode.endIndex },
     };
-    this.addFunctionComplexity(ir, targetNode);
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   private processStatementList(
@@ -165,7 +177,7 @@ export class GoAstParser extends AbstractParser {
         }
       }
       
-      lastExitPoints = res.exitPoints;
+      lastExitPoints = this.joinExitPoints(st, res.exitPoints, nodes, edges);
       
       if (res.entryNodeId && lastExitPoints.length === 0) {
         break; 
@@ -219,6 +231,8 @@ export class GoAstParser extends AbstractParser {
         return this.processSelect(node, exitId, loopContext);
       case "go_statement":
         return this.processGo(node, exitId, loopContext);
+      case "defer_statement":
+        return this.processDefer(node);
       case "return_statement":
         return this.processReturn(node, exitId);
       case "break_statement":
@@ -367,7 +381,7 @@ export class GoAstParser extends AbstractParser {
       const updateId = this.generateNodeId("for_update");
       result.nodes.push(this.createSemanticNode(updateId, this.summarizeNode(update), NodeType.PROCESS, update));
       this.locationMap.push({ start: update.startIndex, end: update.endIndex, nodeId: updateId });
-      result.edges.push({ from: updateId, to: headerId });
+      result.edges.push({ from: updateId, to: headerId, edgeType: EdgeType.BACK });
       continueTargetId = updateId;
     }
 
@@ -383,7 +397,11 @@ export class GoAstParser extends AbstractParser {
       }
       inner.exitPoints.forEach((ep) => {
         if (!inner.nodesConnectedToExit.has(ep.id)) {
-          result.edges.push({ from: ep.id, to: continueTargetId });
+          result.edges.push({
+            from: ep.id,
+            to: continueTargetId,
+            edgeType: continueTargetId === headerId ? EdgeType.BACK : undefined,
+          });
         }
       });
       hasBreakToEnd = inner.edges.some((e) => e.to === endId);
@@ -564,6 +582,106 @@ export class GoAstParser extends AbstractParser {
     return result;
   }
 
+  /**
+   * A defer statement only registers its call; the call itself runs when
+   * the function exits, so it is drawn again on the exit path.
+   */
+  private processDefer(node: Parser.SyntaxNode): ProcessResult {
+    const result = this.createProcessResult();
+    const call = node.namedChildren[0];
+    if (!call) return result;
+
+    const id = this.generateNodeId("defer");
+    this.deferredCalls.set(id, call);
+    result.nodes.push(
+      this.createSemanticNode(id, `defer ${this.summarizeNode(call)}`, NodeType.PROCESS, node)
+    );
+    this.locationMap.push({ start: node.startIndex, end: node.endIndex, nodeId: id });
+    result.entryNodeId = id;
+    result.exitPoints.push({ id });
+    return result;
+  }
+
+  /**
+   * Sends each edge into the exit through the deferred calls registered on
+   * every path to it, run last-registered first as Go does. A return that
+   * comes before a `defer`, or on a path that skips one inside an `if` or a
+   * loop, does not run it.
+   */
+  private routeExitsThroughDeferred(
+    nodes: FlowchartNode[],
+    edges: FlowchartEdge[],
+    entryId: string,
+    exitId: string
+  ): void {
+    if (this.deferredCalls.size === 0) return;
+
+    const registered = this.registeredDefers(nodes, edges, entryId);
+    // Of the defers registered before a node, the one that ran last
+    const latest = (defers: Set<string> | undefined): string | undefined =>
+      [...(defers ?? [])].reduce<string | undefined>(
+        (last, id) => (last && registered.get(last)!.size > registered.get(id)!.size ? last : id),
+        undefined
+      );
+
+    const runIds = new Map<string, string>();
+    const runDeferred = (deferId: string): string => {
+      const known = runIds.get(deferId);
+      if (known) return known;
+      const id = this.generateNodeId("deferred");
+      runIds.set(deferId, id);
+      const call = this.deferredCalls.get(deferId)!;
+      nodes.push(this.createSemanticNode(id, `deferred: ${this.summarizeNode(call)}`, NodeType.FUNCTION_CALL));
+      const earlier = new Set(registered.get(deferId));
+      earlier.delete(deferId);
+      const next = latest(earlier);
+      edges.push({ from: id, to: next ? runDeferred(next) : exitId });
+      return id;
+    };
+
+    for (const edge of edges.filter((candidate) => candidate.to === exitId)) {
+      const last = latest(registered.get(edge.from));
+      if (last) edge.to = runDeferred(last);
+    }
+  }
+
+  /**
+   * The defer nodes on every path from the entry to each node, the node
+   * itself included. They are its dominators among the defer nodes.
+   */
+  private registeredDefers(
+    nodes: FlowchartNode[],
+    edges: FlowchartEdge[],
+    entryId: string
+  ): Map<string, Set<string>> {
+    const predecessors = new Map<string, string[]>();
+    for (const edge of edges) {
+      predecessors.set(edge.to, [...(predecessors.get(edge.to) ?? []), edge.from]);
+    }
+
+    // Undefined until a path from the entry reaches the node
+    const registered = new Map<string, Set<string>>([[entryId, new Set()]]);
+    let changed = true;
+    while (changed) {
+      changed = false;
+      for (const node of nodes) {
+        if (node.id === entryId) continue;
+        const reached = (predecessors.get(node.id) ?? [])
+          .map((id) => registered.get(id))
+          .filter((defers): defers is Set<string> => defers !== undefined);
+        if (reached.length === 0) continue;
+        const next = new Set([...reached[0]].filter((id) => reached.every((defers) => defers.has(id))));
+        if (this.deferredCalls.has(node.id)) next.add(node.id);
+        const current = registered.get(node.id);
+        if (!current || current.size !== next.size) {
+          registered.set(node.id, next);
+          changed = true;
+        }
+      }
+    }
+    return registered;
+  }
+
   private summarizeForHeader(node: Parser.SyntaxNode): string {
     const clauseNode = node.children.find(c => c.type === 'for_clause' || c.type === 'range_clause');
     if (clauseNode?.type === "range_clause") {
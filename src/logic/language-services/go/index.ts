@@ -1,12 +1,11 @@
 import { GoAstParser } from "./GoAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let goParser: GoAstParser | null = null;
 
 export async function initGoLanguageService(wasmPath: string): Promise<void> {
   try {
     goParser = await GoAstParser.create(wasmPath);
-    console.log("Go language service initialized successfully");
   } catch (error) {
     console.error("Failed to initialize Go language service:", error);
     throw error;
@@ -36,5 +35,45 @@ export function listGoFunctions(sourceCode: string): string[] {
   return goParser.listFunctions(sourceCode);
 }
 
+export function listGoFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+  if (!goParser) {
+    throw new Error(
+      "Go language service not initialized. Call initGoLanguageService first."
+    );
+  }
+  return goParser.listFunctionDefinitions(sourceCode);
+}
+
+export function listGoCalls(sourceCode: string): CallSite[] {
+  if (!goParser) {
+    throw new Error(
+      "Go language service not initialized. Call initGoLanguageService first."
+    );
+  }
+
+  return goParser.listCalls(sourceCode);
+}
+
+export function findGoFunctionAtPosition(
+  sourceCode: string,
+  position: number
+): string | undefined {
+  if (!goParser) {
+    throw new Error(
+      "Go language service not initialized. Call initGoLanguageService first."
+    );
+  }
+  return goParser.findFunctionAtPosition(sourceCode, position);
+}
+
+export function findGoParseErrors(sourceCode: string): ParseErrorInfo[] {
+  if (!goParser) {
+    throw new Error(
+      "Go language service not initialized. Call initGoLanguageService first."
+    );
+  }
+  return goParser.findParseErrors(sourceCode);
+}
+
 export { GoAstParser };
 
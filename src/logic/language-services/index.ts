@@ -1,12 +1,6 @@
 import * as vscode from "vscode";
-import { initPythonLanguageService } from "./python";
-import { initTypeScriptLanguageService } from "./typescript";
-import { initJavaLanguageService } from "./java";
-import { initCppLanguageService } from "./cpp";
-import { initCLanguageService } from "./c";
-import { initRustLanguageService } from "./rust";
 import { EnvironmentDetector } from "../utils/EnvironmentDetector";
-import { initGoLanguageService } from "./go";
+import { LANGUAGE_SERVICES } from "./registry";
 
 /**
  * Initializes all language services for the extension.
@@ -15,85 +9,17 @@ export async function initLanguageServices(context: vscode.ExtensionContext) {
   const env = EnvironmentDetector.detectEnvironment();
   const isCompatibilityMode = env.requiresCompatibilityMode;
   
-  const services = [
-    {
-      name: "Python",
-      init: async () => {
-        const pythonWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-python.wasm"
-        ).fsPath;
-        await initPythonLanguageService(pythonWasmPath);
-      },
+  const services = LANGUAGE_SERVICES.map((definition) => ({
+    name: definition.name,
+    init: async () => {
+      const wasmPath = vscode.Uri.joinPath(
+        context.extensionUri,
+        "dist",
+        definition.wasmFile
+      ).fsPath;
+      await definition.init(wasmPath);
     },
-    {
-      name: "TypeScript",
-      init: async () => {
-        const typescriptWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-typescript.wasm"
-        ).fsPath;
-        await initTypeScriptLanguageService(typescriptWasmPath);
-      },
-    },
-    {
-      name: "Java",
-      init: async () => {
-        const javaWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-java.wasm"
-        ).fsPath;
-        await initJavaLanguageService(javaWasmPath);
-      },
-    },
-    {
-      name: "C++",
-      init: async () => {
-        const cppWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-cpp.wasm"
-        ).fsPath;
-        await initCppLanguageService(cppWasmPath);
-      },
-    },
-    {
-      name: "C",
-      init: async () => {
-        const cWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-c.wasm"
-        ).fsPath;
-        await initCLanguageService(cWasmPath);
-      },
-    },
-    {
-      name: "Rust",
-      init: async () => {
-        const rustWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-rust.wasm"
-        ).fsPath;
-        await initRustLanguageService(rustWasmPath);
-      },
-    },
-    {
-      name: "Go",
-      init: async () => {
-        const goWasmPath = vscode.Uri.joinPath(
-          context.extensionUri,
-          "dist",
-          "tree-sitter-go.wasm"
-        ).fsPath;
-        await initGoLanguageService(goWasmPath);
-      },
-    },
-  ];
+  }));
 
   let successfullyInitialized = 0;
   const errors: string[] = [];
@@ -4,6 +4,7 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
@@ -30,36 +31,42 @@ export class JavaAstParser extends AbstractParser {
     return new JavaAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
 
       // Get method declarations
-      const methodNames = tree.rootNode
+      const methods = tree.rootNode
         .descendantsOfType("method_declaration")
-        .map(
-          (m: Parser.SyntaxNode) =>
-            m.childForFieldName("name")?.text || "[anonymous method]"
-        );
+        .map((m: Parser.SyntaxNode) => ({
+          name: m.childForFieldName("name")?.text || "[anonymous method]",
+          start: m.startIndex,
+          end: m.endIndex,
+        }));
 
       // Get constructor declarations
-      const constructorNames = tree.rootNode
+      const constructors = tree.rootNode
         .descendantsOfType("constructor_declaration")
-        .map(
-          (c: Parser.SyntaxNode) =>
-            c.childForFieldName("name")?.text || "[constructor]"
-        );
+        .map((c: Parser.SyntaxNode) => ({
+          name: c.childForFieldName("name")?.text || "[constructor]",
+          start: c.startIndex,
+          end: c.endIndex,
+        }));
 
       // Get lambda expressions assigned to variables
-      const lambdaNames = tree.rootNode
+      const lambdas = tree.rootNode
         .descendantsOfType("variable_declarator")
         .filter((v) => {
           const value = v.childForFieldName("value");
           return value?.type === "lambda_expression";
         })
-        .map((v) => v.childForFieldName("name")?.text || "[anonymous lambda]");
+        .map((v) => ({
+          name: v.childForFieldName("name")?.text || "[anonymous lambda]",
+          start: v.startIndex,
+          end: v.endIndex,
+        }));
 
-      return [...methodNames, ...constructorNames, ...lambdaNames];
+      return [...methods, ...constructors, ...lambdas];
     });
   }
 
@@ -67,7 +74,7 @@ export class JavaAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
 
     // Check method declarations
     let method = tree.rootNode
@@ -102,7 +109,7 @@ export class JavaAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
     let targetNode: Parser.SyntaxNode | undefined;
@@ -230,6 +237,11 @@ export class JavaAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -279,10 +291,7 @@ export class JavaAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
 
-    // Add function complexity analysis
-    this.addFunctionComplexity(ir, targetNode);
-
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   protected processBlock(
@@ -338,7 +347,7 @@ export class JavaAstParser extends AbstractParser {
       }
 
       // The current statement's exits are now the new connection points.
-      lastExitPoints = result.exitPoints;
+      lastExitPoints = this.joinExitPoints(statement, result.exitPoints, allNodes, allEdges);
     }
 
     return this.createProcessResult(
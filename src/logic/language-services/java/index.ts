@@ -1,5 +1,5 @@
 import { JavaAstParser } from "./JavaAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let parserPromise: Promise<JavaAstParser> | null = null;
 
@@ -16,13 +16,74 @@ export function initJavaLanguageService(wasmPath: string) {
  */
 export async function analyzeJavaCode(
   code: string,
-  position: number
+  position?: number,
+  functionName?: string
 ): Promise<FlowchartIR> {
   if (!parserPromise) {
     throw new Error("Java language service not initialized.");
   }
   const parser = await parserPromise;
-  return parser.generateFlowchart(code, undefined, position);
+  return parser.generateFlowchart(code, functionName, position);
+}
+
+/**
+ * Lists the names of the Java functions defined in the code.
+ */
+export async function listJavaFunctions(code: string): Promise<string[]> {
+  if (!parserPromise) {
+    throw new Error("Java language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctions(code);
+}
+
+/**
+ * Lists the Java functions defined in the code, with where each starts.
+ */
+export async function listJavaFunctionDefinitions(code: string): Promise<FunctionDefinition[]> {
+  if (!parserPromise) {
+    throw new Error("Java language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctionDefinitions(code);
+}
+
+/**
+ * Lists the calls made inside Java functions, with their callers.
+ */
+export async function listJavaCalls(code: string): Promise<CallSite[]> {
+  if (!parserPromise) {
+    throw new Error("Java language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listCalls(code);
+}
+
+/**
+ * Returns the name of the Java function containing the offset, if any.
+ */
+export async function findJavaFunctionAtPosition(
+  code: string,
+  position: number
+): Promise<string | undefined> {
+  if (!parserPromise) {
+    throw new Error("Java language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findFunctionAtPosition(code, position);
+}
+
+/**
+ * Lists the regions of the Java code that did not parse.
+ */
+export async function findJavaParseErrors(
+  code: string
+): Promise<ParseErrorInfo[]> {
+  if (!parserPromise) {
+    throw new Error("Java language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findParseErrors(code);
 }
 
 export { JavaAstParser };
@@ -4,6 +4,7 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
@@ -30,22 +31,27 @@ export class PyAstParser extends AbstractParser {
     return new PyAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
-      const funcNames = tree.rootNode
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
+      const functions = tree.rootNode
         .descendantsOfType("function_definition")
-        .map(
-          (f: Parser.SyntaxNode) =>
-            f.childForFieldName("name")?.text || "[anonymous]"
-        );
+        .map((f: Parser.SyntaxNode) => ({
+          name: f.childForFieldName("name")?.text || "[anonymous]",
+          start: f.startIndex,
+          end: f.endIndex,
+        }));
 
-      const lambdaNames = tree.rootNode
+      const lambdas = tree.rootNode
         .descendantsOfType("assignment")
         .filter((a) => a.childForFieldName("right")?.type === "lambda")
-        .map((a) => a.childForFieldName("left")?.text || "[anonymous lambda]");
+        .map((a) => ({
+          name: a.childForFieldName("left")?.text || "[anonymous lambda]",
+          start: a.startIndex,
+          end: a.endIndex,
+        }));
 
-      return [...funcNames, ...lambdaNames];
+      return [...functions, ...lambdas];
     });
   }
 
@@ -53,21 +59,28 @@ export class PyAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
-    const func = tree.rootNode
-      .descendantsOfType("function_definition")
-      .find((f) => position >= f.startIndex && position <= f.endIndex);
-    if (func) return func.childForFieldName("name")?.text || "[anonymous]";
+    const tree = this.parseSource(sourceCode);
+    const target = this.findInnermostContaining(
+      this.getFunctionCandidates(tree.rootNode),
+      position
+    );
+    if (!target) return undefined;
+
+    return target.type === "assignment"
+      ? target.childForFieldName("left")?.text || "[anonymous lambda]"
+      : target.childForFieldName("name")?.text || "[anonymous]";
+  }
 
-    const lambda = tree.rootNode
+  /**
+   * All function-like definitions in the file: top-level and nested `def` /
+   * `async def` (including methods inside classes) and lambdas bound to names.
+   */
+  private getFunctionCandidates(root: Parser.SyntaxNode): Parser.SyntaxNode[] {
+    const functions = root.descendantsOfType("function_definition");
+    const lambdas = root
       .descendantsOfType("assignment")
-      .find(
-        (a) =>
-          position >= a.startIndex &&
-          position <= a.endIndex &&
-          a.childForFieldName("right")?.type === "lambda"
-      );
-    return lambda?.childForFieldName("left")?.text || "[anonymous lambda]";
+      .filter((a) => a.childForFieldName("right")?.type === "lambda");
+    return [...functions, ...lambdas];
   }
 
   public generateFlowchart(
@@ -75,7 +88,7 @@ export class PyAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
     if (position !== undefined) {
@@ -146,20 +159,12 @@ export class PyAstParser extends AbstractParser {
     let isLambda = false;
 
     if (position !== undefined) {
-      targetNode = tree.rootNode
-        .descendantsOfType("function_definition")
-        .find((f) => position >= f.startIndex && position <= f.endIndex);
-      if (!targetNode) {
-        targetNode = tree.rootNode
-          .descendantsOfType("assignment")
-          .find(
-            (a) =>
-              position >= a.startIndex &&
-              position <= a.endIndex &&
-              a.childForFieldName("right")?.type === "lambda"
-          );
-        isLambda = !!targetNode;
-      }
+      // Innermost match so nested functions and methods get their own chart
+      targetNode = this.findInnermostContaining(
+        this.getFunctionCandidates(tree.rootNode),
+        position
+      );
+      isLambda = targetNode?.type === "assignment";
     } else if (functionName) {
       targetNode = tree.rootNode
         .descendantsOfType("function_definition")
@@ -201,8 +206,10 @@ export class PyAstParser extends AbstractParser {
         ? targetNode.childForFieldName("left")!.text
         : targetNode.childForFieldName("name")!.text
     );
+    const isAsync =
+      !isLambda && targetNode.children.some((c) => c.type === "async");
     const title = `Flowchart for ${
-      isLambda ? "lambda" : "function"
+      isLambda ? "lambda" : isAsync ? "async function" : "function"
     }: ${funcNameStr}`;
 
     if (!bodyToProcess) {
@@ -219,6 +226,11 @@ export class PyAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -265,10 +277,7 @@ export class PyAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
 
-    // Add function complexity analysis
-    this.addFunctionComplexity(ir, targetNode);
-
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   protected processStatement(
@@ -417,7 +426,7 @@ export class PyAstParser extends AbstractParser {
               end: statement.endIndex,
               nodeId: consequenceId,
             });
-            edges.push({ from: conditionId, to: consequenceId, label: "True" });
+            edges.push({ from: conditionId, to: consequenceId, label: "true" });
 
             const alternativeId = this.generateNodeId("ternary_false");
             nodes.push(
@@ -436,7 +445,7 @@ export class PyAstParser extends AbstractParser {
             edges.push({
               from: conditionId,
               to: alternativeId,
-              label: "False",
+              label: "false",
             });
 
             return this.createProcessResult(nodes, edges, conditionId, [
@@ -610,7 +619,7 @@ export class PyAstParser extends AbstractParser {
       edges.push({ from: ep.id, to: endId, label: ep.label })
     );
 
-    return {
+    const ir: FlowchartIR = {
       nodes,
       edges,
       locationMap: this.locationMap,
@@ -622,6 +631,8 @@ export class PyAstParser extends AbstractParser {
       entryNodeId: startId,
       exitNodeId: endId,
     };
+    this.addLineNumbers(ir, statementNode);
+    return ir;
   }
 
   private processConditionalExpression(
@@ -677,14 +688,14 @@ export class PyAstParser extends AbstractParser {
       edges.push({
         from: conditionId,
         to: consequenceResult.entryNodeId,
-        label: "True",
+        label: "true",
       });
     }
     if (alternativeResult.entryNodeId) {
       edges.push({
         from: conditionId,
         to: alternativeResult.entryNodeId,
-        label: "False",
+        label: "false",
       });
     }
 
@@ -744,10 +755,10 @@ export class PyAstParser extends AbstractParser {
       edges.push({
         from: ifConditionId,
         to: ifConsequenceResult.entryNodeId,
-        label: "True",
+        label: "true",
       });
     } else {
-      allExitPoints.push({ id: ifConditionId, label: "True" });
+      allExitPoints.push({ id: ifConditionId, label: "true" });
     }
 
     let lastConditionId = ifConditionId;
@@ -778,7 +789,7 @@ export class PyAstParser extends AbstractParser {
         edges.push({
           from: lastConditionId,
           to: elifConditionId,
-          label: "False",
+          label: "false",
         });
         lastConditionId = elifConditionId;
 
@@ -798,10 +809,10 @@ export class PyAstParser extends AbstractParser {
           edges.push({
             from: elifConditionId,
             to: elifConsequenceResult.entryNodeId,
-            label: "True",
+            label: "true",
           });
         } else {
-          allExitPoints.push({ id: elifConditionId, label: "True" });
+          allExitPoints.push({ id: elifConditionId, label: "true" });
         }
         allExitPoints.push(...elifConsequenceResult.exitPoints);
       } else if (clause.type === "else_clause") {
@@ -827,14 +838,14 @@ export class PyAstParser extends AbstractParser {
         edges.push({
           from: lastConditionId,
           to: elseResult.entryNodeId,
-          label: "False",
+          label: "false",
         });
       } else {
-        allExitPoints.push({ id: lastConditionId, label: "False" });
+        allExitPoints.push({ id: lastConditionId, label: "false" });
       }
       allExitPoints.push(...elseResult.exitPoints);
     } else {
-      allExitPoints.push({ id: lastConditionId, label: "False" });
+      allExitPoints.push({ id: lastConditionId, label: "false" });
     }
 
     return this.createProcessResult(
@@ -956,16 +967,16 @@ export class PyAstParser extends AbstractParser {
       edges.push({
         from: conditionId,
         to: bodyResult.entryNodeId,
-        label: "True",
+        label: "true",
       });
     } else {
-      edges.push({ from: conditionId, to: conditionId, label: "True" });
+      edges.push({ from: conditionId, to: conditionId, label: "true" });
     }
 
     bodyResult.exitPoints.forEach((ep) =>
       edges.push({ from: ep.id, to: conditionId })
     );
-    edges.push({ from: conditionId, to: loopExitId, label: "False" });
+    edges.push({ from: conditionId, to: loopExitId, label: "false" });
 
     return this.createProcessResult(
       nodes,
@@ -1250,14 +1261,14 @@ export class PyAstParser extends AbstractParser {
         edges.push({
           from: caseConditionId,
           to: bodyResult.entryNodeId,
-          label: "True",
+          label: "true",
         });
       } else {
-        allExitPoints.push({ id: caseConditionId, label: "True" });
+        allExitPoints.push({ id: caseConditionId, label: "true" });
       }
       allExitPoints.push(...bodyResult.exitPoints);
 
-      lastConditionExit = { id: caseConditionId, label: "False" };
+      lastConditionExit = { id: caseConditionId, label: "false" };
     }
 
     allExitPoints.push(lastConditionExit);
@@ -1327,15 +1338,55 @@ export class PyAstParser extends AbstractParser {
     edges.push(...tryResult.edges);
     tryResult.nodesConnectedToExit.forEach((n) => nodesConnectedToExit.add(n));
 
+    // The else clause runs only when the try body completes without raising
+    let elseResult: ProcessResult | null = null;
+    const elseClause = tryNode.children.find((c) => c.type === "else_clause");
+    const elseBody =
+      elseClause?.childForFieldName("body") ||
+      elseClause?.namedChildren.find((c) => c.type === "block");
+    if (elseBody) {
+      elseResult = this.processBlock(
+        elseBody,
+        exitId,
+        loopContext,
+        newFinallyContext || finallyContext
+      );
+      if (elseResult.entryNodeId) {
+        nodes.push(...elseResult.nodes);
+        edges.push(...elseResult.edges);
+        elseResult.nodesConnectedToExit.forEach((n) =>
+          nodesConnectedToExit.add(n)
+        );
+      } else {
+        elseResult = null;
+      }
+    }
+
+    let normalExitPoints = tryResult.exitPoints;
     if (tryResult.entryNodeId) {
       edges.push({ from: entryId, to: tryResult.entryNodeId });
+    } else if (elseResult?.entryNodeId) {
+      edges.push({ from: entryId, to: elseResult.entryNodeId });
     } else if (finallyResult?.entryNodeId) {
       edges.push({ from: entryId, to: finallyResult.entryNodeId });
     } else {
       allExitPoints.push({ id: entryId });
     }
 
-    tryResult.exitPoints.forEach((ep) => {
+    if (elseResult?.entryNodeId) {
+      if (tryResult.entryNodeId) {
+        tryResult.exitPoints.forEach((ep) =>
+          edges.push({
+            from: ep.id,
+            to: elseResult!.entryNodeId!,
+            label: ep.label || "no exception",
+          })
+        );
+      }
+      normalExitPoints = elseResult.exitPoints;
+    }
+
+    normalExitPoints.forEach((ep) => {
       if (finallyResult?.entryNodeId) {
         edges.push({
           from: ep.id,
@@ -1496,7 +1547,7 @@ export class PyAstParser extends AbstractParser {
     );
 
     const edges: FlowchartEdge[] = [
-      { from: conditionId, to: raiseNodeId, label: "False" },
+      { from: conditionId, to: raiseNodeId, label: "false" },
       {
         from: raiseNodeId,
         to: finallyContext ? finallyContext.finallyEntryId : exitId,
@@ -1507,7 +1558,7 @@ export class PyAstParser extends AbstractParser {
       nodes,
       edges,
       conditionId,
-      [{ id: conditionId, label: "True" }],
+      [{ id: conditionId, label: "true" }],
       new Set([raiseNodeId])
     );
   }
@@ -1,5 +1,5 @@
 import { PyAstParser } from "./PyAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let parserPromise: Promise<PyAstParser> | null = null;
 
@@ -16,11 +16,72 @@ export function initPythonLanguageService(wasmPath: string) {
  */
 export async function analyzePythonCode(
   code: string,
-  position: number
+  position?: number,
+  functionName?: string
 ): Promise<FlowchartIR> {
   if (!parserPromise) {
     throw new Error("Python language service not initialized.");
   }
   const parser = await parserPromise;
-  return parser.generateFlowchart(code, undefined, position);
+  return parser.generateFlowchart(code, functionName, position);
+}
+
+/**
+ * Lists the names of the Python functions defined in the code.
+ */
+export async function listPythonFunctions(code: string): Promise<string[]> {
+  if (!parserPromise) {
+    throw new Error("Python language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctions(code);
+}
+
+/**
+ * Lists the Python functions defined in the code, with where each starts.
+ */
+export async function listPythonFunctionDefinitions(code: string): Promise<FunctionDefinition[]> {
+  if (!parserPromise) {
+    throw new Error("Python language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctionDefinitions(code);
+}
+
+/**
+ * Lists the calls made inside Python functions, with their callers.
+ */
+export async function listPythonCalls(code: string): Promise<CallSite[]> {
+  if (!parserPromise) {
+    throw new Error("Python language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listCalls(code);
+}
+
+/**
+ * Returns the name of the Python function containing the offset, if any.
+ */
+export async function findPythonFunctionAtPosition(
+  code: string,
+  position: number
+): Promise<string | undefined> {
+  if (!parserPromise) {
+    throw new Error("Python language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findFunctionAtPosition(code, position);
+}
+
+/**
+ * Lists the regions of the Python code that did not parse.
+ */
+export async function findPythonParseErrors(
+  code: string
+): Promise<ParseErrorInfo[]> {
+  if (!parserPromise) {
+    throw new Error("Python language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findParseErrors(code);
 }
@@ -0,0 +1,55 @@
+import { initPythonLanguageService } from "./python";
+import { initTypeScriptLanguageService } from "./typescript";
+import { initJavaLanguageService } from "./java";
+import { initCppLanguageService } from "./cpp";
+import { initCLanguageService } from "./c";
+import { initRustLanguageService } from "./rust";
+import { initGoLanguageService } from "./go";
+
+export interface LanguageServiceDefinition {
+  name: string;
+  wasmFile: string;
+  init: (wasmPath: string) => Promise<void>;
+}
+
+/**
+ * Every language service and the grammar it loads. Kept free of VS Code APIs
+ * so the library entry point can initialize parsers from any directory.
+ */
+export const LANGUAGE_SERVICES: LanguageServiceDefinition[] = [
+  {
+    name: "Python",
+    wasmFile: "tree-sitter-python.wasm",
+    init: async (wasmPath) => initPythonLanguageService(wasmPath),
+  },
+  {
+    name: "TypeScript",
+    wasmFile: "tree-sitter-typescript.wasm",
+    init: async (wasmPath) => initTypeScriptLanguageService(wasmPath),
+  },
+  {
+    name: "Java",
+    wasmFile: "tree-sitter-java.wasm",
+    init: async (wasmPath) => initJavaLanguageService(wasmPath),
+  },
+  {
+    name: "C++",
+    wasmFile: "tree-sitter-cpp.wasm",
+    init: initCppLanguageService,
+  },
+  {
+    name: "C",
+    wasmFile: "tree-sitter-c.wasm",
+    init: initCLanguageService,
+  },
+  {
+    name: "Rust",
+    wasmFile: "tree-sitter-rust.wasm",
+    init: initRustLanguageService,
+  },
+  {
+    name: "Go",
+    wasmFile: "tree-sitter-go.wasm",
+    init: initGoLanguageService,
+  },
+];
@@ -4,14 +4,34 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
+  EdgeType,
   Location,
+  ClassDiagramIR,
+  ClassDefinition,
+  ClassMember,
+  ClassRelation,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
 import { ensureParserInit } from "../common/ParserInit";
 
+// Macros that panic where they run, and methods that panic on `None`/`Err`
+const ABORT_MACROS: ReadonlySet<string> = new Set(["panic", "unreachable", "todo", "unimplemented"]);
+const ABORT_METHODS: ReadonlySet<string> = new Set(["unwrap", "expect", "unwrap_err", "expect_err"]);
+
 export class RustAstParser extends AbstractParser {
   private currentFunctionIsClosure = false;
+  // One shared "return Err" node per function exit, created on the first `?`
+  private errorExits = new Map<string, FlowchartNode>();
+  // One shared "abort" node per function exit, for panics and unwraps
+  private abortExits = new Map<string, FlowchartNode>();
+  // Blocks whose final expression is returned from the function or closure
+  private tailBlockIds = new Set<number>();
+  // Awaited expression per await node, written onto the edges into it
+  private awaitedExpressions = new Map<string, string>();
+  // Merge node the values of a tail `if` or `match` flow into, per exit
+  private tailMerges = new Map<string, string>();
 
   private constructor(parser: Parser) {
     super(parser, "rust");
@@ -31,20 +51,24 @@ export class RustAstParser extends AbstractParser {
     return new RustAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
 
       // Get function definitions
-      const funcNames = tree.rootNode
+      const functions = tree.rootNode
         .descendantsOfType("function_item")
         .map((f: Parser.SyntaxNode) => {
           const nameField = f.childForFieldName("name");
-          return nameField?.text || "[anonymous]";
+          return {
+            name: nameField?.text || "[anonymous]",
+            start: f.startIndex,
+            end: f.endIndex,
+          };
         });
 
       // Get closure expressions assigned to variables
-      const closureNames = tree.rootNode
+      const closures = tree.rootNode
         .descendantsOfType("let_declaration")
         .filter((v) => {
           const value = v.childForFieldName("value");
@@ -52,20 +76,28 @@ export class RustAstParser extends AbstractParser {
         })
         .map((v) => {
           const pattern = v.childForFieldName("pattern");
-          return pattern?.text || "[anonymous closure]";
+          return {
+            name: pattern?.text || "[anonymous closure]",
+            start: v.startIndex,
+            end: v.endIndex,
+          };
         });
       
       // Get method names from impl blocks
-      const methodNames = tree.rootNode
+      const methods = tree.rootNode
         .descendantsOfType("impl_item")
         .flatMap((impl) => {
           return impl.descendantsOfType("function_item").map((f) => {
             const nameField = f.childForFieldName("name");
-            return nameField?.text || "[anonymous method]";
+            return {
+              name: nameField?.text || "[anonymous method]",
+              start: f.startIndex,
+              end: f.endIndex,
+            };
           });
         });
 
-      return [...funcNames, ...closureNames, ...methodNames];
+      return [...functions, ...closures, ...methods];
     });
   }
 
@@ -73,7 +105,7 @@ export class RustAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
 
     // Check impl blocks first
     const impl = tree.rootNode
@@ -120,8 +152,13 @@ export class RustAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
+    this.errorExits.clear();
+    this.abortExits.clear();
+    this.tailBlockIds.clear();
+    this.awaitedExpressions.clear();
+    this.tailMerges.clear();
 
     let targetNode: Parser.SyntaxNode | undefined;
     let isClosure = false;
@@ -271,6 +308,11 @@ export class RustAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -283,10 +325,12 @@ export class RustAstParser extends AbstractParser {
       this.createSemanticNode(exitId, "End", NodeType.EXIT, targetNode)
     );
 
+    this.tailBlockIds.add(bodyNode.id);
     const bodyResult = this.processBlock(bodyNode, exitId);
 
     nodes.push(...bodyResult.nodes);
     edges.push(...bodyResult.edges);
+    this.appendSharedExits(exitId, nodes, edges);
 
     edges.push(
       bodyResult.entryNodeId
@@ -305,6 +349,8 @@ export class RustAstParser extends AbstractParser {
       (e) => nodeIdSet.has(e.from) && nodeIdSet.has(e.to)
     );
 
+    this.labelAwaitEdges(validEdges);
+
     const ir: FlowchartIR = {
       nodes,
       edges: validEdges,
@@ -315,9 +361,191 @@ export class RustAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
     
-    this.addFunctionComplexity(ir, targetNode);
-    
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
+  }
+
+  /**
+   * Collects the structs, enums, traits and impl blocks of a file into a
+   * class-diagram model. Methods from every `impl Type` block are merged into
+   * the type's entry, and `impl Trait for Type` adds a realization relation.
+   */
+  public generateClassDiagram(sourceCode: string): ClassDiagramIR {
+    return this.measurePerformance("generateClassDiagram", () => {
+      const tree = this.parseSource(sourceCode);
+      const classes = new Map<string, ClassDefinition>();
+      const relations: ClassRelation[] = [];
+
+      const getOrCreate = (
+        name: string,
+        kind: ClassDefinition["kind"]
+      ): ClassDefinition => {
+        let definition = classes.get(name);
+        if (!definition) {
+          definition = { name, kind, fields: [], methods: [], variants: [] };
+          classes.set(name, definition);
+        }
+        return definition;
+      };
+
+      for (const structNode of tree.rootNode.descendantsOfType("struct_item")) {
+        const name = structNode.childForFieldName("name")?.text;
+        if (!name) {
+          continue;
+        }
+        const definition = getOrCreate(name, "class");
+        definition.typeParameters =
+          structNode.childForFieldName("type_parameters")?.text;
+        definition.location = {
+          start: structNode.startIndex,
+          end: structNode.endIndex,
+        };
+        definition.fields.push(
+          ...this.getStructFields(structNode.childForFieldName("body"))
+        );
+      }
+
+      for (const enumNode of tree.rootNode.descendantsOfType("enum_item")) {
+        const name = enumNode.childForFieldName("name")?.text;
+        if (!name) {
+          continue;
+        }
+        const definition = getOrCreate(name, "enum");
+        definition.kind = "enum";
+        definition.typeParameters =
+          enumNode.childForFieldName("type_parameters")?.text;
+        definition.location = {
+          start: enumNode.startIndex,
+          end: enumNode.endIndex,
+        };
+        const variants = enumNode.childForFieldName("body")?.namedChildren || [];
+        definition.variants.push(
+          ...variants
+            .filter((v) => v.type === "enum_variant")
+            .map((v) => v.text.replace(/\s+/g, " ").replace(/,$/, "").trim())
+        );
+      }
+
+      for (const traitNode of tree.rootNode.descendantsOfType("trait_item")) {
+        const name = traitNode.childForFieldName("name")?.text;
+        if (!name) {
+          continue;
+        }
+        const definition = getOrCreate(name, "interface");
+        definition.kind = "interface";
+        definition.location = {
+          start: traitNode.startIndex,
+          end: traitNode.endIndex,
+        };
+        const items = traitNode.childForFieldName("body")?.namedChildren || [];
+        for (const item of items) {
+          if (
+            item.type === "function_signature_item" ||
+            item.type === "function_item"
+          ) {
+            // Trait items take the trait's visibility
+            definition.methods.push({
+              ...this.getMethodMember(item),
+              visibility: "public",
+            });
+          }
+        }
+      }
+
+      for (const implNode of tree.rootNode.descendantsOfType("impl_item")) {
+        const typeNode = implNode.childForFieldName("type");
+        if (!typeNode) {
+          continue;
+        }
+        const typeName = this.getBaseTypeName(typeNode.text);
+        const traitNode = implNode.childForFieldName("trait");
+        const definition = getOrCreate(typeName, "class");
+
+        if (traitNode) {
+          // Trait methods are documented on the trait, not repeated per impl
+          const traitName = this.getBaseTypeName(traitNode.text);
+          getOrCreate(traitName, "interface");
+          if (
+            !relations.some((r) => r.from === typeName && r.to === traitName)
+          ) {
+            relations.push({ from: typeName, to: traitName, kind: "realization" });
+          }
+          continue;
+        }
+
+        const items = implNode.childForFieldName("body")?.namedChildren || [];
+        for (const item of items) {
+          if (item.type === "function_item") {
+            definition.methods.push(this.getMethodMember(item));
+          }
+        }
+      }
+
+      return {
+        classes: Array.from(classes.values()),
+        relations,
+        title: "Type structure",
+      };
+    });
+  }
+
+  private getStructFields(body: Parser.SyntaxNode | null): ClassMember[] {
+    if (!body) {
+      return [];
+    }
+
+    if (body.type === "ordered_field_declaration_list") {
+      // Tuple struct: fields are positional
+      return body.namedChildren
+        .filter((child) => child.type !== "visibility_modifier")
+        .map((child, index) => ({
+          name: String(index),
+          type: child.text,
+          visibility: "public" as const,
+        }));
+    }
+
+    return body.namedChildren
+      .filter((child) => child.type === "field_declaration")
+      .map((field) => ({
+        name: field.childForFieldName("name")?.text || "?",
+        type: field.childForFieldName("type")?.text.replace(/\s+/g, " "),
+        visibility: this.getVisibility(field),
+      }));
+  }
+
+  private getMethodMember(fn: Parser.SyntaxNode): ClassMember {
+    const parameters = fn.childForFieldName("parameters");
+    const params = parameters?.namedChildren || [];
+    const hasReceiver = params.some((p) => p.type === "self_parameter");
+    const paramText = params
+      .filter((p) => p.type !== "self_parameter" && p.type !== "attribute_item")
+      .map((p) => p.text.replace(/\s+/g, " "))
+      .join(", ");
+
+    return {
+      name: fn.childForFieldName("name")?.text || "[anonymous method]",
+      type: fn.childForFieldName("return_type")?.text.replace(/\s+/g, " "),
+      parameters: paramText,
+      visibility: this.getVisibility(fn),
+      isStatic: !hasReceiver,
+    };
+  }
+
+  private getVisibility(node: Parser.SyntaxNode): ClassMember["visibility"] {
+    return node.namedChildren.some((c) => c.type === "visibility_modifier")
+      ? "public"
+      : "private";
+  }
+
+  /**
+   * Strips generic arguments and leading references so `impl<T> Foo<T>` and
+   * `impl Display for &Foo` both attach to `Foo`. Module paths are kept.
+   */
+  private getBaseTypeName(typeText: string): string {
+    return typeText
+      .replace(/^&\s*(mut\s+)?/, "")
+      .replace(/<[\s\S]*$/, "")
+      .trim();
   }
 
   private processImplItem(implNode: Parser.SyntaxNode): FlowchartIR {
@@ -365,7 +593,7 @@ export class RustAstParser extends AbstractParser {
         edges.push({ from: lastExit.id, to: exitId, label: lastExit.label });
     }
 
-    return {
+    const ir: FlowchartIR = {
         nodes,
         edges,
         locationMap: this.locationMap,
@@ -374,6 +602,8 @@ export class RustAstParser extends AbstractParser {
         entryNodeId: entryId,
         exitNodeId: exitId,
     };
+    this.addLineNumbers(ir, implNode);
+    return ir;
   }
 
   /**
@@ -403,16 +633,19 @@ export class RustAstParser extends AbstractParser {
       const internalExitTargetId = this.generateNodeId("func_internal_exit");
       
       if (bodyNode) {
+          this.tailBlockIds.add(bodyNode.id);
           const bodyResult = this.processBlock(bodyNode, internalExitTargetId);
           nodes.push(...bodyResult.nodes);
+          const bodyEdges = [...bodyResult.edges];
+          this.appendSharedExits(internalExitTargetId, nodes, bodyEdges);
           
           // Identify nodes that connect to the internal exit (i.e., return statements)
-          const returnExitPoints = bodyResult.edges
+          const returnExitPoints = bodyEdges
               .filter(e => e.to === internalExitTargetId)
               .map(e => ({ id: e.from, label: e.label }));
 
           // Add edges from the body, excluding those that point to the internal exit.
-          edges.push(...bodyResult.edges.filter(e => e.to !== internalExitTargetId));
+          edges.push(...bodyEdges.filter(e => e.to !== internalExitTargetId));
           
           // Connect the header to the entry point of the function's body.
           if (bodyResult.entryNodeId) {
@@ -524,7 +757,7 @@ protected processBlock(
     }
     
     // The exit points for the next iteration are the exit points from the statement we just processed.
-    lastExitPoints = result.exitPoints;
+    lastExitPoints = this.joinExitPoints(statement, result.exitPoints, nodes, edges);
   }
 
   return this.createProcessResult(
@@ -540,7 +773,8 @@ protected processBlock(
     statement: Parser.SyntaxNode,
     blockNode: Parser.SyntaxNode
   ): boolean {
-    if (statement.type === "expression_statement") {
+    // The tail of a loop body or a non-returned `if` is just a value
+    if (statement.type === "expression_statement" || !this.tailBlockIds.has(blockNode.id)) {
       return false; 
     }
 
@@ -549,9 +783,25 @@ protected processBlock(
       "match_expression",
       "call_expression",
       "method_call_expression",
+      "macro_invocation",
       "binary_expression",
+      "unary_expression",
+      "type_cast_expression",
+      "reference_expression",
+      "parenthesized_expression",
+      "tuple_expression",
+      "array_expression",
+      "struct_expression",
+      "range_expression",
       "identifier",
-      "literal",
+      "scoped_identifier",
+      "self",
+      "integer_literal",
+      "float_literal",
+      "string_literal",
+      "char_literal",
+      "boolean_literal",
+      "unit_expression",
       "field_expression",
       "index_expression",
       "try_expression",
@@ -562,11 +812,105 @@ protected processBlock(
     return returnableExpressions.includes(statement.type);
   }
 
+  /**
+   * Marks the blocks whose final expression becomes the value of `expr`, so
+   * their tails are drawn as returns when `expr` itself is returned.
+   */
+  private markTailBlocks(expr: Parser.SyntaxNode): void {
+    switch (expr.type) {
+      case "block":
+        this.tailBlockIds.add(expr.id);
+        break;
+      case "if_expression": {
+        const consequence = expr.childForFieldName("consequence");
+        if (consequence) {
+          this.markTailBlocks(consequence);
+        }
+        // else_clause holds either a block or the next `if`
+        expr
+          .childForFieldName("alternative")
+          ?.namedChildren.forEach((child) => this.markTailBlocks(child));
+        break;
+      }
+      case "match_expression":
+        expr
+          .childForFieldName("body")
+          ?.namedChildren.filter((arm) => arm.type === "match_arm")
+          .forEach((arm) => {
+            const value = arm.childForFieldName("value");
+            if (value) {
+              this.markTailBlocks(value);
+            }
+          });
+        break;
+    }
+  }
+
+  /**
+   * A returned value without calls or control flow of its own. Such returns
+   * are drawn as a `return` node whose edge into the exit carries the value.
+   */
+  private isPlainReturnValue(value: Parser.SyntaxNode): boolean {
+    const effectful = [
+      "call_expression",
+      "method_call_expression",
+      "macro_invocation",
+      "if_expression",
+      "match_expression",
+      "loop_expression",
+      "while_expression",
+      "for_expression",
+      "block",
+      "unsafe_block",
+      "closure_expression",
+      "try_expression",
+      "await_expression",
+      "return_expression",
+    ];
+    return (
+      !effectful.includes(value.type) &&
+      value.descendantsOfType(effectful).length === 0
+    );
+  }
+
+  private processValueReturn(
+    value: Parser.SyntaxNode,
+    statement: Parser.SyntaxNode,
+    exitId: string
+  ): ProcessResult {
+    const returnId = this.generateNodeId("return");
+    const returnNode = this.createSemanticNode(
+      returnId,
+      "return",
+      NodeType.RETURN,
+      statement
+    );
+    this.locationMap.push({
+      start: statement.startIndex,
+      end: statement.endIndex,
+      nodeId: returnId,
+    });
+
+    return this.createProcessResult(
+      [returnNode],
+      [{ from: returnId, to: exitId, label: this.escapeString(this.truncateText(value.text, 40)) }],
+      returnId,
+      [],
+      new Set([returnId])
+    );
+  }
+
   private processImplicitReturn(
     statement: Parser.SyntaxNode,
     exitId: string,
     loopContext?: LoopContext
   ): ProcessResult {
+    if (statement.type === "if_expression" || statement.type === "match_expression") {
+      return this.processBranchingTail(statement, exitId, loopContext);
+    }
+    // Inside a tail `if` or `match` the value goes to its merge node
+    const target = this.tailMerges.get(exitId) ?? exitId;
+
     // [FIX 1]: Check if the implicit return is a method call chain OR a call expression that's part of a chain
     if (statement.type === "method_call_expression" || this.isMethodCallChain(statement)) {
       const chainResult = this.processChainedMethodCalls(statement, exitId);
@@ -577,7 +921,7 @@ protected processBlock(
       
       chainResult.exitPoints.forEach((ep) => {
         if (!chainResult.nodesConnectedToExit.has(ep.id)) {
-          edges.push({ from: ep.id, to: exitId, label: ep.label || "return" });
+          edges.push({ from: ep.id, to: target, label: ep.label || "return" });
           nodesConnectedToExit.add(ep.id);
         }
       });
@@ -591,7 +935,12 @@ protected processBlock(
       );
     }
   
+    if (this.isPlainReturnValue(statement)) {
+      return this.processValueReturn(statement, statement, target);
+    }
+
     // Original logic for other implicit return types
+    this.markTailBlocks(statement);
     const exprResult = this.processStatement(statement, exitId, loopContext);
   
     if (exprResult.exitPoints.length > 0) {
@@ -600,7 +949,7 @@ protected processBlock(
   
       exprResult.exitPoints.forEach((ep) => {
         if (!exprResult.nodesConnectedToExit.has(ep.id)) {
-          edges.push({ from: ep.id, to: exitId, label: ep.label || "return" });
+          edges.push({ from: ep.id, to: target, label: ep.label || "return" });
           nodesConnectedToExit.add(ep.id);
         }
       });
@@ -617,6 +966,77 @@ protected processBlock(
     return exprResult;
   }
 
+  /**
+   * A tail `if` or `match` is the function's value: every branch that
+   * produces one flows into a merge node, which goes on to the exit once.
+   * Explicit `return`s and `?` still leave for the exit directly.
+   */
+  private processBranchingTail(
+    statement: Parser.SyntaxNode,
+    exitId: string,
+    loopContext?: LoopContext
+  ): ProcessResult {
+    const target = this.tailMerges.get(exitId) ?? exitId;
+    const mergeId = this.generateNodeId("merge");
+    this.tailMerges.set(exitId, mergeId);
+
+    let exprResult: ProcessResult;
+    try {
+      this.markTailBlocks(statement);
+      exprResult = this.processStatement(statement, exitId, loopContext);
+    } finally {
+      if (target === exitId) {
+        this.tailMerges.delete(exitId);
+      } else {
+        this.tailMerges.set(exitId, target);
+      }
+    }
+
+    const nodes = [...exprResult.nodes];
+    const edges = [...exprResult.edges];
+    const nodesConnectedToExit = new Set(exprResult.nodesConnectedToExit);
+    exprResult.exitPoints.forEach((ep) => {
+      if (!exprResult.nodesConnectedToExit.has(ep.id)) {
+        edges.push({ from: ep.id, to: mergeId, label: ep.label || "return" });
+      }
+    });
+
+    // Every branch may have returned or panicked on its own
+    if (edges.some((edge) => edge.to === mergeId)) {
+      nodes.push(this.createMergeNode(mergeId, statement));
+      edges.push({ from: mergeId, to: target });
+      nodesConnectedToExit.add(mergeId);
+    }
+
+    return this.createProcessResult(
+      nodes,
+      edges,
+      exprResult.entryNodeId,
+      [],
+      nodesConnectedToExit
+    );
+  }
+
+  /**
+   * The file's `#[cold]` functions, plus `cold_path()`, the hint that marks
+   * the branch calling it as unlikely.
+   */
+  protected getColdFunctionNames(root: Parser.SyntaxNode): ReadonlySet<string> {
+    const names = new Set(["cold_path"]);
+    for (const func of root.descendantsOfType("function_item")) {
+      const name = func.childForFieldName("name")?.text;
+      let attribute = func.previousNamedSibling;
+      while (name && attribute && (attribute.type === "attribute_item" || attribute.type.endsWith("comment"))) {
+        if (attribute.type === "attribute_item" && /^#\[\s*cold\s*\]$/.test(attribute.text)) {
+          names.add(name);
+          break;
+        }
+        attribute = attribute.previousNamedSibling;
+      }
+    }
+    return names;
+  }
+
   protected processStatement(
     statement: Parser.SyntaxNode,
     exitId: string,
@@ -706,13 +1126,14 @@ protected processBlock(
       nodeId: stmtId,
     });
 
-    return this.createProcessResult(
+    const result = this.createProcessResult(
       [stmtNode],
       [],
       stmtId,
       [{ id: stmtId }],
       new Set()
     );
+    return this.addNestedTryBranch(result, node, exitId);
   }
 
   private processExpressionStatement(
@@ -766,13 +1187,14 @@ protected processBlock(
       nodeId: stmtId,
     });
   
-    return this.createProcessResult(
+    const result = this.createProcessResult(
       [stmtNode],
       [],
       stmtId,
       [{ id: stmtId }],
       new Set<string>()
     );
+    return this.addNestedTryBranch(result, expr, exitId);
   }
 
   private getNodeTypeForExpression(exprType: string): NodeType {
@@ -851,7 +1273,12 @@ protected processBlock(
       const declId = this.generateNodeId("let");
       const declNode = this.createSemanticNode(declId, label, NodeType.ASSIGNMENT, node);
       this.locationMap.push({ start: node.startIndex, end: node.endIndex, nodeId: declId });
-      return this.createProcessResult([declNode], [], declId, [{ id: declId }], new Set());
+      // `let is_even = |x| x % 2 == 0;` shows the closure's body next to the binding
+      const nodes: FlowchartNode[] = [declNode];
+      const edges: FlowchartEdge[] = [];
+      this.addClosureFlows(value, declId, nodes, edges);
+      const result = this.createProcessResult(nodes, edges, declId, [{ id: declId }], new Set());
+      return this.addNestedTryBranch(result, value, exitId);
     }
   }
 
@@ -896,6 +1323,7 @@ protected processBlock(
     const edges: FlowchartEdge[] = [];
     const exitPoints: { id: string; label?: string }[] = [];
     const nodesConnectedToExit = new Set<string>();
+    const [thenLabel, elseLabel] = this.getBranchLabels(condition);
 
     this.locationMap.push({
       start: condition.startIndex,
@@ -916,15 +1344,18 @@ protected processBlock(
         edges.push({
           from: conditionId,
           to: thenResult.entryNodeId,
-          label: "true",
+          label: thenLabel,
         });
+      } else {
+        // An empty body: the branch goes straight on
+        exitPoints.push({ id: conditionId, label: thenLabel });
       }
       exitPoints.push(...thenResult.exitPoints);
       thenResult.nodesConnectedToExit.forEach((id) =>
         nodesConnectedToExit.add(id)
       );
     } else {
-      exitPoints.push({ id: conditionId, label: "true" });
+      exitPoints.push({ id: conditionId, label: thenLabel });
     }
 
     if (alternative) {
@@ -943,16 +1374,21 @@ protected processBlock(
           edges.push({
             from: conditionId,
             to: elseResult.entryNodeId,
-            label: "false",
+            label: elseLabel,
           });
+        } else {
+          exitPoints.push({ id: conditionId, label: elseLabel });
         }
         exitPoints.push(...elseResult.exitPoints);
         elseResult.nodesConnectedToExit.forEach((id) =>
           nodesConnectedToExit.add(id)
         );
+      } else {
+        exitPoints.push({ id: conditionId, label: elseLabel });
       }
     } else {
-      exitPoints.push({ id: conditionId, label: "false" });
+      // No else: the false edge runs straight to whatever follows the if
+      exitPoints.push({ id: conditionId, label: elseLabel });
     }
 
     return this.createProcessResult(
@@ -964,6 +1400,37 @@ protected processBlock(
     );
   }
 
+  /**
+   * The labels of the taken and not-taken edges of an `if` or `while`:
+   * true/false for a boolean condition, and for `if let` / `while let` the
+   * variant the pattern matches against the other one (Some/None, Ok/Err),
+   * or match/no match for any other pattern.
+   */
+  private getBranchLabels(condition: Parser.SyntaxNode): [string, string] {
+    if (condition.type !== "let_condition" && condition.type !== "let_chain") {
+      return ["true", "false"];
+    }
+    const pattern =
+      condition.type === "let_condition"
+        ? condition.childForFieldName("pattern")?.text.trim() || ""
+        : "";
+    const variant = /^(?:(?:std::)?(?:option::Option|result::Result|Option|Result)::)?(Some|None|Ok|Err)\b/.exec(
+      pattern
+    )?.[1];
+    switch (variant) {
+      case "Some":
+        return ["Some", "None"];
+      case "None":
+        return ["None", "Some"];
+      case "Ok":
+        return ["Ok", "Err"];
+      case "Err":
+        return ["Err", "Ok"];
+      default:
+        return ["match", "no match"];
+    }
+  }
+
   private processStatementOrBlock(
     statementOrBlock: Parser.SyntaxNode,
     exitId: string,
@@ -1009,88 +1476,80 @@ protected processBlock(
     });
 
     if (body) {
-      const arms = body.descendantsOfType("match_arm");
-      arms.forEach((arm, index) => {
+      // Only direct arms: nested matches inside an arm body are handled when
+      // that body is processed, so they stay nested under their own arm.
+      const arms = body.namedChildren.filter((child) => child.type === "match_arm");
+      // Where control goes when the previous arm did not match
+      let fallThrough: { id: string; label?: string } = { id: matchId };
+
+      for (let index = 0; index < arms.length; index++) {
+        const arm = arms[index];
         const pattern = arm.childForFieldName("pattern");
-        const guard = arm.childForFieldName("guard");
+        const guard =
+          pattern?.childForFieldName("condition") || arm.childForFieldName("guard");
         const armValue = arm.childForFieldName("value");
+        const isLastArm = index === arms.length - 1;
 
-        const patternText = pattern
-          ? this.truncateText(pattern.text)
-          : `arm_${index}`;
-
-        let currentNodeId = matchId;
-        let currentLabel = patternText;
-
-        if (guard) {
-          const guardCondition = guard.namedChild(0);
-          if (guardCondition) {
-            const guardId = this.generateNodeId("guard");
-            const guardText = this.truncateText(guardCondition.text);
-            const guardNode = this.createSemanticNode(
-              guardId,
-              `${patternText} if ${guardText}`,
-              NodeType.DECISION,
-              guard
-            );
-
-            nodes.push(guardNode);
-            edges.push({
-              from: matchId,
-              to: guardId,
-              label: patternText,
-            });
-
-            this.locationMap.push({
-              start: guard.startIndex,
-              end: guard.endIndex,
-              nodeId: guardId,
-            });
-
-            currentNodeId = guardId;
-            currentLabel = "true";
-          }
-        }
-
-        if (armValue) {
-          const armResult = this.processStatementOrBlock(
-            armValue,
-            exitId,
-            loopContext
-          );
-          nodes.push(...armResult.nodes);
-          edges.push(...armResult.edges);
+        const patternText = this.getMatchPatternText(pattern, guard, index);
+        const isWildcard = patternText === "_" && !guard;
 
-          if (armResult.entryNodeId) {
-            edges.push({
-              from: currentNodeId,
-              to: armResult.entryNodeId,
-              label: currentLabel,
-            });
-          }
-          exitPoints.push(...armResult.exitPoints);
-          armResult.nodesConnectedToExit.forEach((id) =>
-            nodesConnectedToExit.add(id)
-          );
+        let armEntry: { id: string; label?: string };
+        if (isWildcard || (isLastArm && !guard)) {
+          // Default arm: the previous arm's fall-through edge leads straight into it
+          armEntry = {
+            id: fallThrough.id,
+            label: isWildcard ? "default" : patternText,
+          };
         } else {
           const armId = this.generateNodeId("arm");
           const armNode = this.createSemanticNode(
             armId,
             patternText,
-            NodeType.PROCESS,
-            arm
+            NodeType.DECISION,
+            pattern || arm
           );
           nodes.push(armNode);
-          edges.push({ from: currentNodeId, to: armId, label: currentLabel });
-          exitPoints.push({ id: armId });
+          edges.push({ from: fallThrough.id, to: armId, label: fallThrough.label });
 
           this.locationMap.push({
-            start: arm.startIndex,
-            end: arm.endIndex,
+            start: (pattern || arm).startIndex,
+            end: (pattern || arm).endIndex,
             nodeId: armId,
           });
+
+          armEntry = {
+            id: armId,
+            label: guard ? `if ${this.truncateText(guard.text)}` : "match",
+          };
+          fallThrough = { id: armId, label: guard ? "guard fails" : "no match" };
+        }
+
+        const armResult = armValue
+          ? this.processStatementOrBlock(armValue, exitId, loopContext)
+          : this.createProcessResult();
+
+        if (armResult.entryNodeId) {
+          nodes.push(...armResult.nodes);
+          edges.push(...armResult.edges);
+          edges.push({
+            from: armEntry.id,
+            to: armResult.entryNodeId,
+            label: armEntry.label,
+          });
+          exitPoints.push(...armResult.exitPoints);
+          armResult.nodesConnectedToExit.forEach((id) =>
+            nodesConnectedToExit.add(id)
+          );
+        } else {
+          // Empty arm body: control continues after the match
+          exitPoints.push(armEntry);
         }
-      });
+
+        if (isWildcard) {
+          // Arms after `_` can never be reached
+          break;
+        }
+      }
     }
 
     return this.createProcessResult(
@@ -1102,6 +1561,26 @@ protected processBlock(
     );
   }
 
+  /**
+   * Returns the display text of a match arm pattern without its guard.
+   */
+  private getMatchPatternText(
+    pattern: Parser.SyntaxNode | null,
+    guard: Parser.SyntaxNode | null | undefined,
+    index: number
+  ): string {
+    if (!pattern) {
+      return `arm_${index}`;
+    }
+    let text = pattern.text;
+    if (guard && guard.startIndex > pattern.startIndex) {
+      text = text
+        .substring(0, guard.startIndex - pattern.startIndex)
+        .replace(/\s*if\s*$/, "");
+    }
+    return this.truncateText(text);
+  }
+
   private processWhileExpression(
     node: Parser.SyntaxNode,
     exitId: string,
@@ -1123,47 +1602,30 @@ protected processBlock(
       condition
     );
 
-    const nodes: FlowchartNode[] = [conditionNode];
-    const edges: FlowchartEdge[] = [];
-    const exitPoints: { id: string; label?: string }[] = [
-      { id: conditionId, label: "false" },
-    ];
-    const nodesConnectedToExit = new Set<string>();
-
     this.locationMap.push({
       start: condition.startIndex,
       end: condition.endIndex,
       nodeId: conditionId,
     });
 
-    if (body) {
-      const newLoopContext: LoopContext = {
-        breakTargetId: exitId,
-        continueTargetId: conditionId,
-      };
+    const [thenLabel, elseLabel] = this.getBranchLabels(condition);
+    const loopExit = this.createLoopExitNode(node);
+    const nodes: FlowchartNode[] = [conditionNode, loopExit];
+    const edges: FlowchartEdge[] = [
+      { from: conditionId, to: loopExit.id, label: elseLabel },
+    ];
+    const nodesConnectedToExit = new Set<string>();
 
-      const bodyResult = this.processStatementOrBlock(
+    if (body) {
+      const bodyResult = this.processLoopBody(
         body,
         exitId,
-        newLoopContext
+        conditionId,
+        loopExit.id,
+        thenLabel
       );
       nodes.push(...bodyResult.nodes);
       edges.push(...bodyResult.edges);
-
-      if (bodyResult.entryNodeId) {
-        edges.push({
-          from: conditionId,
-          to: bodyResult.entryNodeId,
-          label: "true",
-        });
-      }
-
-      bodyResult.exitPoints.forEach((ep) => {
-        if (!bodyResult.nodesConnectedToExit.has(ep.id)) {
-          edges.push({ from: ep.id, to: conditionId });
-        }
-      });
-
       bodyResult.nodesConnectedToExit.forEach((id) =>
         nodesConnectedToExit.add(id)
       );
@@ -1173,7 +1635,7 @@ protected processBlock(
       nodes,
       edges,
       conditionId,
-      exitPoints,
+      [{ id: loopExit.id }],
       nodesConnectedToExit
     );
   }
@@ -1193,51 +1655,42 @@ protected processBlock(
       node
     );
 
-    const nodes: FlowchartNode[] = [loopNode];
-    const edges: FlowchartEdge[] = [];
-    const exitPoints: { id: string; label?: string }[] = [];
-    const nodesConnectedToExit = new Set<string>();
-
     this.locationMap.push({
       start: node.startIndex,
       end: node.endIndex,
       nodeId: loopId,
     });
 
-    if (body) {
-      const newLoopContext: LoopContext = {
-        breakTargetId: exitId,
-        continueTargetId: loopId,
-      };
+    const loopExit = this.createLoopExitNode(node);
+    const nodes: FlowchartNode[] = [loopNode];
+    const edges: FlowchartEdge[] = [];
+    const nodesConnectedToExit = new Set<string>();
 
-      const bodyResult = this.processStatementOrBlock(
+    if (body) {
+      const bodyResult = this.processLoopBody(
         body,
         exitId,
-        newLoopContext
+        loopId,
+        loopExit.id
       );
       nodes.push(...bodyResult.nodes);
       edges.push(...bodyResult.edges);
-
-      if (bodyResult.entryNodeId) {
-        edges.push({ from: loopId, to: bodyResult.entryNodeId });
-      }
-      
-      bodyResult.exitPoints.forEach((ep) => {
-        if (!bodyResult.nodesConnectedToExit.has(ep.id)) {
-          edges.push({ from: ep.id, to: loopId });
-        }
-      });
-
       bodyResult.nodesConnectedToExit.forEach((id) =>
         nodesConnectedToExit.add(id)
       );
     }
 
+    // A `loop` only terminates through `break`; without one nothing follows it
+    const hasBreak = edges.some((e) => e.to === loopExit.id);
+    if (hasBreak) {
+      nodes.push(loopExit);
+    }
+
     return this.createProcessResult(
       nodes,
       edges,
       loopId,
-      exitPoints,
+      hasBreak ? [{ id: loopExit.id }] : [],
       nodesConnectedToExit
     );
   }
@@ -1262,43 +1715,29 @@ protected processBlock(
       node
     );
 
-    const nodes: FlowchartNode[] = [forNode];
-    const edges: FlowchartEdge[] = [];
-    const exitPoints: { id: string; label?: string }[] = [
-      { id: forId, label: "done" },
-    ];
-    const nodesConnectedToExit = new Set<string>();
-
     this.locationMap.push({
       start: node.startIndex,
       end: node.endIndex,
       nodeId: forId,
     });
 
-    if (body) {
-      const newLoopContext: LoopContext = {
-        breakTargetId: exitId,
-        continueTargetId: forId,
-      };
+    const loopExit = this.createLoopExitNode(node);
+    const nodes: FlowchartNode[] = [forNode, loopExit];
+    const edges: FlowchartEdge[] = [
+      { from: forId, to: loopExit.id, label: "done" },
+    ];
+    const nodesConnectedToExit = new Set<string>();
 
-      const bodyResult = this.processStatementOrBlock(
+    if (body) {
+      const bodyResult = this.processLoopBody(
         body,
         exitId,
-        newLoopContext
+        forId,
+        loopExit.id,
+        "next"
       );
       nodes.push(...bodyResult.nodes);
       edges.push(...bodyResult.edges);
-
-      if (bodyResult.entryNodeId) {
-        edges.push({ from: forId, to: bodyResult.entryNodeId });
-      }
-      
-      bodyResult.exitPoints.forEach((ep) => {
-        if (!bodyResult.nodesConnectedToExit.has(ep.id)) {
-          edges.push({ from: ep.id, to: forId });
-        }
-      });
-
       bodyResult.nodesConnectedToExit.forEach((id) =>
         nodesConnectedToExit.add(id)
       );
@@ -1308,11 +1747,85 @@ protected processBlock(
       nodes,
       edges,
       forId,
-      exitPoints,
+      [{ id: loopExit.id }],
       nodesConnectedToExit
     );
   }
 
+  /**
+   * Creates the node every exit of a loop converges on: the header's
+   * false/done edge and any `break` inside the body.
+   */
+  private createLoopExitNode(loopNode: Parser.SyntaxNode): FlowchartNode {
+    // No syntax node: the exit marker should not claim the loop's source range
+    const exitNode = this.createSemanticNode(
+      this.generateNodeId("loop_exit"),
+      "end loop",
+      NodeType.LOOP_END
+    );
+    exitNode.location = { start: loopNode.endIndex, end: loopNode.endIndex };
+    return exitNode;
+  }
+
+  /**
+   * Processes a loop body and wires it to its header: the header enters the
+   * body, and every normal exit of the body returns to the header through a
+   * back-edge. `break`/`continue` inside target the loop exit and header.
+   */
+  private processLoopBody(
+    body: Parser.SyntaxNode,
+    exitId: string,
+    headerId: string,
+    loopExitId: string,
+    entryLabel?: string
+  ): ProcessResult {
+    const newLoopContext: LoopContext = {
+      breakTargetId: loopExitId,
+      continueTargetId: headerId,
+    };
+
+    const bodyResult = this.processStatementOrBlock(
+      body,
+      exitId,
+      newLoopContext
+    );
+    const edges = [...bodyResult.edges];
+
+    if (bodyResult.entryNodeId) {
+      edges.push({
+        from: headerId,
+        to: bodyResult.entryNodeId,
+        label: entryLabel,
+      });
+      bodyResult.exitPoints.forEach((ep) => {
+        if (!bodyResult.nodesConnectedToExit.has(ep.id)) {
+          edges.push({
+            from: ep.id,
+            to: headerId,
+            label: ep.label,
+            edgeType: EdgeType.BACK,
+          });
+        }
+      });
+    } else {
+      // Empty body: the header loops straight back onto itself
+      edges.push({
+        from: headerId,
+        to: headerId,
+        label: entryLabel,
+        edgeType: EdgeType.BACK,
+      });
+    }
+
+    return this.createProcessResult(
+      bodyResult.nodes,
+      edges,
+      bodyResult.entryNodeId,
+      [],
+      bodyResult.nodesConnectedToExit
+    );
+  }
+
   private processBreakExpression(
     node: Parser.SyntaxNode,
     exitId: string,
@@ -1372,7 +1885,11 @@ protected processBlock(
     });
 
     if (loopContext) {
-      edges.push({ from: continueId, to: loopContext.continueTargetId });
+      edges.push({
+        from: continueId,
+        to: loopContext.continueTargetId,
+        edgeType: EdgeType.BACK,
+      });
       nodesConnectedToExit.add(continueId);
     }
 
@@ -1419,8 +1936,13 @@ protected processBlock(
       );
     }
 
+    if (this.isPlainReturnValue(value)) {
+      return this.processValueReturn(value, node, exitId);
+    }
+
     // Recursively process the expression within the return statement.
     // This will correctly delegate to `processChainedMethodCalls` for method chains.
+    this.markTailBlocks(value);
     const valueResult = this.processStatement(value, exitId);
 
     // Add the edges from the processed expression.
@@ -1479,13 +2001,14 @@ protected processBlock(
       nodeId: assignId,
     });
 
-    return this.createProcessResult(
+    const result = this.createProcessResult(
       [assignNode],
       [],
       assignId,
       [{ id: assignId }],
       new Set()
     );
+    return right ? this.addNestedTryBranch(result, right, exitId) : result;
   }
 
   private processCallExpression(
@@ -1517,40 +2040,22 @@ protected processBlock(
       nodeId: callId,
     });
 
-    const closureNodes = this.findClosuresInArguments(arguments_node);
     const nodes: FlowchartNode[] = [callNode];
     const edges: FlowchartEdge[] = [];
+    if (arguments_node) {
+      this.addClosureFlows(arguments_node, callId, nodes, edges);
+    }
 
-    closureNodes.forEach((closure, index) => {
-      const closureBody = closure.childForFieldName("body");
-      if (closureBody) {
-        const closureId = this.generateNodeId(`closure_${index}`);
-        const closureHeaderNode = this.createSemanticNode(
-          closureId,
-          `closure ${index + 1}`,
-          NodeType.FUNCTION_CALL,
-          closure
-        );
-        nodes.push(closureHeaderNode);
-        edges.push({ from: callId, to: closureId, label: `arg ${index + 1}` });
-
-        const closureResult = this.processBlock(closureBody, exitId);
-        nodes.push(...closureResult.nodes);
-        edges.push(...closureResult.edges);
-
-        if (closureResult.entryNodeId) {
-          edges.push({ from: closureId, to: closureResult.entryNodeId });
-        }
-      }
-    });
-
-    return this.createProcessResult(
+    const result = this.createProcessResult(
       nodes,
       edges,
       callId,
       [{ id: callId }],
       new Set<string>()
     );
+    return arguments_node
+      ? this.addNestedTryBranch(result, arguments_node, exitId)
+      : result;
   }
 
   private isMethodCallChain(node: Parser.SyntaxNode): boolean {
@@ -1675,39 +2180,15 @@ protected processBlock(
         }
       }
   
-      // Handle closures in method arguments - create branches for complex closures
+      // `.unwrap()` and `.expect()` continue with the value or panic
+      if (ABORT_METHODS.has(method)) {
+        edges.push({ from: methodId, to: this.getAbortExitId(exitId), label: "panic" });
+      }
+
+      // Closures passed to the call (`.filter(|x| ...)`) branch off into their own flow
       const argsNode = methodNode.childForFieldName('arguments');
       if (argsNode) {
-        const closures = this.findClosuresInArguments(argsNode);
-        
-        closures.forEach((closure, closureIndex) => {
-          const closureBody = closure.childForFieldName("body");
-          
-          if (closureBody && this.isComplexClosureBody(closureBody)) {
-            const closureId = this.generateNodeId(`closure_${closureIndex}`);
-            const params = this.getClosureParameters(closure);
-            const closureHeaderNode = this.createSemanticNode(
-              closureId,
-              `|${params}| { ... }`,
-              NodeType.FUNCTION_CALL,
-              closure
-            );
-            nodes.push(closureHeaderNode);
-            edges.push({ from: methodId, to: closureId, label: `closure` });
-
-            const internalClosureExitId = this.generateNodeId('closure_internal_exit');
-            const closureResult = this.processBlock(closureBody, internalClosureExitId);
-            
-            nodes.push(...closureResult.nodes);
-            edges.push(...closureResult.edges.filter(e => e.to !== internalClosureExitId));
-
-            if (closureResult.entryNodeId) {
-              edges.push({ from: closureId, to: closureResult.entryNodeId });
-            }
-            
-            closureResult.nodesConnectedToExit.forEach(id => nodesConnectedToExit.add(id));
-          }
-        });
+        this.addClosureFlows(argsNode, methodId, nodes, edges);
       }
   
       // For the next method in the chain, the flow continues from this method call node.
@@ -1724,39 +2205,51 @@ protected processBlock(
   }
 
   /**
-   * Determines if a closure's body is complex enough to warrant its own branch.
-   * @param body The closure body node.
-   * @returns True if the closure body is considered complex.
+   * Draws every closure in `node` (a call's arguments, or the value bound by
+   * a `let`) as a small flow of its own branching off `callId`: a header
+   * with its parameters, then its body, whose returns (including the value
+   * of an expression body) lead to an End Closure node. A `return`, `?` or
+   * panic inside it leaves the closure, not the function, so it gets its
+   * own shared exits.
    */
-  private isComplexClosureBody(body: Parser.SyntaxNode): boolean {
-    // A block is complex if it has more than one statement,
-    // or if its single statement is itself a complex structure.
-    if (body.type === 'block') {
-        const statements = body.namedChildren.filter(
-            c => c.type !== 'line_comment' && c.type !== 'block_comment'
-        );
-        if (statements.length > 1) {
-            return true;
-        }
-        if (statements.length === 1) {
-            // Check if the single statement is complex (e.g., if, match, loop)
-            return this.isComplexExpression(statements[0]);
+  private addClosureFlows(
+    node: Parser.SyntaxNode,
+    callId: string,
+    nodes: FlowchartNode[],
+    edges: FlowchartEdge[]
+  ): void {
+    for (const closure of this.findClosuresInArguments(node)) {
+      const body = closure.childForFieldName("body");
+      if (!body) {
+        continue;
+      }
+      const closureId = this.generateNodeId("closure");
+      const header = closure.text.slice(0, body.startIndex - closure.startIndex).replace(/\s+/g, " ").trim();
+      nodes.push(this.createSemanticNode(closureId, header, NodeType.FUNCTION_CALL, closure));
+      const parameters = closure.childForFieldName("parameters") ?? closure;
+      this.locationMap.push({ start: parameters.startIndex, end: parameters.endIndex, nodeId: closureId });
+      edges.push({ from: callId, to: closureId, label: "closure" });
+
+      const closureExitId = this.generateNodeId("closure_end");
+      let bodyResult: ProcessResult;
+      if (body.type === "block") {
+        this.tailBlockIds.add(body.id);
+        bodyResult = this.processBlock(body, closureExitId);
+      } else {
+        bodyResult = this.processImplicitReturn(body, closureExitId);
+      }
+      nodes.push(...bodyResult.nodes);
+      edges.push(...bodyResult.edges);
+      edges.push({ from: closureId, to: bodyResult.entryNodeId ?? closureExitId });
+      // A block ending in a statement returns `()`
+      for (const exitPoint of bodyResult.exitPoints) {
+        if (!bodyResult.nodesConnectedToExit.has(exitPoint.id)) {
+          edges.push({ from: exitPoint.id, to: closureExitId, label: exitPoint.label });
         }
+      }
+      nodes.push(this.createSemanticNode(closureExitId, "End Closure", NodeType.MERGE, undefined, "round"));
+      this.appendSharedExits(closureExitId, nodes, edges);
     }
-    // If it's not a block (e.g., a simple expression like `word.len() > 2`), treat it as simple.
-    return false;
-  }
-
-  /**
-  * Helper method to extract closure parameters for better labeling
-  */
-  private getClosureParameters(closure: Parser.SyntaxNode): string {
-    const params = closure.childForFieldName("parameters");
-    if (params) {
-      // Remove the | | wrapper and clean up whitespace
-      return params.text.slice(1, -1).trim();
-    }
-    return "";
   }
 
   private processMacroInvocation(
@@ -1772,11 +2265,11 @@ protected processBlock(
       label += argumentsNode.text;
     }
 
-    if (macroName === "panic!") {
+    if (ABORT_MACROS.has(this.getMacroName(node))) {
       const panicId = this.generateNodeId("panic");
       const panicNode = this.createSemanticNode(
         panicId,
-        `panic!${argumentsNode?.text || "()"}`,
+        `${this.getMacroName(node)}!${argumentsNode?.text || "()"}`,
         NodeType.PANIC,
         node
       );
@@ -1787,7 +2280,7 @@ protected processBlock(
         nodeId: panicId,
       });
 
-      const edges: FlowchartEdge[] = [{ from: panicId, to: exitId }];
+      const edges: FlowchartEdge[] = [{ from: panicId, to: this.getAbortExitId(exitId) }];
       const nodesConnectedToExit = new Set([panicId]);
 
       return this.createProcessResult(
@@ -1823,13 +2316,14 @@ protected processBlock(
       nodeId: callId,
     });
 
-    return this.createProcessResult(
+    const result = this.createProcessResult(
       [callNode],
       [],
       callId,
       [{ id: callId }],
       new Set()
     );
+    return this.addNestedTryBranch(result, node, exitId);
   }
 
   private processTryExpression(
@@ -1851,10 +2345,11 @@ protected processBlock(
       const awaitTryId = this.generateNodeId("await_try");
       const awaitTryNode = this.createSemanticNode(
         awaitTryId,
-        `${this.truncateText(innerExpr.text)}.await?`,
+        "await?",
         NodeType.AWAIT,
         node
       );
+      this.awaitedExpressions.set(awaitTryId, innerExpr.text);
 
       this.locationMap.push({
         start: node.startIndex,
@@ -1867,10 +2362,10 @@ protected processBlock(
       ];
       const nodesConnectedToExit = new Set<string>();
       
+      // The Ok path continues, so the node is not terminal despite the Err edge
       const edges: FlowchartEdge[] = [
-        { from: awaitTryId, to: exitId, label: "Err" },
+        { from: awaitTryId, to: this.getErrorExitId(exitId), label: "Err" },
       ];
-      nodesConnectedToExit.add(awaitTryId);
 
       return this.createProcessResult(
         [awaitTryNode],
@@ -1918,8 +2413,7 @@ protected processBlock(
         edges.push({ from: exprResult.entryNodeId, to: tryId });
       }
 
-      edges.push({ from: tryId, to: exitId, label: "Err" });
-      nodesConnectedToExit.add(tryId);
+      edges.push({ from: tryId, to: this.getErrorExitId(exitId), label: "Err" });
 
       return this.createProcessResult(
         nodes,
@@ -1949,9 +2443,8 @@ protected processBlock(
       });
       
       const edges: FlowchartEdge[] = [
-        { from: tryId, to: exitId, label: "Err" },
+        { from: tryId, to: this.getErrorExitId(exitId), label: "Err" },
       ];
-      nodesConnectedToExit.add(tryId);
 
       return this.createProcessResult(
         [tryNode],
@@ -1963,6 +2456,144 @@ protected processBlock(
     }
   }
 
+  /**
+   * Returns the shared error-return node for the function owning `exitId`,
+   * creating it on first use so every `?` in a function converges on one node.
+   */
+  private getErrorExitId(exitId: string): string {
+    let errorExit = this.errorExits.get(exitId);
+    if (!errorExit) {
+      errorExit = this.createSemanticNode(
+        this.generateNodeId("error_exit"),
+        "return Err",
+        NodeType.EARLY_RETURN_ERROR
+      );
+      this.errorExits.set(exitId, errorExit);
+    }
+    return errorExit.id;
+  }
+
+  /**
+   * Returns the shared abort node for the function owning `exitId`. Every
+   * panic and unwrap ends there instead of at the function exit, since the
+   * function does not return from them.
+   */
+  private getAbortExitId(exitId: string): string {
+    let abortExit = this.abortExits.get(exitId);
+    if (!abortExit) {
+      abortExit = this.createSemanticNode(
+        this.generateNodeId("abort_exit"),
+        "abort",
+        NodeType.PANIC,
+        undefined,
+        "round"
+      );
+      this.abortExits.set(exitId, abortExit);
+    }
+    return abortExit.id;
+  }
+
+  /**
+   * Adds the function's shared error-return node (if any `?` created one)
+   * with its edge to the function exit, and its abort node (if anything
+   * can panic), which has no way out.
+   */
+  private appendSharedExits(
+    exitId: string,
+    nodes: FlowchartNode[],
+    edges: FlowchartEdge[]
+  ): void {
+    const errorExit = this.errorExits.get(exitId);
+    if (errorExit) {
+      nodes.push(errorExit);
+      edges.push({ from: errorExit.id, to: exitId });
+    }
+    const abortExit = this.abortExits.get(exitId);
+    if (abortExit) {
+      nodes.push(abortExit);
+    }
+  }
+
+  /**
+   * A `?` nested inside an otherwise atomic statement (a call argument, a
+   * macro argument, the right side of an assignment) still returns early, so
+   * the statement node branches to the error exit just like a bare `?`.
+   * A nested `unwrap()` or `panic!` likewise branches to the abort node.
+   */
+  private addNestedTryBranch(
+    result: ProcessResult,
+    node: Parser.SyntaxNode,
+    exitId: string
+  ): ProcessResult {
+    if (result.exitPoints.length === 1 && this.containsAbort(node)) {
+      result.edges.push({
+        from: result.exitPoints[0].id,
+        to: this.getAbortExitId(exitId),
+        label: "panic",
+      });
+    }
+    if (result.exitPoints.length !== 1 || !this.containsTryExpression(node)) {
+      return result;
+    }
+    const statementId = result.exitPoints[0].id;
+    result.edges.push({
+      from: statementId,
+      to: this.getErrorExitId(exitId),
+      label: "Err",
+    });
+    result.exitPoints = [{ id: statementId, label: "Ok" }];
+    return result;
+  }
+
+  private containsAbort(node: Parser.SyntaxNode): boolean {
+    return node
+      .descendantsOfType(["call_expression", "method_call_expression", "macro_invocation"])
+      .some((candidate) => {
+        if (!this.isAbortPoint(candidate)) {
+          return false;
+        }
+        // A panic inside a closure happens when the closure is called
+        for (let p = candidate.parent; p && p.id !== node.id; p = p.parent) {
+          if (p.type === "closure_expression") {
+            return false;
+          }
+        }
+        return true;
+      });
+  }
+
+  /** `panic!`, `todo!` and the like, or a call of `.unwrap()`/`.expect()`. */
+  private isAbortPoint(node: Parser.SyntaxNode): boolean {
+    if (node.type === "macro_invocation") {
+      return ABORT_MACROS.has(this.getMacroName(node));
+    }
+    const method =
+      node.type === "method_call_expression"
+        ? node.childForFieldName("method")
+        : node.childForFieldName("function")?.type === "field_expression"
+          ? node.childForFieldName("function")?.childForFieldName("field")
+          : null;
+    return !!method && ABORT_METHODS.has(method.text);
+  }
+
+  /** `std::panic!` and `panic!` are both `panic`. */
+  private getMacroName(node: Parser.SyntaxNode): string {
+    const name = node.childForFieldName("macro")?.text || "";
+    return name.replace(/!$/, "").split("::").pop() || name;
+  }
+
+  private containsTryExpression(node: Parser.SyntaxNode): boolean {
+    return node.descendantsOfType("try_expression").some((tryNode) => {
+      // A `?` inside a closure returns from the closure, not this function
+      for (let p = tryNode.parent; p && p.id !== node.id; p = p.parent) {
+        if (p.type === "closure_expression") {
+          return false;
+        }
+      }
+      return true;
+    });
+  }
+
   private processComplexExpression(
     expr: Parser.SyntaxNode,
     exitId: string
@@ -2047,13 +2678,9 @@ protected processBlock(
     if (this.shouldDecomposeExpression(expr)) {
       const exprResult = this.processComplexExpression(expr, exitId);
 
+      // The decomposed expression is drawn just above, so the marker stays bare
       const awaitId = this.generateNodeId("await");
-      const awaitNode = this.createSemanticNode(
-        awaitId,
-        `await ${this.truncateText(expr.text)}`,
-        NodeType.AWAIT,
-        node
-      );
+      const awaitNode = this.createSemanticNode(awaitId, "await", NodeType.AWAIT, node);
 
       const nodes: FlowchartNode[] = [...exprResult.nodes, awaitNode];
       const edges: FlowchartEdge[] = [...exprResult.edges];
@@ -2087,12 +2714,8 @@ protected processBlock(
       );
     } else {
       const awaitId = this.generateNodeId("await");
-      const awaitNode = this.createSemanticNode(
-        awaitId,
-        `await ${this.truncateText(expr.text)}`,
-        NodeType.AWAIT,
-        node
-      );
+      const awaitNode = this.createSemanticNode(awaitId, "await", NodeType.AWAIT, node);
+      this.awaitedExpressions.set(awaitId, expr.text);
       
       this.locationMap.push({
         start: node.startIndex,
@@ -2110,6 +2733,21 @@ protected processBlock(
     }
   }
 
+  /**
+   * Writes the awaited expression onto every control-flow edge into an
+   * await marker, keeping a branch label in front ("Ok: fetch(url)").
+   */
+  private labelAwaitEdges(edges: FlowchartEdge[]): void {
+    for (const edge of edges) {
+      const awaited = this.awaitedExpressions.get(edge.to);
+      if (awaited === undefined || edge.edgeType === EdgeType.BACK) {
+        continue;
+      }
+      const text = this.escapeString(this.truncateText(awaited, 40));
+      edge.label = edge.label ? `${edge.label}: ${text}` : text;
+    }
+  }
+
   /**
   * Enhanced method to find closures in function arguments
   */
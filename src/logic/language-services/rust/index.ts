@@ -1,12 +1,11 @@
 import { RustAstParser } from "./RustAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ClassDiagramIR, ParseErrorInfo } from "../../../ir/ir";
 
 let rustParser: RustAstParser | null = null;
 
 export async function initRustLanguageService(wasmPath: string): Promise<void> {
   try {
     rustParser = await RustAstParser.create(wasmPath);
-    console.log("Rust language service initialized successfully");
   } catch (error) {
     console.error("Failed to initialize Rust language service:", error);
     throw error;
@@ -37,6 +36,26 @@ export function listRustFunctions(sourceCode: string): string[] {
   return rustParser.listFunctions(sourceCode);
 }
 
+export function listRustFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+  if (!rustParser) {
+    throw new Error(
+      "Rust language service not initialized. Call initRustLanguageService first."
+    );
+  }
+
+  return rustParser.listFunctionDefinitions(sourceCode);
+}
+
+export function listRustCalls(sourceCode: string): CallSite[] {
+  if (!rustParser) {
+    throw new Error(
+      "Rust language service not initialized. Call initRustLanguageService first."
+    );
+  }
+
+  return rustParser.listCalls(sourceCode);
+}
+
 export function findRustFunctionAtPosition(
   sourceCode: string,
   position: number
@@ -49,3 +68,23 @@ export function findRustFunctionAtPosition(
 
   return rustParser.findFunctionAtPosition(sourceCode, position);
 }
+
+export function findRustParseErrors(sourceCode: string): ParseErrorInfo[] {
+  if (!rustParser) {
+    throw new Error(
+      "Rust language service not initialized. Call initRustLanguageService first."
+    );
+  }
+
+  return rustParser.findParseErrors(sourceCode);
+}
+
+export function analyzeRustTypes(sourceCode: string): ClassDiagramIR {
+  if (!rustParser) {
+    throw new Error(
+      "Rust language service not initialized. Call initRustLanguageService first."
+    );
+  }
+
+  return rustParser.generateClassDiagram(sourceCode);
+}
@@ -4,13 +4,46 @@ import {
   FlowchartIR,
   FlowchartNode,
   FlowchartEdge,
+  FunctionDefinition,
   NodeType,
 } from "../../../ir/ir";
 import { ProcessResult, LoopContext } from "../../common/AstParserTypes";
 import { ensureParserInit } from "../common/ParserInit";
 
+/**
+ * A function the user can pick: the syntax node its range and name come
+ * from (the declaration, or the binding for function values) and the
+ * function node that owns the parameters and body.
+ */
+interface FunctionTarget {
+  node: Parser.SyntaxNode;
+  functionNode: Parser.SyntaxNode;
+  name: string;
+  kind: "function" | "method" | "arrow function";
+}
+
+const FUNCTION_DECLARATION_TYPES = new Set([
+  "function_declaration",
+  "generator_function_declaration",
+]);
+
+/** Function-valued expressions, named by what they are bound to. */
+const FUNCTION_VALUE_TYPES = new Set([
+  "arrow_function",
+  "function",
+  "function_expression",
+  "generator_function",
+]);
+
+/** Binding nodes and the field holding the bound name. */
+const FUNCTION_BINDING_NAME_FIELDS: Record<string, string> = {
+  variable_declarator: "name",
+  public_field_definition: "name",
+  pair: "key",
+};
+
 export class TsAstParser extends AbstractParser {
-  private currentFunctionIsArrow = false;
+  private currentBodyIsExpression = false;
 
   private constructor(parser: Parser) {
     super(parser, "typescript");
@@ -30,36 +63,14 @@ export class TsAstParser extends AbstractParser {
     return new TsAstParser(parser);
   }
 
-  public listFunctions(sourceCode: string): string[] {
-    return this.measurePerformance("listFunctions", () => {
-      const tree = this.parser.parse(sourceCode);
-
-      // Get regular function declarations
-      const funcNames = tree.rootNode
-        .descendantsOfType("function_declaration")
-        .map(
-          (f: Parser.SyntaxNode) =>
-            f.childForFieldName("name")?.text || "[anonymous]"
-        );
-
-      // Get method definitions in classes
-      const methodNames = tree.rootNode
-        .descendantsOfType("method_definition")
-        .map(
-          (m: Parser.SyntaxNode) =>
-            m.childForFieldName("name")?.text || "[anonymous method]"
-        );
-
-      // Get arrow functions assigned to variables/constants
-      const arrowFunctionNames = tree.rootNode
-        .descendantsOfType("variable_declarator")
-        .filter((v) => {
-          const value = v.childForFieldName("value");
-          return value?.type === "arrow_function";
-        })
-        .map((v) => v.childForFieldName("name")?.text || "[anonymous arrow]");
-
-      return [...funcNames, ...methodNames, ...arrowFunctionNames];
+  public listFunctionDefinitions(sourceCode: string): FunctionDefinition[] {
+    return this.measurePerformance("listFunctionDefinitions", () => {
+      const tree = this.parseSource(sourceCode);
+      return this.collectFunctions(tree.rootNode).map((f) => ({
+        name: f.name,
+        start: f.node.startIndex,
+        end: f.node.endIndex,
+      }));
     });
   }
 
@@ -67,33 +78,8 @@ export class TsAstParser extends AbstractParser {
     sourceCode: string,
     position: number
   ): string | undefined {
-    const tree = this.parser.parse(sourceCode);
-
-    // Check function declarations
-    let func = tree.rootNode
-      .descendantsOfType("function_declaration")
-      .find((f) => position >= f.startIndex && position <= f.endIndex);
-    if (func) return func.childForFieldName("name")?.text || "[anonymous]";
-
-    // Check method definitions
-    func = tree.rootNode
-      .descendantsOfType("method_definition")
-      .find((f) => position >= f.startIndex && position <= f.endIndex);
-    if (func)
-      return func.childForFieldName("name")?.text || "[anonymous method]";
-
-    // Check arrow functions
-    const arrowFunc = tree.rootNode
-      .descendantsOfType("variable_declarator")
-      .find((v) => {
-        const value = v.childForFieldName("value");
-        return (
-          position >= v.startIndex &&
-          position <= v.endIndex &&
-          value?.type === "arrow_function"
-        );
-      });
-    return arrowFunc?.childForFieldName("name")?.text || "[anonymous arrow]";
+    const tree = this.parseSource(sourceCode);
+    return this.findInnermostFunction(tree.rootNode, position)?.name;
   }
 
   public generateFlowchart(
@@ -101,81 +87,21 @@ export class TsAstParser extends AbstractParser {
     functionName?: string,
     position?: number
   ): FlowchartIR {
-    const tree = this.parser.parse(sourceCode);
+    const tree = this.parseSource(sourceCode);
     this.resetState();
 
-    let targetNode: Parser.SyntaxNode | undefined;
-    let isArrowFunction = false;
-    let isMethod = false;
-
+    let target: FunctionTarget | undefined;
     if (position !== undefined) {
-      // Try to find function declaration at position
-      targetNode = tree.rootNode
-        .descendantsOfType("function_declaration")
-        .find((f) => position >= f.startIndex && position <= f.endIndex);
-
-      if (!targetNode) {
-        // Try to find method definition at position
-        targetNode = tree.rootNode
-          .descendantsOfType("method_definition")
-          .find((f) => position >= f.startIndex && position <= f.endIndex);
-        isMethod = !!targetNode;
-      }
-
-      if (!targetNode) {
-        // Try to find arrow function at position
-        targetNode = tree.rootNode
-          .descendantsOfType("variable_declarator")
-          .find((v) => {
-            const value = v.childForFieldName("value");
-            return (
-              position >= v.startIndex &&
-              position <= v.endIndex &&
-              value?.type === "arrow_function"
-            );
-          });
-        isArrowFunction = !!targetNode;
-      }
+      target = this.findInnermostFunction(tree.rootNode, position);
     } else if (functionName) {
-      // Find by function name
-      targetNode = tree.rootNode
-        .descendantsOfType("function_declaration")
-        .find((f) => f.childForFieldName("name")?.text === functionName);
-
-      if (!targetNode) {
-        targetNode = tree.rootNode
-          .descendantsOfType("method_definition")
-          .find((f) => f.childForFieldName("name")?.text === functionName);
-        isMethod = !!targetNode;
-      }
-
-      if (!targetNode) {
-        targetNode = tree.rootNode
-          .descendantsOfType("variable_declarator")
-          .find(
-            (v) =>
-              v.childForFieldName("name")?.text === functionName &&
-              v.childForFieldName("value")?.type === "arrow_function"
-          );
-        isArrowFunction = !!targetNode;
-      }
+      target = this.collectFunctions(tree.rootNode).find(
+        (f) => f.name === functionName
+      );
     } else {
-      // Get first function
-      targetNode =
-        tree.rootNode.descendantsOfType("function_declaration")[0] ||
-        tree.rootNode.descendantsOfType("method_definition")[0] ||
-        tree.rootNode
-          .descendantsOfType("variable_declarator")
-          .find((v) => v.childForFieldName("value")?.type === "arrow_function");
-
-      if (targetNode?.type === "method_definition") {
-        isMethod = true;
-      } else if (targetNode?.type === "variable_declarator") {
-        isArrowFunction = true;
-      }
+      target = this.collectFunctions(tree.rootNode)[0];
     }
 
-    if (!targetNode) {
+    if (!target) {
       return {
         nodes: [
           {
@@ -190,32 +116,19 @@ export class TsAstParser extends AbstractParser {
       };
     }
 
-    this.currentFunctionIsArrow = isArrowFunction;
-
-    // Get function body and name
-    let bodyToProcess: Parser.SyntaxNode | null = null;
-    let funcNameStr = "";
+    const targetNode = target.node;
+    const bodyToProcess = target.functionNode.childForFieldName("body");
+    const isExpressionBody =
+      !!bodyToProcess && bodyToProcess.type !== "statement_block";
+    // Expression bodies are implicitly returned
+    this.currentBodyIsExpression = isExpressionBody;
 
-    if (isArrowFunction) {
-      const arrowFunc = targetNode.childForFieldName("value");
-      bodyToProcess = arrowFunc?.childForFieldName("body") || null;
-      funcNameStr = this.escapeString(
-        targetNode.childForFieldName("name")?.text || "[anonymous arrow]"
-      );
-    } else if (isMethod) {
-      bodyToProcess = targetNode.childForFieldName("body");
-      funcNameStr = this.escapeString(
-        targetNode.childForFieldName("name")?.text || "[anonymous method]"
-      );
-    } else {
-      bodyToProcess = targetNode.childForFieldName("body");
-      funcNameStr = this.escapeString(
-        targetNode.childForFieldName("name")?.text || "[anonymous function]"
-      );
-    }
-
-    const title = `Flowchart for ${
-      isArrowFunction ? "arrow function" : isMethod ? "method" : "function"
+    const funcNameStr = this.escapeString(target.name);
+    const isAsync = target.functionNode.children.some(
+      (child) => child.type === "async"
+    );
+    const title = `Flowchart for ${isAsync ? "async " : ""}${
+      target.kind
     }: ${funcNameStr}`;
 
     if (!bodyToProcess) {
@@ -232,6 +145,11 @@ export class TsAstParser extends AbstractParser {
       };
     }
 
+    const cached = this.getCachedFlowchart(targetNode);
+    if (cached) {
+      return cached;
+    }
+
     const nodes: FlowchartNode[] = [];
     const edges: FlowchartEdge[] = [];
     const entryId = this.generateNodeId("start");
@@ -245,11 +163,9 @@ export class TsAstParser extends AbstractParser {
       this.createSemanticNode(exitId, "End", NodeType.EXIT, targetNode)
     );
 
-    // For arrow functions with expression bodies, handle them differently
-    const bodyResult =
-      isArrowFunction && bodyToProcess.type !== "statement_block"
-        ? this.processStatement(bodyToProcess, exitId)
-        : this.processBlock(bodyToProcess, exitId);
+    const bodyResult = isExpressionBody
+      ? this.processStatement(bodyToProcess, exitId)
+      : this.processBlock(bodyToProcess, exitId);
 
     nodes.push(...bodyResult.nodes);
     edges.push(...bodyResult.edges);
@@ -281,10 +197,7 @@ export class TsAstParser extends AbstractParser {
       exitNodeId: exitId,
     };
 
-    // Add function complexity analysis
-    this.addFunctionComplexity(ir, targetNode);
-
-    return ir;
+    return this.finalizeFlowchart(ir, targetNode);
   }
 
   protected processStatement(
@@ -470,8 +383,8 @@ export class TsAstParser extends AbstractParser {
           ]);
         }
 
-        // For arrow functions, treat expressions as return statements
-        return this.currentFunctionIsArrow
+        // Expression-bodied arrow functions return their expression
+        return this.currentBodyIsExpression
           ? this.processReturnStatementForExpression(
               statement,
               exitId,
@@ -484,6 +397,89 @@ export class TsAstParser extends AbstractParser {
 
   // --- PRIVATE HELPER AND PROCESSING METHODS --- //
 
+  /**
+   * Collects every function in document order: declarations, class and
+   * object-literal methods, and function values bound to a variable, class
+   * field or object property.
+   */
+  private collectFunctions(root: Parser.SyntaxNode): FunctionTarget[] {
+    const targets: FunctionTarget[] = [];
+    const visit = (node: Parser.SyntaxNode) => {
+      const target = this.toFunctionTarget(node);
+      if (target) {
+        targets.push(target);
+      }
+      for (const child of node.namedChildren) {
+        visit(child);
+      }
+    };
+    visit(root);
+    return targets;
+  }
+
+  private toFunctionTarget(
+    node: Parser.SyntaxNode
+  ): FunctionTarget | undefined {
+    if (FUNCTION_DECLARATION_TYPES.has(node.type)) {
+      return {
+        node,
+        functionNode: node,
+        name: node.childForFieldName("name")?.text || "[anonymous function]",
+        kind: "function",
+      };
+    }
+
+    if (node.type === "method_definition") {
+      return {
+        node,
+        functionNode: node,
+        name: node.childForFieldName("name")?.text || "[anonymous method]",
+        kind: "method",
+      };
+    }
+
+    const nameField = FUNCTION_BINDING_NAME_FIELDS[node.type];
+    const value = nameField ? node.childForFieldName("value") : null;
+    if (nameField && value && FUNCTION_VALUE_TYPES.has(value.type)) {
+      const isArrow = value.type === "arrow_function";
+      return {
+        node,
+        functionNode: value,
+        name:
+          node.childForFieldName(nameField)?.text ||
+          (isArrow ? "[anonymous arrow]" : "[anonymous function]"),
+        kind: isArrow
+          ? "arrow function"
+          : node.type === "variable_declarator"
+            ? "function"
+            : "method",
+      };
+    }
+
+    return undefined;
+  }
+
+  /**
+   * Finds the most deeply nested function containing the position, so a
+   * cursor inside a method picks the method rather than its enclosing
+   * function.
+   */
+  private findInnermostFunction(
+    root: Parser.SyntaxNode,
+    position: number
+  ): FunctionTarget | undefined {
+    let innermost: FunctionTarget | undefined;
+    for (const target of this.collectFunctions(root)) {
+      const { startIndex, endIndex } = target.node;
+      if (position < startIndex || position > endIndex) {
+        continue;
+      }
+      // Document order visits parents first, so a later match is nested
+      innermost = target;
+    }
+    return innermost;
+  }
+
   private processConditionalExpression(
     condExprNode: Parser.SyntaxNode,
     exitId: string,
@@ -552,7 +548,7 @@ export class TsAstParser extends AbstractParser {
       nodes,
       edges,
       conditionId,
-      this.currentFunctionIsArrow
+      this.currentBodyIsExpression
         ? []
         : [...consequenceResult.exitPoints, ...alternativeResult.exitPoints],
       nodesConnectedToExit
@@ -1,5 +1,5 @@
 import { TsAstParser } from "./TsAstParser";
-import { FlowchartIR } from "../../../ir/ir";
+import { CallSite, FlowchartIR, FunctionDefinition, ParseErrorInfo } from "../../../ir/ir";
 
 let parserPromise: Promise<TsAstParser> | null = null;
 
@@ -16,13 +16,74 @@ export function initTypeScriptLanguageService(wasmPath: string) {
  */
 export async function analyzeTypeScriptCode(
   code: string,
-  position: number
+  position?: number,
+  functionName?: string
 ): Promise<FlowchartIR> {
   if (!parserPromise) {
     throw new Error("TypeScript language service not initialized.");
   }
   const parser = await parserPromise;
-  return parser.generateFlowchart(code, undefined, position);
+  return parser.generateFlowchart(code, functionName, position);
+}
+
+/**
+ * Lists the names of the TypeScript functions defined in the code.
+ */
+export async function listTypeScriptFunctions(code: string): Promise<string[]> {
+  if (!parserPromise) {
+    throw new Error("TypeScript language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctions(code);
+}
+
+/**
+ * Lists the TypeScript functions defined in the code, with where each starts.
+ */
+export async function listTypeScriptFunctionDefinitions(code: string): Promise<FunctionDefinition[]> {
+  if (!parserPromise) {
+    throw new Error("TypeScript language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listFunctionDefinitions(code);
+}
+
+/**
+ * Lists the calls made inside TypeScript functions, with their callers.
+ */
+export async function listTypeScriptCalls(code: string): Promise<CallSite[]> {
+  if (!parserPromise) {
+    throw new Error("TypeScript language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.listCalls(code);
+}
+
+/**
+ * Returns the name of the TypeScript function containing the offset, if any.
+ */
+export async function findTypeScriptFunctionAtPosition(
+  code: string,
+  position: number
+): Promise<string | undefined> {
+  if (!parserPromise) {
+    throw new Error("TypeScript language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findFunctionAtPosition(code, position);
+}
+
+/**
+ * Lists the regions of the TypeScript code that did not parse.
+ */
+export async function findTypeScriptParseErrors(
+  code: string
+): Promise<ParseErrorInfo[]> {
+  if (!parserPromise) {
+    throw new Error("TypeScript language service not initialized.");
+  }
+  const parser = await parserPromise;
+  return parser.findParseErrors(code);
 }
 
 export { TsAstParser };
@@ -0,0 +1,95 @@
+/**
+ * Bounds on how much work one analysis may do, so a huge or pathological
+ * input fails with a clear message instead of freezing the editor or
+ * stalling a batch run.
+ */
+export interface AnalysisLimits {
+  /** Sources with more lines are rejected before parsing. */
+  maxLines: number;
+  /** Wall-clock budget for parsing and building one flowchart. */
+  timeoutMs: number;
+}
+
+export const DEFAULT_MAX_LINES = 20000;
+export const DEFAULT_TIMEOUT_MS = 10000;
+
+export type AnalysisLimitReason = "tooLarge" | "timeout";
+
+export class AnalysisLimitError extends Error {
+  constructor(
+    public readonly reason: AnalysisLimitReason,
+    message: string
+  ) {
+    super(message);
+    this.name = "AnalysisLimitError";
+  }
+}
+
+const limits: AnalysisLimits = {
+  maxLines: DEFAULT_MAX_LINES,
+  timeoutMs: DEFAULT_TIMEOUT_MS,
+};
+
+// Set while a batch analyzes one file, so all of its functions share a budget
+let sharedDeadline: number | undefined;
+
+export function getAnalysisLimits(): Readonly<AnalysisLimits> {
+  return limits;
+}
+
+/** Changes the limits for every later analysis; unset fields keep their value. */
+export function setAnalysisLimits(update: Partial<AnalysisLimits>): void {
+  if (update.maxLines !== undefined && update.maxLines > 0) {
+    limits.maxLines = Math.floor(update.maxLines);
+  }
+  if (update.timeoutMs !== undefined && update.timeoutMs > 0) {
+    limits.timeoutMs = update.timeoutMs;
+  }
+}
+
+/**
+ * When the analysis starting now must stop: `timeoutMs` from now, or the
+ * end of the budget started by `beginSharedDeadline`, whichever is first.
+ */
+export function analysisDeadline(): number {
+  const own = Date.now() + limits.timeoutMs;
+  return sharedDeadline === undefined ? own : Math.min(own, sharedDeadline);
+}
+
+/**
+ * Starts one `timeoutMs` budget shared by every analysis until
+ * `endSharedDeadline`, such as all the functions of one file in a batch.
+ */
+export function beginSharedDeadline(): void {
+  sharedDeadline = Date.now() + limits.timeoutMs;
+}
+
+export function endSharedDeadline(): void {
+  sharedDeadline = undefined;
+}
+
+export function checkDeadline(deadline: number): void {
+  if (Date.now() > deadline) {
+    throw timeoutError();
+  }
+}
+
+export function timeoutError(): AnalysisLimitError {
+  return new AnalysisLimitError(
+    "timeout",
+    `Analysis timed out after ${limits.timeoutMs} ms; the input is too large or complex to analyze.`
+  );
+}
+
+/** Rejects sources longer than `maxLines` without scanning all of them. */
+export function checkInputSize(source: string): void {
+  let lines = 1;
+  for (let index = source.indexOf("\n"); index !== -1; index = source.indexOf("\n", index + 1)) {
+    if (++lines > limits.maxLines) {
+      throw new AnalysisLimitError(
+        "tooLarge",
+        `The input has more than ${limits.maxLines} lines, the most Visor analyzes at once.`
+      );
+    }
+  }
+}
@@ -1,4 +1,5 @@
 import Parser from "web-tree-sitter";
+import { EdgeType, FlowchartIR } from "../../ir/ir";
 import {
   ComplexityConfiguration,
   getComplexityConfig,
@@ -58,6 +59,131 @@ export class ComplexityAnalyzer {
     };
   }
 
+  /**
+   * Cyclomatic complexity read off the control-flow graph itself:
+   * E - N + 2 over the nodes reachable from the entry. Unlike the AST
+   * count this reflects exactly what the diagram shows, including early
+   * returns, shared error exits and loop back-edges. Data-flow overlay
+   * edges are not control flow and are left out.
+   */
+  public static calculateGraphComplexity(ir: FlowchartIR): number {
+    const edges = ir.edges.filter((edge) => edge.edgeType !== EdgeType.DATAFLOW);
+    const reachable = ir.entryNodeId
+      ? this.findReachableNodeIds(ir)
+      : new Set(ir.nodes.map((node) => node.id));
+
+    const nodeIds = new Set(ir.nodes.map((node) => node.id));
+    const nodeCount = Array.from(reachable).filter((id) => nodeIds.has(id)).length;
+    const edgeCount = edges.filter(
+      (edge) =>
+        reachable.has(edge.from) && nodeIds.has(edge.from) && nodeIds.has(edge.to)
+    ).length;
+    return Math.max(1, edgeCount - nodeCount + 2);
+  }
+
+  /**
+   * Ids of the nodes a control-flow path from the entry can reach,
+   * following normal and back edges only. Empty when the IR has no entry.
+   */
+  public static findReachableNodeIds(ir: FlowchartIR): Set<string> {
+    const reachable = new Set<string>();
+    if (!ir.entryNodeId) {
+      return reachable;
+    }
+    const outgoing = new Map<string, string[]>();
+    for (const edge of ir.edges) {
+      if (edge.edgeType === EdgeType.DATAFLOW) {
+        continue;
+      }
+      const targets = outgoing.get(edge.from) || [];
+      targets.push(edge.to);
+      outgoing.set(edge.from, targets);
+    }
+    const stack = [ir.entryNodeId];
+    while (stack.length > 0) {
+      const id = stack.pop()!;
+      if (!reachable.has(id)) {
+        reachable.add(id);
+        stack.push(...(outgoing.get(id) || []));
+      }
+    }
+    return reachable;
+  }
+
+  /**
+   * The longest acyclic path from the entry to the exit, as node ids, the
+   * worst-case route through the function. Back edges and any other edge
+   * that would close a cycle are left out, as are data-flow edges. Ties go
+   * to the branch listed first. Empty when the exit is unreachable.
+   */
+  public static findLongestPath(ir: FlowchartIR): string[] {
+    const { entryNodeId, exitNodeId } = ir;
+    if (!entryNodeId || !exitNodeId) {
+      return [];
+    }
+    const outgoing = new Map<string, string[]>();
+    for (const edge of ir.edges) {
+      if (edge.edgeType === EdgeType.BACK || edge.edgeType === EdgeType.DATAFLOW) {
+        continue;
+      }
+      const targets = outgoing.get(edge.from) || [];
+      targets.push(edge.to);
+      outgoing.set(edge.from, targets);
+    }
+
+    // Edges to the exit along the longest path from each node, -1 when the
+    // exit cannot be reached; filled in post-order by an iterative DFS
+    const distance = new Map<string, number>();
+    const next = new Map<string, string>();
+    const onStack = new Set<string>([entryNodeId]);
+    const stack = [{ id: entryNodeId, index: 0 }];
+    while (stack.length > 0) {
+      const frame = stack[stack.length - 1];
+      const targets = outgoing.get(frame.id) || [];
+      if (frame.index < targets.length) {
+        const target = targets[frame.index++];
+        if (!distance.has(target) && !onStack.has(target)) {
+          onStack.add(target);
+          stack.push({ id: target, index: 0 });
+        }
+        continue;
+      }
+      stack.pop();
+      onStack.delete(frame.id);
+      let best = frame.id === exitNodeId ? 0 : -1;
+      for (const target of targets) {
+        // Targets still on the stack are ancestors: that edge closes a cycle
+        const rest = distance.get(target);
+        if (rest !== undefined && rest >= 0 && rest + 1 > best) {
+          best = rest + 1;
+          next.set(frame.id, target);
+        }
+      }
+      distance.set(frame.id, best);
+    }
+
+    if ((distance.get(entryNodeId) ?? -1) < 0) {
+      return [];
+    }
+    const path = [entryNodeId];
+    while (path[path.length - 1] !== exitNodeId) {
+      path.push(next.get(path[path.length - 1])!);
+    }
+    return path;
+  }
+
+  /**
+   * Rates a complexity value against the configured thresholds.
+   */
+  public static rateComplexity(
+    cyclomaticComplexity: number
+  ): Pick<ComplexityResult, "rating" | "description"> {
+    return {
+      rating: getComplexityRating(cyclomaticComplexity, this.config),
+      description: getComplexityDescription(cyclomaticComplexity, this.config),
+    };
+  }
+
   /**
    * Calculate complexity for a specific AST node
    */
@@ -1,4 +1,4 @@
-import * as vscode from "vscode";
+import type * as vscode from "vscode";
 
 /**
  * Configuration interface for complexity analysis features
@@ -55,11 +55,30 @@ export const DEFAULT_COMPLEXITY_CONFIG: ComplexityConfiguration = {
   },
 };
 
+/**
+ * Resolves VS Code settings when running inside the editor. The analysis
+ * core is also usable as a plain library, where the `vscode` module does not
+ * exist, so it is loaded lazily and defaults apply when it is missing.
+ */
+export function getWorkspaceConfiguration(
+  section: string
+): vscode.WorkspaceConfiguration | undefined {
+  try {
+    const vscodeModule: typeof vscode = require("vscode");
+    return vscodeModule.workspace.getConfiguration(section);
+  } catch {
+    return undefined;
+  }
+}
+
 /**
  * Factory function to get complexity configuration from VS Code settings
  */
 export function getComplexityConfig(): ComplexityConfiguration {
-  const config = vscode.workspace.getConfiguration("visor.complexity");
+  const config = getWorkspaceConfiguration("visor.complexity");
+  if (!config) {
+    return DEFAULT_COMPLEXITY_CONFIG;
+  }
 
   return {
     enabled: config.get<boolean>("enabled", DEFAULT_COMPLEXITY_CONFIG.enabled),
@@ -0,0 +1,112 @@
+import { DiffStatus } from "../../ir/ir";
+
+/**
+ * Colors applied by the text emitters (PlantUML skinparams, DOT attributes,
+ * SVG styles and a Mermaid init directive). Styling only: the emitters
+ * produce the same nodes and edges with or without a color map.
+ */
+export interface DiagramColors {
+  background: string;
+  nodeBackground: string;
+  nodeBorder: string;
+  nodeText: string;
+  edge: string;
+}
+
+export const DIAGRAM_COLOR_THEMES: Readonly<Record<string, DiagramColors>> = {
+  light: {
+    background: "#ffffff",
+    nodeBackground: "#f4f6fa",
+    nodeBorder: "#4a5568",
+    nodeText: "#222222",
+    edge: "#555555",
+  },
+  dark: {
+    background: "#1e1e1e",
+    nodeBackground: "#2d2d30",
+    nodeBorder: "#9aa5b1",
+    nodeText: "#e6e6e6",
+    edge: "#a0a0a0",
+  },
+};
+
+/**
+ * Outline of nodes no path from the entry reaches. Fixed rather than part
+ * of the color map so the warning reads the same under every theme.
+ */
+export const UNREACHABLE_COLOR = "#d32f2f";
+
+/**
+ * Stroke of edges on cold paths: a mid gray, so it is fainter than the
+ * edge color of both the light and the dark theme.
+ */
+export const COLD_EDGE_COLOR = "#7f8690";
+
+/** Fill and outline of diff-view nodes; edges take the outline color. */
+export const DIFF_COLORS: Readonly<Record<DiffStatus, { fill: string; stroke: string }>> = {
+  added: { fill: "#e8f5e9", stroke: "#2e7d32" },
+  removed: { fill: "#fdecea", stroke: "#c62828" },
+  changed: { fill: "#fff3e0", stroke: "#ef6c00" },
+};
+
+export const DIAGRAM_COLOR_KEYS: ReadonlyArray<keyof DiagramColors> = [
+  "background",
+  "nodeBackground",
+  "nodeBorder",
+  "nodeText",
+  "edge",
+];
+
+// Hex colors or plain color names; anything else could break out of the
+// emitted syntax, so it is rejected rather than escaped.
+const COLOR_PATTERN = /^(#[0-9a-fA-F]{3}|#[0-9a-fA-F]{6}|#[0-9a-fA-F]{8}|[a-zA-Z]+)$/;
+
+export function isDiagramColorTheme(value: unknown): value is string {
+  return (
+    typeof value === "string" &&
+    Object.prototype.hasOwnProperty.call(DIAGRAM_COLOR_THEMES, value)
+  );
+}
+
+/**
+ * Lists the problems with a user-supplied color map: unknown keys and values
+ * that are not hex colors or color names.
+ */
+export function validateDiagramColors(colors: unknown): string[] {
+  if (typeof colors !== "object" || colors === null || Array.isArray(colors)) {
+    return ["Colors must be an object mapping color keys to values"];
+  }
+  const errors: string[] = [];
+  for (const [key, value] of Object.entries(colors)) {
+    if (!DIAGRAM_COLOR_KEYS.includes(key as keyof DiagramColors)) {
+      errors.push(`Unknown color key '${key}' (expected ${DIAGRAM_COLOR_KEYS.join(", ")})`);
+    } else if (typeof value !== "string" || !COLOR_PATTERN.test(value)) {
+      errors.push(`Invalid color for '${key}': ${JSON.stringify(value)}`);
+    }
+  }
+  return errors;
+}
+
+/**
+ * Combines a preset with custom overrides. Returns undefined when neither is
+ * given, so emitters keep their built-in look. Custom colors without a preset
+ * start from the light theme; invalid entries are ignored.
+ */
+export function resolveDiagramColors(
+  theme?: string,
+  custom?: Partial<DiagramColors>
+): DiagramColors | undefined {
+  const preset = isDiagramColorTheme(theme) ? DIAGRAM_COLOR_THEMES[theme] : undefined;
+  if (!preset && !custom) {
+    return undefined;
+  }
+
+  const colors = { ...(preset || DIAGRAM_COLOR_THEMES.light) };
+  for (const key of DIAGRAM_COLOR_KEYS) {
+    const value = custom?.[key];
+    if (typeof value === "string" && COLOR_PATTERN.test(value)) {
+      colors[key] = value;
+    }
+  }
+  return colors;
+}
@@ -0,0 +1,240 @@
+import { EdgeType, FlowchartEdge, FlowchartIR, FlowchartNode } from "../../ir/ir";
+
+// How far past an inserted or deleted statement the matcher looks for the
+// node that continues the old flow
+const LOOKAHEAD = 3;
+
+interface IndexedGraph {
+  nodes: Map<string, FlowchartNode>;
+  outgoing: Map<string, FlowchartEdge[]>;
+}
+
+/**
+ * Builds a diff view of two versions of a function. Nodes are matched by
+ * structural position: starting from the entries, the successors of every
+ * matched pair are paired along edges with the same label. The result is
+ * the new graph with every node and edge marked `added` or `changed` (same
+ * place, new text), plus the old nodes and edges that no longer exist,
+ * marked `removed`. Data-flow edges are not compared.
+ */
+export function diffFlowcharts(before: FlowchartIR, after: FlowchartIR): FlowchartIR {
+  const old = toGraph(before);
+  const current = toGraph(after);
+  const matches = matchNodes(before, after, old, current);
+  const matchedBefore = new Map(Array.from(matches, ([afterId, beforeId]) => [beforeId, afterId]));
+
+  const nodes: FlowchartNode[] = after.nodes.map((node) => {
+    const copy: FlowchartNode = { ...node };
+    const previous = matches.get(node.id);
+    if (previous === undefined) {
+      copy.diff = "added";
+    } else if (old.nodes.get(previous)!.label !== node.label) {
+      copy.diff = "changed";
+    }
+    return copy;
+  });
+
+  // Old nodes keep their id unless the new graph already uses it
+  const usedIds = new Set(after.nodes.map((node) => node.id));
+  const mergedId = new Map<string, string>(matchedBefore);
+  for (const node of before.nodes) {
+    if (matchedBefore.has(node.id)) {
+      continue;
+    }
+    let id = `removed_${node.id}`;
+    while (usedIds.has(id)) {
+      id += "_";
+    }
+    usedIds.add(id);
+    mergedId.set(node.id, id);
+    // Its offsets point into the old text, so it must not take part in cursor
+    // sync; its scope is one of the old graph's
+    const { location, line, unreachable, scope, ...rest } = node;
+    nodes.push({ ...rest, id, diff: "removed" });
+  }
+
+  const key = (from: string, to: string, label?: string) => `${from}\u0000${to}\u0000${label || ""}`;
+  const oldEdges = before.edges
+    .filter((edge) => edge.edgeType !== EdgeType.DATAFLOW)
+    .map((edge) => ({ ...edge, from: mergedId.get(edge.from)!, to: mergedId.get(edge.to)! }))
+    .filter((edge) => edge.from !== undefined && edge.to !== undefined);
+  const oldKeys = new Set(oldEdges.map((edge) => key(edge.from, edge.to, edge.label)));
+
+  const edges: FlowchartEdge[] = [];
+  const newKeys = new Set<string>();
+  for (const edge of after.edges) {
+    if (edge.edgeType === EdgeType.DATAFLOW) {
+      continue;
+    }
+    const edgeKey = key(edge.from, edge.to, edge.label);
+    newKeys.add(edgeKey);
+    edges.push(oldKeys.has(edgeKey) ? { ...edge } : { ...edge, diff: "added" });
+  }
+  for (const edge of oldEdges) {
+    if (!newKeys.has(key(edge.from, edge.to, edge.label))) {
+      edges.push({ ...edge, diff: "removed" });
+    }
+  }
+
+  return {
+    ...after,
+    nodes,
+    edges,
+    locationMap: after.locationMap.map((entry) => ({ ...entry })),
+    title: after.title ? `${after.title} (changes)` : after.title,
+  };
+}
+
+function toGraph(ir: FlowchartIR): IndexedGraph {
+  const outgoing = new Map<string, FlowchartEdge[]>();
+  for (const edge of ir.edges) {
+    if (edge.edgeType === EdgeType.DATAFLOW) {
+      continue;
+    }
+    const list = outgoing.get(edge.from) || [];
+    list.push(edge);
+    outgoing.set(edge.from, list);
+  }
+  return { nodes: new Map(ir.nodes.map((node) => [node.id, node])), outgoing };
+}
+
+/**
+ * Returns new node id -> old node id for every node present in both
+ * versions, walking both graphs breadth-first from the entry. Statements
+ * moved under a new branch lose their structural position; those are
+ * matched afterwards when their text is unique on both sides.
+ */
+function matchNodes(
+  before: FlowchartIR,
+  after: FlowchartIR,
+  old: IndexedGraph,
+  current: IndexedGraph
+): Map<string, string> {
+  const matches = new Map<string, string>();
+  const matchedOld = new Set<string>();
+  const queue: [string, string][] = [];
+  const match = (beforeId: string, afterId: string): void => {
+    matches.set(afterId, beforeId);
+    matchedOld.add(beforeId);
+    queue.push([beforeId, afterId]);
+  };
+  const isFree = (beforeId: string, afterId: string) =>
+    !matchedOld.has(beforeId) && !matches.has(afterId);
+
+  const walk = (): void => {
+    while (queue.length > 0) {
+      const [beforeId, afterId] = queue.shift()!;
+      const oldOut = [...(old.outgoing.get(beforeId) || [])];
+      const newOut = [...(current.outgoing.get(afterId) || [])];
+
+      // Same-label edges first, in order; then whatever is left, in order
+      for (const sameLabel of [true, false]) {
+        for (const oldEdge of [...oldOut]) {
+          const index = newOut.findIndex(
+            (newEdge) => !sameLabel || (newEdge.label || "") === (oldEdge.label || "")
+          );
+          if (index < 0) {
+            continue;
+          }
+          const paired = pairTargets(oldEdge.to, newOut[index].to, old, current, isFree);
+          if (!paired && !sameLabel) {
+            continue;
+          }
+          oldOut.splice(oldOut.indexOf(oldEdge), 1);
+          newOut.splice(index, 1);
+          if (paired) {
+            match(paired[0], paired[1]);
+          }
+        }
+      }
+    }
+  };
+
+  if (before.exitNodeId && after.exitNodeId) {
+    match(before.exitNodeId, after.exitNodeId);
+  }
+  if (before.entryNodeId && after.entryNodeId) {
+    match(before.entryNodeId, after.entryNodeId);
+  }
+  walk();
+
+  // Text -> node id, or undefined when the text occurs more than once
+  const unique = (nodes: FlowchartNode[]): Map<string, string | undefined> => {
+    const byText = new Map<string, string | undefined>();
+    for (const node of nodes) {
+      const text = `${node.nodeType ?? node.shape}\u0000${node.label}`;
+      byText.set(text, byText.has(text) ? undefined : node.id);
+    }
+    return byText;
+  };
+  const oldByText = unique(before.nodes.filter((node) => !matchedOld.has(node.id)));
+  const newByText = unique(after.nodes.filter((node) => !matches.has(node.id)));
+  for (const [text, afterId] of newByText) {
+    const beforeId = oldByText.get(text);
+    if (afterId !== undefined && beforeId !== undefined && isFree(beforeId, afterId)) {
+      match(beforeId, afterId);
+    }
+  }
+  walk();
+
+  return matches;
+}
+
+/**
+ * Decides which old and new node continue each other along a pair of
+ * edges. Identical nodes match directly; otherwise a short lookahead
+ * recognizes statements inserted or deleted in between, and as a last
+ * resort nodes of the same kind match as changed.
+ */
+function pairTargets(
+  beforeId: string,
+  afterId: string,
+  old: IndexedGraph,
+  current: IndexedGraph,
+  isFree: (beforeId: string, afterId: string) => boolean
+): [string, string] | undefined {
+  const oldNode = old.nodes.get(beforeId);
+  const newNode = current.nodes.get(afterId);
+  if (!oldNode || !newNode || !isFree(beforeId, afterId)) {
+    return undefined;
+  }
+  if (sameKind(oldNode, newNode) && oldNode.label === newNode.label) {
+    return [beforeId, afterId];
+  }
+
+  // Inserted statements: the old node reappears a few steps further on
+  for (const laterId of chain(afterId, current)) {
+    const later = current.nodes.get(laterId)!;
+    if (isFree(beforeId, laterId) && sameKind(oldNode, later) && oldNode.label === later.label) {
+      return [beforeId, laterId];
+    }
+  }
+  // Deleted statements: the new node was a few steps further on before
+  for (const laterId of chain(beforeId, old)) {
+    const later = old.nodes.get(laterId)!;
+    if (isFree(laterId, afterId) && sameKind(later, newNode) && later.label === newNode.label) {
+      return [laterId, afterId];
+    }
+  }
+
+  return sameKind(oldNode, newNode) ? [beforeId, afterId] : undefined;
+}
+
+/** The nodes after `id` along single-successor edges, up to the lookahead. */
+function chain(id: string, graph: IndexedGraph): string[] {
+  const ids: string[] = [];
+  let currentId = id;
+  while (ids.length < LOOKAHEAD) {
+    const out = graph.outgoing.get(currentId) || [];
+    if (out.length !== 1 || out[0].edgeType === EdgeType.BACK) {
+      break;
+    }
+    currentId = out[0].to;
+    ids.push(currentId);
+  }
+  return ids;
+}
+
+function sameKind(a: FlowchartNode, b: FlowchartNode): boolean {
+  return (a.nodeType ?? a.shape) === (b.nodeType ?? b.shape);
+}
@@ -0,0 +1,690 @@
+import {
+  EdgeType,
+  FlowchartEdge,
+  FlowchartIR,
+  FlowchartNode,
+  FlowchartScope,
+  NodeType,
+} from "../../ir/ir";
+import { ComplexityAnalyzer } from "./ComplexityAnalyzer";
+import { applyLabelMode, fitLabels, LabelMode, LabelOverflow } from "./NodeLabels";
+
+/**
+ * Presentation options applied to an analyzed flowchart before it is
+ * emitted. They change labels and layout hints only; the parser output is
+ * the same regardless of the options chosen.
+ */
+export interface FlowchartViewOptions {
+  /** Prefix node labels with their source line, e.g. `L12: x = 1`. */
+  lineNumbers?: boolean;
+  /** Merge runs of straight-line statements into a single node. */
+  compact?: boolean;
+  /** List each node's defined/used variables and draw def-use edges. */
+  dataflow?: boolean;
+  /** Draw the edges of the longest acyclic entry-to-exit path in bold. */
+  longestPath?: boolean;
+  /**
+   * Fold code nested deeper than this many levels into summary nodes; 1
+   * keeps only the statements directly in the function body.
+   */
+  maxDepth?: number;
+  /** Ids of summary nodes to leave unfolded, as produced by `maxDepth`. */
+  expandedFolds?: string[];
+  /** Ids of branch and loop nodes whose bodies are drawn as one placeholder. */
+  collapsedBranches?: string[];
+  /** Source text, a short phrase or the node kind instead of the parser's labels. */
+  labelMode?: LabelMode;
+  /** Longest label line in characters; 0 or unset leaves lines as they are. */
+  labelWidth?: number;
+  /** Wrap long lines at spaces (the default) or cut them with an ellipsis. */
+  labelOverflow?: LabelOverflow;
+  /**
+   * Draw every nested body as a box around its nodes (PlantUML and
+   * Graphviz output only).
+   */
+  scopes?: boolean;
+  /**
+   * Gray out the edges the parser flagged as cold (panics, `#[cold]` calls,
+   * `unlikely()` branches). On unless set to false.
+   */
+  coldPaths?: boolean;
+}
+
+/**
+ * Applies the view options to the IR in place and returns it. Callers that
+ * need the original IR afterwards should pass a copy.
+ */
+export function applyViewOptions(
+  ir: FlowchartIR,
+  options: FlowchartViewOptions = {}
+): FlowchartIR {
+  // Without scopes no emitter draws boxes and compaction may cross bodies
+  if (!options.scopes) {
+    dropScopes(ir);
+  }
+  if (options.coldPaths === false) {
+    for (const edge of ir.edges) {
+      delete edge.cold;
+    }
+  }
+  // Folding first, so the summaries take part in compaction and data flow
+  if (options.collapsedBranches && options.collapsedBranches.length > 0) {
+    collapseBranches(ir, new Set(options.collapsedBranches));
+  }
+  if (options.maxDepth !== undefined && options.maxDepth > 0) {
+    foldDeepNesting(ir, options.maxDepth, new Set(options.expandedFolds));
+  }
+  // Before the prefixes, which then number the rewritten labels
+  if (options.labelMode) {
+    applyLabelMode(ir, options.labelMode);
+  }
+  // Line prefixes before compaction, so each statement in a compacted node keeps its own
+  if (options.lineNumbers) {
+    addLineNumberPrefixes(ir);
+  }
+  if (options.compact) {
+    compactLinearSequences(ir);
+  }
+  // After compaction, so the path runs through the nodes that are drawn
+  if (options.longestPath) {
+    markLongestPath(ir);
+  }
+  // Last, so def-use edges connect the nodes that are actually drawn
+  if (options.dataflow) {
+    addDataflowOverlay(ir);
+  }
+  // After everything that adds label lines, so every line fits
+  if (options.labelWidth !== undefined && options.labelWidth > 0) {
+    fitLabels(ir, options.labelWidth, options.labelOverflow);
+  }
+  return ir;
+}
+
+/** A scope with the nodes drawn directly inside it and its nested scopes. */
+export interface ScopeBox {
+  scope?: FlowchartScope;
+  nodes: FlowchartNode[];
+  children: ScopeBox[];
+}
+
+/**
+ * Arranges the nodes into the IR's scopes for emitters that draw them as
+ * boxes. The returned root has no scope and holds everything outside a
+ * nested body; scopes left without nodes (by folding, say) are dropped.
+ * Nodes keep their IR order within each box.
+ */
+export function groupByScope(ir: FlowchartIR): ScopeBox {
+  const root: ScopeBox = { nodes: [], children: [] };
+  const boxes = new Map<string, ScopeBox>();
+  for (const scope of ir.scopes ?? []) {
+    const box: ScopeBox = { scope, nodes: [], children: [] };
+    boxes.set(scope.id, box);
+    // Scopes are listed outermost first, so the parent box exists already
+    const parent = scope.parent !== undefined ? boxes.get(scope.parent) : undefined;
+    (parent ?? root).children.push(box);
+  }
+  for (const node of ir.nodes) {
+    const box = node.scope !== undefined ? boxes.get(node.scope) : undefined;
+    (box ?? root).nodes.push(node);
+  }
+  const prune = (box: ScopeBox): boolean => {
+    box.children = box.children.filter(prune);
+    return box.nodes.length > 0 || box.children.length > 0;
+  };
+  prune(root);
+  return root;
+}
+
+function dropScopes(ir: FlowchartIR): void {
+  delete ir.scopes;
+  for (const node of ir.nodes) {
+    delete node.scope;
+  }
+}
+
+/**
+ * Prefixes each node that maps to a source line with `L<line>:`. Start and
+ * End are left alone since they stand for the whole function.
+ */
+export function addLineNumberPrefixes(ir: FlowchartIR): void {
+  for (const node of ir.nodes) {
+    if (
+      node.line === undefined ||
+      node.nodeType === NodeType.ENTRY ||
+      node.nodeType === NodeType.EXIT
+    ) {
+      continue;
+    }
+    node.label = `L${node.line}: ${node.label}`;
+  }
+}
+
+/**
+ * Flags the edges of the longest acyclic path from entry to exit (see
+ * ComplexityAnalyzer.findLongestPath) so the emitters draw them bold.
+ */
+export function markLongestPath(ir: FlowchartIR): void {
+  const path = ComplexityAnalyzer.findLongestPath(ir);
+  for (let i = 0; i + 1 < path.length; i++) {
+    const edge = ir.edges.find(
+      (candidate) =>
+        candidate.from === path[i] &&
+        candidate.to === path[i + 1] &&
+        candidate.edgeType !== EdgeType.BACK &&
+        candidate.edgeType !== EdgeType.DATAFLOW
+    );
+    if (edge) {
+      edge.onLongestPath = true;
+    }
+  }
+}
+
+/**
+ * Replaces every connected region of nodes nested deeper than `maxDepth`
+ * with one summary node labelled with how many nodes it hides. Edges into
+ * and out of the region attach to the summary, and the location map points
+ * at it so cursor sync still lands somewhere visible. A summary's id is
+ * `fold_` plus the id of its first node; regions whose summary id is in
+ * `expanded` are left as they are.
+ *
+ * Synthetic nodes without a source location (merge points) take the
+ * shallowest depth of their predecessors. Start and End are never folded.
+ */
+export function foldDeepNesting(
+  ir: FlowchartIR,
+  maxDepth: number,
+  expanded: ReadonlySet<string> = new Set()
+): void {
+  const controlEdges = ir.edges.filter((edge) => edge.edgeType !== EdgeType.DATAFLOW);
+  const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+  const depthOf = inferDepths(ir, controlEdges, nodeById);
+
+  const isDeep = (id: string): boolean => {
+    const node = nodeById.get(id);
+    // Collapsed branches already are a summary
+    return !!node && !isBoundary(node) && !node.folded && (depthOf.get(id) ?? 0) > maxDepth;
+  };
+  const neighbours = new Map<string, string[]>();
+  for (const edge of controlEdges) {
+    if (isDeep(edge.from) && isDeep(edge.to)) {
+      addToMultiMap(neighbours, edge.from, edge.to);
+      addToMultiMap(neighbours, edge.to, edge.from);
+    }
+  }
+
+  const foldedInto = new Map<string, string>();
+  const summaries = new Map<string, FlowchartNode>();
+  // An expanded region is still visited, so none of its nodes starts another
+  const visited = new Set<string>();
+  for (const first of ir.nodes) {
+    if (!isDeep(first.id) || visited.has(first.id)) {
+      continue;
+    }
+    const id = `fold_${first.id}`;
+    const region: FlowchartNode[] = [];
+    const pending = [first.id];
+    visited.add(first.id);
+    while (pending.length > 0) {
+      const current = pending.pop()!;
+      region.push(nodeById.get(current)!);
+      for (const next of neighbours.get(current) || []) {
+        if (!visited.has(next)) {
+          visited.add(next);
+          pending.push(next);
+        }
+      }
+    }
+    if (expanded.has(id)) {
+      continue;
+    }
+    region.forEach((node) => foldedInto.set(node.id, id));
+    const depth = Math.min(...region.map((node) => depthOf.get(node.id)!));
+    summaries.set(id, summarizeRegion(id, region, depth, "nested node", "folded"));
+  }
+
+  replaceRegions(ir, foldedInto, summaries);
+}
+
+/**
+ * Draws the body of each listed branch or loop node as a single placeholder
+ * saying how many nodes it hides; the branch node itself stays visible. The
+ * body is every node reached from the branch that is nested deeper than it,
+ * up to where the flow returns to the branch's own level. A placeholder's
+ * id is `collapsed_` plus the branch's id. Ids that are not branch or loop
+ * nodes are ignored, and a branch inside another collapsed one is simply
+ * part of the outer placeholder.
+ */
+export function collapseBranches(ir: FlowchartIR, branchIds: ReadonlySet<string>): void {
+  const controlEdges = ir.edges.filter((edge) => edge.edgeType !== EdgeType.DATAFLOW);
+  const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+  const depthOf = inferDepths(ir, controlEdges, nodeById);
+  const forward = new Map<string, string[]>();
+  for (const edge of controlEdges) {
+    if (edge.edgeType !== EdgeType.BACK) {
+      addToMultiMap(forward, edge.from, edge.to);
+    }
+  }
+
+  const bodies: { branch: FlowchartNode; body: FlowchartNode[] }[] = [];
+  for (const id of branchIds) {
+    const branch = nodeById.get(id);
+    const depth = depthOf.get(id);
+    if (!branch || !isCollapsible(branch) || depth === undefined) {
+      continue;
+    }
+    const seen = new Set<string>();
+    const body: FlowchartNode[] = [];
+    const pending = [...(forward.get(id) || [])];
+    while (pending.length > 0) {
+      const current = pending.pop()!;
+      const node = nodeById.get(current);
+      if (!node || seen.has(current) || isBoundary(node) || (depthOf.get(current) ?? 0) <= depth) {
+        continue;
+      }
+      seen.add(current);
+      body.push(node);
+      pending.push(...(forward.get(current) || []));
+    }
+    if (body.length > 0) {
+      bodies.push({ branch, body });
+    }
+  }
+
+  // Outer branches first, so nested ones end up inside their placeholder
+  bodies.sort((a, b) => b.body.length - a.body.length);
+  const foldedInto = new Map<string, string>();
+  const summaries = new Map<string, FlowchartNode>();
+  for (const { branch, body } of bodies) {
+    if (foldedInto.has(branch.id)) {
+      continue;
+    }
+    const id = `collapsed_${branch.id}`;
+    const region = body.filter((node) => !foldedInto.has(node.id));
+    // Restore graph order so the placeholder takes the first node's place
+    region.sort((a, b) => ir.nodes.indexOf(a) - ir.nodes.indexOf(b));
+    region.forEach((node) => foldedInto.set(node.id, id));
+    const depth = Math.min(...region.map((node) => depthOf.get(node.id)!));
+    summaries.set(id, summarizeRegion(id, region, depth, "node", "collapsed"));
+  }
+
+  replaceRegions(ir, foldedInto, summaries);
+}
+
+/** Decisions and loop headers: the nodes whose bodies can be collapsed. */
+export function isCollapsible(node: FlowchartNode): boolean {
+  return (
+    node.nodeType === NodeType.DECISION ||
+    node.nodeType === NodeType.LOOP_START ||
+    (node.nodeType === undefined && node.shape === "diamond")
+  );
+}
+
+function isBoundary(node: FlowchartNode): boolean {
+  return node.nodeType === NodeType.ENTRY || node.nodeType === NodeType.EXIT;
+}
+
+/**
+ * Nesting depth per node id. Synthetic nodes without a source location
+ * (merge points) take the shallowest depth of their predecessors.
+ */
+function inferDepths(
+  ir: FlowchartIR,
+  controlEdges: FlowchartEdge[],
+  nodeById: Map<string, FlowchartNode>
+): Map<string, number> {
+  const depthOf = new Map<string, number>();
+  for (const node of ir.nodes) {
+    if (node.depth !== undefined) {
+      depthOf.set(node.id, node.depth);
+    }
+  }
+  // Depths only shrink and are bounded below, so this terminates
+  let changed = true;
+  while (changed) {
+    changed = false;
+    for (const edge of controlEdges) {
+      const target = nodeById.get(edge.to);
+      const inherited = depthOf.get(edge.from);
+      if (
+        !target ||
+        target.depth !== undefined ||
+        isBoundary(target) ||
+        inherited === undefined ||
+        inherited >= (depthOf.get(edge.to) ?? Infinity)
+      ) {
+        continue;
+      }
+      depthOf.set(edge.to, inherited);
+      changed = true;
+    }
+  }
+  return depthOf;
+}
+
+/**
+ * Swaps each region (node id -> summary id) for its summary node, which
+ * takes the place of the region's first node. Edges into and out of a
+ * region attach to the summary, and the location map points at it.
+ */
+function replaceRegions(
+  ir: FlowchartIR,
+  foldedInto: Map<string, string>,
+  summaries: Map<string, FlowchartNode>
+): void {
+  if (summaries.size === 0) {
+    return;
+  }
+
+  const edgeKeys = new Set<string>();
+  const edges: FlowchartEdge[] = [];
+  for (const edge of ir.edges) {
+    const from = foldedInto.get(edge.from) || edge.from;
+    const to = foldedInto.get(edge.to) || edge.to;
+    const key = `${from}\u0000${to}\u0000${edge.label || ""}\u0000${edge.edgeType || ""}`;
+    if (from === to || edgeKeys.has(key)) {
+      continue;
+    }
+    edgeKeys.add(key);
+    edges.push(from === edge.from && to === edge.to ? edge : { ...edge, from, to });
+  }
+
+  const nodes: FlowchartNode[] = [];
+  const placed = new Set<string>();
+  for (const node of ir.nodes) {
+    const summaryId = foldedInto.get(node.id);
+    if (summaryId === undefined) {
+      nodes.push(node);
+    } else if (!placed.has(summaryId)) {
+      placed.add(summaryId);
+      nodes.push(summaries.get(summaryId)!);
+    }
+  }
+  ir.nodes = nodes;
+  ir.edges = edges;
+  for (const entry of ir.locationMap) {
+    entry.nodeId = foldedInto.get(entry.nodeId) || entry.nodeId;
+  }
+}
+
+function summarizeRegion(
+  id: string,
+  region: FlowchartNode[],
+  depth: number,
+  noun: string,
+  verb: string
+): FlowchartNode {
+  const located = region.filter((node) => node.location);
+  const lines = region.map((node) => node.line).filter((line): line is number => line !== undefined);
+  let defines: string[] | undefined;
+  let uses: string[] | undefined;
+  for (const node of region) {
+    defines = mergeNames(defines, node.defines);
+    uses = mergeNames(uses, node.uses);
+  }
+  // Merge points are an artifact of the graph, not code the reader would miss
+  const count = region.filter((node) => node.nodeType !== NodeType.MERGE).length || region.length;
+  // Drawn in the box of the outermost body it replaces
+  const outermost = region.reduce((best, node) =>
+    (node.depth ?? Infinity) < (best.depth ?? Infinity) ? node : best
+  );
+  return {
+    id,
+    label: `${count} ${noun}${count === 1 ? "" : "s"} ${verb}`,
+    location:
+      located.length > 0
+        ? {
+            start: Math.min(...located.map((node) => node.location!.start)),
+            end: Math.max(...located.map((node) => node.location!.end)),
+          }
+        : undefined,
+    line: lines.length > 0 ? Math.min(...lines) : undefined,
+    depth,
+    scope: outermost.scope,
+    shape: "rect",
+    defines,
+    uses,
+    unreachable: region.every((node) => node.unreachable) || undefined,
+    folded: count,
+  };
+}
+
+/**
+ * Plain statements that may be folded together. Branches, loops, returns
+ * and other control-flow nodes are never merged.
+ */
+const MERGEABLE_NODE_TYPES: ReadonlySet<NodeType> = new Set([
+  NodeType.PROCESS,
+  NodeType.ASSIGNMENT,
+  NodeType.FUNCTION_CALL,
+  NodeType.METHOD_CALL,
+  NodeType.MACRO_CALL,
+]);
+
+/**
+ * Merges consecutive statement nodes that each have exactly one incoming and
+ * one outgoing edge into the first node of the run. The merged label lists
+ * the statements one per line, and the location map is redirected so cursor
+ * sync still lands on the merged node.
+ */
+export function compactLinearSequences(ir: FlowchartIR): void {
+  const incoming = new Map<string, FlowchartEdge[]>();
+  const outgoing = new Map<string, FlowchartEdge[]>();
+  for (const edge of ir.edges) {
+    if (edge.edgeType === EdgeType.DATAFLOW) {
+      continue;
+    }
+    addToMultiMap(outgoing, edge.from, edge);
+    addToMultiMap(incoming, edge.to, edge);
+  }
+  const nodeById = new Map(ir.nodes.map((node) => [node.id, node]));
+
+  const isCandidate = (id: string): boolean => {
+    const node = nodeById.get(id);
+    return (
+      !!node &&
+      !!node.nodeType &&
+      MERGEABLE_NODE_TYPES.has(node.nodeType) &&
+      node.shape !== "diamond" &&
+      incoming.get(id)?.length === 1 &&
+      outgoing.get(id)?.length === 1
+    );
+  };
+  // Labelled edges carry branch meaning (Ok, true, ...) and must stay visible;
+  // a run never leaves the body it started in
+  const canJoin = (edge: FlowchartEdge | undefined): edge is FlowchartEdge =>
+    !!edge &&
+    !edge.label &&
+    nodeById.get(edge.from)?.scope === nodeById.get(edge.to)?.scope &&
+    edge.edgeType !== EdgeType.BACK &&
+    edge.from !== edge.to &&
+    isCandidate(edge.from) &&
+    isCandidate(edge.to);
+
+  const removedNodes = new Set<string>();
+  const removedEdges = new Set<FlowchartEdge>();
+  const addedEdges: FlowchartEdge[] = [];
+  const mergedInto = new Map<string, string>();
+
+  for (const head of ir.nodes) {
+    if (removedNodes.has(head.id) || !isCandidate(head.id)) {
+      continue;
+    }
+    // Only start from the first node of a run
+    if (canJoin(incoming.get(head.id)![0])) {
+      continue;
+    }
+
+    const labels = [head.label];
+    let tail = head;
+    let edge = outgoing.get(head.id)![0];
+    while (canJoin(edge) && !removedNodes.has(edge.to)) {
+      const next = nodeById.get(edge.to)!;
+      removedEdges.add(edge);
+      removedNodes.add(next.id);
+      mergedInto.set(next.id, head.id);
+      labels.push(next.label);
+      head.defines = mergeNames(head.defines, next.defines);
+      head.uses = mergeNames(head.uses, next.uses);
+      tail = next;
+      edge = outgoing.get(next.id)![0];
+    }
+
+    if (tail === head) {
+      continue;
+    }
+
+    head.label = labels.join("\n");
+    if (head.location && tail.location) {
+      head.location = { start: head.location.start, end: tail.location.end };
+    }
+    // The run's last outgoing edge now leaves from the head
+    removedEdges.add(edge);
+    addedEdges.push({ ...edge, from: head.id });
+  }
+
+  if (removedNodes.size === 0) {
+    return;
+  }
+
+  // Def-use edges follow their endpoints into the merged node
+  const dataflowKeys = new Set<string>();
+  const keptEdges: FlowchartEdge[] = [];
+  for (const edge of ir.edges) {
+    if (removedEdges.has(edge)) {
+      continue;
+    }
+    if (edge.edgeType !== EdgeType.DATAFLOW) {
+      keptEdges.push(edge);
+      continue;
+    }
+    const from = mergedInto.get(edge.from) || edge.from;
+    const to = mergedInto.get(edge.to) || edge.to;
+    const key = `${from}\u0000${to}\u0000${edge.label}`;
+    if (from !== to && !dataflowKeys.has(key)) {
+      dataflowKeys.add(key);
+      keptEdges.push({ ...edge, from, to });
+    }
+  }
+
+  ir.nodes = ir.nodes.filter((node) => !removedNodes.has(node.id));
+  ir.edges = [...keptEdges, ...addedEdges];
+  for (const entry of ir.locationMap) {
+    entry.nodeId = mergedInto.get(entry.nodeId) || entry.nodeId;
+  }
+}
+
+function mergeNames(a?: string[], b?: string[]): string[] | undefined {
+  if (!a || !b) {
+    return a || b;
+  }
+  return Array.from(new Set([...a, ...b]));
+}
+
+type ReachingDefinitions = Map<string, Set<string>>;
+
+/**
+ * Adds the data-flow view: each node's label gains a `def … · use …` line,
+ * and a dashed DATAFLOW edge runs from every definition to each use it
+ * reaches along the control-flow edges (classic reaching definitions).
+ * Edges between the same pair of nodes are combined, listing every
+ * variable on one label.
+ */
+export function addDataflowOverlay(ir: FlowchartIR): void {
+  const predecessors = new Map<string, string[]>();
+  for (const edge of ir.edges) {
+    if (edge.edgeType !== EdgeType.DATAFLOW) {
+      addToMultiMap(predecessors, edge.to, edge.from);
+    }
+  }
+
+  const reachingOut = new Map<string, ReachingDefinitions>();
+  const reachingIn = (id: string): ReachingDefinitions => {
+    const merged: ReachingDefinitions = new Map();
+    for (const predecessor of predecessors.get(id) || []) {
+      for (const [name, definitions] of reachingOut.get(predecessor) || []) {
+        const set = merged.get(name) || new Set<string>();
+        definitions.forEach((definition) => set.add(definition));
+        merged.set(name, set);
+      }
+    }
+    return merged;
+  };
+
+  // Iterate to a fixed point; the sets only grow, so this terminates
+  let changed = true;
+  while (changed) {
+    changed = false;
+    for (const node of ir.nodes) {
+      const out = reachingIn(node.id);
+      for (const name of node.defines || []) {
+        out.set(name, new Set([node.id]));
+      }
+      if (!sameDefinitions(out, reachingOut.get(node.id))) {
+        reachingOut.set(node.id, out);
+        changed = true;
+      }
+    }
+  }
+
+  const labels = new Map<string, { from: string; to: string; names: string[] }>();
+  for (const node of ir.nodes) {
+    if (!node.uses || node.uses.length === 0) {
+      continue;
+    }
+    const reaching = reachingIn(node.id);
+    for (const name of node.uses) {
+      for (const definition of reaching.get(name) || []) {
+        if (definition === node.id) {
+          continue;
+        }
+        const key = `${definition}\u0000${node.id}`;
+        const entry = labels.get(key) || { from: definition, to: node.id, names: [] };
+        entry.names.push(name);
+        labels.set(key, entry);
+      }
+    }
+  }
+  for (const { from, to, names } of labels.values()) {
+    ir.edges.push({ from, to, label: names.join(", "), edgeType: EdgeType.DATAFLOW });
+  }
+
+  for (const node of ir.nodes) {
+    const parts: string[] = [];
+    if (node.defines && node.defines.length > 0) {
+      parts.push(`def ${node.defines.join(", ")}`);
+    }
+    if (node.uses && node.uses.length > 0) {
+      parts.push(`use ${node.uses.join(", ")}`);
+    }
+    if (parts.length > 0) {
+      node.label = `${node.label}\n${parts.join(" · ")}`;
+    }
+  }
+}
+
+function sameDefinitions(a: ReachingDefinitions, b?: ReachingDefinitions): boolean {
+  if (!b || a.size !== b.size) {
+    return false;
+  }
+  for (const [name, definitions] of a) {
+    const other = b.get(name);
+    if (!other || other.size !== definitions.size) {
+      return false;
+    }
+    for (const definition of definitions) {
+      if (!other.has(definition)) {
+        return false;
+      }
+    }
+  }
+  return true;
+}
+
+function addToMultiMap<K, V>(map: Map<K, V[]>, key: K, value: V): void {
+  const values = map.get(key);
+  if (values) {
+    values.push(value);
+  } else {
+    map.set(key, [value]);
+  }
+}
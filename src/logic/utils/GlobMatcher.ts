@@ -0,0 +1,68 @@
+/**
+ * Minimal glob matching for include/exclude filters on relative paths.
+ *
+ * Supports `**` (any number of directories), `*` (anything but `/`), `?`
+ * (one character other than `/`) and `{a,b}` alternatives. Paths are matched
+ * with forward slashes regardless of platform. A pattern without a `/`
+ * matches against the file name alone, so `*.rs` selects Rust files at any
+ * depth.
+ */
+export class GlobMatcher {
+  private readonly patterns: RegExp[];
+
+  constructor(globs: string[]) {
+    this.patterns = globs
+      .map((glob) => glob.trim())
+      .filter((glob) => glob.length > 0)
+      .map((glob) => GlobMatcher.toRegExp(glob));
+  }
+
+  public get isEmpty(): boolean {
+    return this.patterns.length === 0;
+  }
+
+  public matches(relativePath: string): boolean {
+    const normalized = relativePath.replace(/\\/g, "/").replace(/^\.\//, "");
+    return this.patterns.some((pattern) => pattern.test(normalized));
+  }
+
+  private static toRegExp(glob: string): RegExp {
+    let pattern = glob.replace(/\\/g, "/").replace(/^\.\//, "");
+    if (!pattern.includes("/")) {
+      pattern = `**/${pattern}`;
+    }
+
+    let source = "";
+    let inAlternatives = false;
+    for (let i = 0; i < pattern.length; i++) {
+      const char = pattern[i];
+      if (char === "*") {
+        if (pattern[i + 1] === "*") {
+          // `**/` spans zero or more whole directories
+          if (pattern[i + 2] === "/") {
+            source += "(?:.*/)?";
+            i += 2;
+          } else {
+            source += ".*";
+            i += 1;
+          }
+        } else {
+          source += "[^/]*";
+        }
+      } else if (char === "?") {
+        source += "[^/]";
+      } else if (char === "{") {
+        inAlternatives = true;
+        source += "(?:";
+      } else if (char === "}" && inAlternatives) {
+        inAlternatives = false;
+        source += ")";
+      } else if (char === "," && inAlternatives) {
+        source += "|";
+      } else {
+        source += char.replace(/[.+^$()|[\]\\]/g, "\\$&");
+      }
+    }
+    return new RegExp(`^${source}$`);
+  }
+}
@@ -0,0 +1,80 @@
+/**
+ * Maps raw IR node IDs to identifiers that are safe for a diagram syntax.
+ *
+ * Sanitizing by stripping characters alone is lossy ("a-b" and "ab" would
+ * both become "ab"), so every raw ID is assigned a unique output ID the first
+ * time it is seen and the same output ID on every later lookup. Assignment
+ * follows first-seen order, which keeps the output stable for a given IR.
+ */
+export class IdSanitizer {
+  private assigned = new Map<string, string>();
+  private used = new Set<string>();
+
+  constructor(private readonly reservedWords: ReadonlySet<string> = new Set()) {}
+
+  public get(rawId: string): string {
+    const existing = this.assigned.get(rawId);
+    if (existing !== undefined) {
+      return existing;
+    }
+
+    let base = rawId
+      .replace(/\s+/g, "_")
+      .replace(/[^\w]/g, "_") // Replace anything that is not [A-Za-z0-9_]
+      .replace(/_+/g, "_")
+      .replace(/^_+|_+$/g, "");
+
+    if (!base) {
+      base = "n";
+    }
+    if (/^\d/.test(base) || this.reservedWords.has(base.toLowerCase())) {
+      base = `n_${base}`;
+    }
+
+    let candidate = base;
+    let suffix = 2;
+    while (this.used.has(candidate)) {
+      candidate = `${base}_${suffix++}`;
+    }
+
+    this.assigned.set(rawId, candidate);
+    this.used.add(candidate);
+    return candidate;
+  }
+}
+
+/**
+ * Keywords that break Mermaid flowchart parsing when used as bare node IDs.
+ */
+export const MERMAID_RESERVED_WORDS: ReadonlySet<string> = new Set([
+  "end",
+  "graph",
+  "flowchart",
+  "subgraph",
+  "direction",
+  "style",
+  "linkstyle",
+  "classdef",
+  "class",
+  "click",
+  "call",
+  "href",
+  "default",
+]);
+
+/**
+ * Keywords that PlantUML would interpret as element or directive names.
+ */
+export const PLANTUML_RESERVED_WORDS: ReadonlySet<string> = new Set([
+  "as",
+  "end",
+  "title",
+  "note",
+  "skinparam",
+  "rectangle",
+  "hexagon",
+  "usecase",
+  "package",
+  "frame",
+  "together",
+]);
@@ -0,0 +1,259 @@
+import { FlowchartIR, FlowchartNode, NodeType } from "../../ir/ir";
+import { StringProcessor } from "./StringProcessor";
+
+/**
+ * What node labels show: `full` is the verbatim source text of the
+ * statement, `summary` a short phrase such as "assign result", `kind` only
+ * the node type. Without a mode the parsers' own labels are kept.
+ */
+export type LabelMode = "full" | "summary" | "kind";
+
+export const LABEL_MODES: readonly LabelMode[] = ["full", "summary", "kind"];
+
+/** How lines longer than the label width are shortened. */
+export type LabelOverflow = "wrap" | "truncate";
+
+export function isLabelMode(value: string): value is LabelMode {
+  return (LABEL_MODES as readonly string[]).includes(value);
+}
+
+const KIND_LABELS: Record<NodeType, string> = {
+  [NodeType.ENTRY]: "Start",
+  [NodeType.EXIT]: "End",
+  [NodeType.PROCESS]: "statement",
+  [NodeType.DECISION]: "decision",
+  [NodeType.MERGE]: "merge",
+  [NodeType.LOOP_START]: "loop",
+  [NodeType.LOOP_END]: "loop end",
+  [NodeType.EXCEPTION]: "exception",
+  [NodeType.BREAK_CONTINUE]: "jump",
+  [NodeType.FUNCTION_CALL]: "call",
+  [NodeType.ASSIGNMENT]: "assignment",
+  [NodeType.RETURN]: "return",
+  [NodeType.ASYNC_OPERATION]: "async operation",
+  [NodeType.AWAIT]: "await",
+  [NodeType.PANIC]: "panic",
+  [NodeType.EARLY_RETURN_ERROR]: "error return",
+  [NodeType.METHOD_CALL]: "method call",
+  [NodeType.MACRO_CALL]: "macro call",
+  [NodeType.SUBROUTINE]: "subroutine",
+};
+
+// The callee of the first call in a statement: `f(`, `obj.method(`,
+// `ns::f(`, `ptr->f(` or a Rust macro `name!(`
+const CALLEE_PATTERN =
+  /(?:^|[^\w$.:>])([A-Za-z_$][\w$]*(?:(?:\.|::|->)[A-Za-z_$][\w$]*)*!?)\s*\(/;
+
+// One visible character of an escaped label; entities count as one
+const GLYPH_PATTERN = /#(?:quot|60|62|96);|\\\\|[\s\S]/g;
+
+/**
+ * Rewrites every node label for the mode. Start, End, merge points and
+ * folded summaries keep their labels in all modes.
+ */
+export function applyLabelMode(ir: FlowchartIR, mode: LabelMode): void {
+  const sourceSpans = mode === "full" ? spansWithSource(ir) : [];
+  for (const node of ir.nodes) {
+    if (isFixed(node)) {
+      continue;
+    }
+    const label =
+      mode === "full"
+        ? fullLabel(ir, node, sourceSpans)
+        : mode === "summary"
+          ? summaryLabel(node)
+          : kindLabel(node);
+    if (label !== undefined) {
+      node.label = label;
+    }
+  }
+}
+
+/**
+ * Keeps every label line within `width` characters: longer lines wrap at
+ * spaces (words longer than a line are split), or with "truncate" are cut
+ * and end in an ellipsis.
+ */
+export function fitLabels(
+  ir: FlowchartIR,
+  width: number,
+  overflow: LabelOverflow = "wrap"
+): void {
+  // Room for at least one character before the ellipsis
+  const limit = Math.max(4, Math.floor(width));
+  for (const node of ir.nodes) {
+    node.label = node.label
+      .split("\n")
+      .map((line) =>
+        overflow === "truncate"
+          ? truncateLine(line, limit)
+          : wrapLine(line, limit)
+      )
+      .join("\n");
+  }
+}
+
+function isFixed(node: FlowchartNode): boolean {
+  return (
+    !!node.folded ||
+    node.nodeType === NodeType.ENTRY ||
+    node.nodeType === NodeType.EXIT ||
+    node.nodeType === NodeType.MERGE ||
+    node.nodeType === NodeType.SUBROUTINE
+  );
+}
+
+function spansWithSource(ir: FlowchartIR): { start: number; end: number }[] {
+  return ir.nodes
+    .filter((node) => node.location && !isFixed(node))
+    .map((node) => node.location!);
+}
+
+/**
+ * The statement's source text, whitespace runs collapsed. A node whose span
+ * encloses other nodes stands for a whole compound statement (a loop, a
+ * labelled block), so it keeps its header label instead.
+ */
+function fullLabel(
+  ir: FlowchartIR,
+  node: FlowchartNode,
+  spans: { start: number; end: number }[]
+): string | undefined {
+  const { location } = node;
+  if (!location || ir.functionSource === undefined || !ir.functionRange) {
+    return undefined;
+  }
+  const enclosesOther = spans.some(
+    (span) =>
+      span !== location &&
+      span.start >= location.start &&
+      span.end <= location.end &&
+      span.end - span.start < location.end - location.start
+  );
+  if (enclosesOther) {
+    return undefined;
+  }
+  const offset = ir.functionRange.start;
+  const text = ir.functionSource
+    .slice(location.start - offset, location.end - offset)
+    .replace(/\s+/g, " ")
+    .trim();
+  return text ? StringProcessor.escapeString(text, Infinity) : undefined;
+}
+
+function summaryLabel(node: FlowchartNode): string | undefined {
+  const text = StringProcessor.unescapeString(node.label);
+  const callee = CALLEE_PATTERN.exec(text)?.[1];
+  const names = (list?: string[]): string | undefined =>
+    list && list.length > 0
+      ? list.slice(0, 3).join(", ") + (list.length > 3 ? ", ..." : "")
+      : undefined;
+  const defines = names(node.defines);
+  const uses = names(node.uses);
+
+  let phrase: string;
+  switch (node.nodeType) {
+    case NodeType.ASSIGNMENT:
+      phrase = defines ? `assign ${defines}` : "assign value";
+      break;
+    case NodeType.FUNCTION_CALL:
+    case NodeType.METHOD_CALL:
+    case NodeType.MACRO_CALL:
+      phrase = callee ? `call ${callee}` : "call";
+      break;
+    case NodeType.ASYNC_OPERATION:
+      phrase = callee ? `start ${callee}` : "start async operation";
+      break;
+    case NodeType.AWAIT:
+      phrase = callee ? `await ${callee}` : "await";
+      break;
+    case NodeType.DECISION:
+      phrase = uses ? `check ${uses}` : "check condition";
+      break;
+    case NodeType.LOOP_START:
+      phrase = uses ? `loop on ${uses}` : "loop";
+      break;
+    case NodeType.LOOP_END:
+      phrase = "end loop";
+      break;
+    case NodeType.RETURN:
+      phrase = uses ? `return ${uses}` : "return";
+      break;
+    case NodeType.EXCEPTION:
+      phrase = firstWord(text) || "exception";
+      break;
+    case NodeType.BREAK_CONTINUE:
+      // Already as short as a phrase: break, continue, goto <label>
+      return undefined;
+    case NodeType.PANIC:
+      phrase = "panic";
+      break;
+    case NodeType.EARLY_RETURN_ERROR:
+      phrase = "propagate error";
+      break;
+    default:
+      if (node.shape === "diamond") {
+        phrase = uses ? `check ${uses}` : "check condition";
+      } else if (defines) {
+        phrase = `assign ${defines}`;
+      } else if (callee) {
+        phrase = `call ${callee}`;
+      } else {
+        phrase = "statement";
+      }
+  }
+  return StringProcessor.escapeString(phrase);
+}
+
+function kindLabel(node: FlowchartNode): string {
+  if (node.nodeType) {
+    return KIND_LABELS[node.nodeType];
+  }
+  return node.shape === "diamond" ? "decision" : "statement";
+}
+
+function firstWord(text: string): string | undefined {
+  return /^[A-Za-z_]+/.exec(text.trim())?.[0];
+}
+
+function glyphs(line: string): string[] {
+  return line.match(GLYPH_PATTERN) || [];
+}
+
+function truncateLine(line: string, limit: number): string {
+  const chars = glyphs(line);
+  if (chars.length <= limit) {
+    return line;
+  }
+  return chars.slice(0, limit - 3).join("").trimEnd() + "...";
+}
+
+function wrapLine(line: string, limit: number): string {
+  if (glyphs(line).length <= limit) {
+    return line;
+  }
+  const lines: string[] = [];
+  let current: string[] = [];
+  for (const word of line.split(" ").filter(Boolean)) {
+    let chars = glyphs(word);
+    const needed = current.length + (current.length > 0 ? 1 : 0) + chars.length;
+    if (needed <= limit) {
+      current = current.length > 0 ? [...current, " ", ...chars] : chars;
+      continue;
+    }
+    if (current.length > 0) {
+      lines.push(current.join(""));
+      current = [];
+    }
+    // A word longer than a whole line is split across lines
+    while (chars.length > limit) {
+      lines.push(chars.slice(0, limit).join(""));
+      chars = chars.slice(limit);
+    }
+    current = chars;
+  }
+  if (current.length > 0) {
+    lines.push(current.join(""));
+  }
+  return lines.join("\n");
+}
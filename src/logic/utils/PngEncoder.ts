@@ -0,0 +1,69 @@
+import * as zlib from "zlib";
+
+const SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+const INCHES_PER_METER = 39.3701;
+
+let crcTable: Uint32Array | undefined;
+
+function crc32(bytes: Buffer): number {
+  if (!crcTable) {
+    crcTable = new Uint32Array(256);
+    for (let n = 0; n < 256; n++) {
+      let c = n;
+      for (let k = 0; k < 8; k++) {
+        c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+      }
+      crcTable[n] = c >>> 0;
+    }
+  }
+  let crc = 0xffffffff;
+  for (const byte of bytes) {
+    crc = crcTable[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+function chunk(type: string, data: Buffer): Buffer {
+  const length = Buffer.alloc(4);
+  length.writeUInt32BE(data.length);
+  const body = Buffer.concat([Buffer.from(type, "ascii"), data]);
+  const crc = Buffer.alloc(4);
+  crc.writeUInt32BE(crc32(body));
+  return Buffer.concat([length, body, crc]);
+}
+
+/**
+ * Encodes 8-bit RGBA pixels as a PNG. The DPI is recorded in a pHYs chunk
+ * so image viewers and word processors show the picture at its intended
+ * physical size.
+ */
+export function encodePng(width: number, height: number, rgba: Uint8Array, dpi: number): Buffer {
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(width, 0);
+  header.writeUInt32BE(height, 4);
+  header[8] = 8; // bit depth
+  header[9] = 6; // color type: RGBA
+  // Bytes 10-12: deflate compression, adaptive filtering, no interlace
+
+  const physical = Buffer.alloc(9);
+  const pixelsPerMeter = Math.round(dpi * INCHES_PER_METER);
+  physical.writeUInt32BE(pixelsPerMeter, 0);
+  physical.writeUInt32BE(pixelsPerMeter, 4);
+  physical[8] = 1; // unit: meter
+
+  // Each scanline starts with its filter type; 0 leaves the row unfiltered
+  const stride = width * 4;
+  const raw = Buffer.alloc((stride + 1) * height);
+  for (let y = 0; y < height; y++) {
+    raw[y * (stride + 1)] = 0;
+    raw.set(rgba.subarray(y * stride, (y + 1) * stride), y * (stride + 1) + 1);
+  }
+
+  return Buffer.concat([
+    SIGNATURE,
+    chunk("IHDR", header),
+    chunk("pHYs", physical),
+    chunk("IDAT", zlib.deflateSync(raw)),
+    chunk("IEND", Buffer.alloc(0)),
+  ]);
+}
@@ -0,0 +1,265 @@
+/**
+ * A small anti-aliased software rasterizer for the PNG export: filled
+ * polygons and stroked polylines on an RGBA canvas. Coordinates are in
+ * diagram units and multiplied by the canvas scale, so the same drawing
+ * calls produce a sharper image at a higher DPI.
+ */
+
+export interface RasterPoint {
+  x: number;
+  y: number;
+}
+
+/** Straight (non-premultiplied) color, channels in 0..1. */
+export interface RasterColor {
+  r: number;
+  g: number;
+  b: number;
+  a: number;
+}
+
+// Sub-scanlines per pixel row for polygon coverage
+const SUBSAMPLES = 5;
+
+const NAMED_COLORS: Readonly<Record<string, string>> = {
+  black: "#000000",
+  white: "#ffffff",
+  gray: "#808080",
+  grey: "#808080",
+  silver: "#c0c0c0",
+  red: "#ff0000",
+  maroon: "#800000",
+  orange: "#ffa500",
+  yellow: "#ffff00",
+  olive: "#808000",
+  lime: "#00ff00",
+  green: "#008000",
+  aqua: "#00ffff",
+  cyan: "#00ffff",
+  teal: "#008080",
+  blue: "#0000ff",
+  navy: "#000080",
+  fuchsia: "#ff00ff",
+  magenta: "#ff00ff",
+  purple: "#800080",
+};
+
+/**
+ * Parses the color forms DiagramColors accepts: #rgb, #rrggbb, #rrggbbaa and
+ * basic color names. Unknown names fall back to black.
+ */
+export function parseColor(value: string, opacity = 1): RasterColor {
+  let hex = value.toLowerCase();
+  if (hex === "transparent") {
+    return { r: 0, g: 0, b: 0, a: 0 };
+  }
+  hex = NAMED_COLORS[hex] || hex;
+  if (/^#[0-9a-f]{3}$/.test(hex)) {
+    hex = "#" + hex.slice(1).replace(/./g, (digit) => digit + digit);
+  }
+  if (!/^#[0-9a-f]{6}([0-9a-f]{2})?$/.test(hex)) {
+    return { r: 0, g: 0, b: 0, a: opacity };
+  }
+  const channel = (index: number) => parseInt(hex.slice(index, index + 2), 16) / 255;
+  return {
+    r: channel(1),
+    g: channel(3),
+    b: channel(5),
+    a: (hex.length === 9 ? channel(7) : 1) * opacity,
+  };
+}
+
+/** Per-pixel coverage for the bounding box of one shape. */
+class CoverageMask {
+  readonly data: Float32Array;
+
+  constructor(
+    readonly left: number,
+    readonly top: number,
+    readonly width: number,
+    readonly height: number
+  ) {
+    this.data = new Float32Array(Math.max(0, width * height));
+  }
+}
+
+export class Raster {
+  /** Premultiplied RGBA, row-major. */
+  private readonly pixels: Float32Array;
+
+  constructor(
+    readonly width: number,
+    readonly height: number,
+    private readonly scale: number
+  ) {
+    this.pixels = new Float32Array(width * height * 4);
+  }
+
+  public fill(color: RasterColor): void {
+    for (let i = 0; i < this.pixels.length; i += 4) {
+      this.pixels[i] = color.r * color.a;
+      this.pixels[i + 1] = color.g * color.a;
+      this.pixels[i + 2] = color.b * color.a;
+      this.pixels[i + 3] = color.a;
+    }
+  }
+
+  /** Fills a closed polygon (even-odd rule). */
+  public fillPolygon(points: RasterPoint[], color: RasterColor): void {
+    const scaled = points.map((point) => ({ x: point.x * this.scale, y: point.y * this.scale }));
+    const mask = this.maskFor(scaled, 0);
+    if (!mask) {
+      return;
+    }
+
+    const weight = 1 / SUBSAMPLES;
+    for (let row = 0; row < mask.height; row++) {
+      for (let sample = 0; sample < SUBSAMPLES; sample++) {
+        const y = mask.top + row + (sample + 0.5) / SUBSAMPLES;
+        const crossings: number[] = [];
+        for (let i = 0; i < scaled.length; i++) {
+          const a = scaled[i];
+          const b = scaled[(i + 1) % scaled.length];
+          if ((a.y <= y && b.y > y) || (b.y <= y && a.y > y)) {
+            crossings.push(a.x + ((y - a.y) * (b.x - a.x)) / (b.y - a.y));
+          }
+        }
+        crossings.sort((a, b) => a - b);
+        for (let i = 0; i + 1 < crossings.length; i += 2) {
+          this.addSpan(mask, row, crossings[i], crossings[i + 1], weight);
+        }
+      }
+    }
+    this.composite(mask, color);
+  }
+
+  /**
+   * Strokes polylines with round joins and caps. All lines share one
+   * coverage mask, so overlapping strokes (a glyph's, a dashed curve's)
+   * never darken where they meet.
+   */
+  public strokePolylines(lines: RasterPoint[][], lineWidth: number, color: RasterColor): void {
+    const scaled = lines.map((points) =>
+      points.map((point) => ({ x: point.x * this.scale, y: point.y * this.scale }))
+    );
+    const halfWidth = (lineWidth * this.scale) / 2;
+    const mask = this.maskFor(scaled.flat(), halfWidth + 1);
+    if (!mask) {
+      return;
+    }
+
+    for (const points of scaled) {
+      if (points.length === 1) {
+        this.strokeSegment(mask, points[0], points[0], halfWidth);
+      }
+      for (let i = 0; i + 1 < points.length; i++) {
+        this.strokeSegment(mask, points[i], points[i + 1], halfWidth);
+      }
+    }
+    this.composite(mask, color);
+  }
+
+  /** Un-premultiplied 8-bit RGBA, as stored in a PNG. */
+  public toRgba8(): Uint8Array {
+    const bytes = new Uint8Array(this.pixels.length);
+    for (let i = 0; i < this.pixels.length; i += 4) {
+      const alpha = this.pixels[i + 3];
+      if (alpha <= 0) {
+        continue;
+      }
+      for (let c = 0; c < 3; c++) {
+        bytes[i + c] = Math.round(Math.min(1, this.pixels[i + c] / alpha) * 255);
+      }
+      bytes[i + 3] = Math.round(Math.min(1, alpha) * 255);
+    }
+    return bytes;
+  }
+
+  private maskFor(points: RasterPoint[], padding: number): CoverageMask | undefined {
+    if (points.length === 0) {
+      return undefined;
+    }
+    const xs = points.map((point) => point.x);
+    const ys = points.map((point) => point.y);
+    const left = Math.max(0, Math.floor(Math.min(...xs) - padding));
+    const top = Math.max(0, Math.floor(Math.min(...ys) - padding));
+    const right = Math.min(this.width, Math.ceil(Math.max(...xs) + padding));
+    const bottom = Math.min(this.height, Math.ceil(Math.max(...ys) + padding));
+    if (right <= left || bottom <= top) {
+      return undefined;
+    }
+    return new CoverageMask(left, top, right - left, bottom - top);
+  }
+
+  /** Adds exact horizontal coverage of [x0, x1) to one mask row. */
+  private addSpan(mask: CoverageMask, row: number, x0: number, x1: number, weight: number): void {
+    const start = Math.max(x0, mask.left) - mask.left;
+    const end = Math.min(x1, mask.left + mask.width) - mask.left;
+    if (end <= start) {
+      return;
+    }
+    const offset = row * mask.width;
+    const first = Math.floor(start);
+    const last = Math.floor(end);
+    if (first === last) {
+      mask.data[offset + first] += (end - start) * weight;
+      return;
+    }
+    mask.data[offset + first] += (first + 1 - start) * weight;
+    for (let x = first + 1; x < last; x++) {
+      mask.data[offset + x] += weight;
+    }
+    if (last < mask.width) {
+      mask.data[offset + last] += (end - last) * weight;
+    }
+  }
+
+  /** Coverage from the distance between each pixel center and the segment. */
+  private strokeSegment(
+    mask: CoverageMask,
+    a: RasterPoint,
+    b: RasterPoint,
+    halfWidth: number
+  ): void {
+    const reach = halfWidth + 1;
+    const x0 = Math.max(mask.left, Math.floor(Math.min(a.x, b.x) - reach));
+    const x1 = Math.min(mask.left + mask.width, Math.ceil(Math.max(a.x, b.x) + reach));
+    const y0 = Math.max(mask.top, Math.floor(Math.min(a.y, b.y) - reach));
+    const y1 = Math.min(mask.top + mask.height, Math.ceil(Math.max(a.y, b.y) + reach));
+    const dx = b.x - a.x;
+    const dy = b.y - a.y;
+    const lengthSquared = dx * dx + dy * dy;
+
+    for (let y = y0; y < y1; y++) {
+      const py = y + 0.5;
+      for (let x = x0; x < x1; x++) {
+        const px = x + 0.5;
+        let t = lengthSquared > 0 ? ((px - a.x) * dx + (py - a.y) * dy) / lengthSquared : 0;
+        t = Math.max(0, Math.min(1, t));
+        const distance = Math.hypot(px - (a.x + t * dx), py - (a.y + t * dy));
+        const coverage = Math.min(1, halfWidth + 0.5 - distance);
+        const index = (y - mask.top) * mask.width + (x - mask.left);
+        if (coverage > mask.data[index]) {
+          mask.data[index] = coverage;
+        }
+      }
+    }
+  }
+
+  private composite(mask: CoverageMask, color: RasterColor): void {
+    for (let row = 0; row < mask.height; row++) {
+      for (let column = 0; column < mask.width; column++) {
+        const coverage = Math.min(1, mask.data[row * mask.width + column]);
+        if (coverage <= 0) {
+          continue;
+        }
+        const alpha = coverage * color.a;
+        const index = ((mask.top + row) * this.width + mask.left + column) * 4;
+        this.pixels[index] = color.r * alpha + this.pixels[index] * (1 - alpha);
+        this.pixels[index + 1] = color.g * alpha + this.pixels[index + 1] * (1 - alpha);
+        this.pixels[index + 2] = color.b * alpha + this.pixels[index + 2] * (1 - alpha);
+        this.pixels[index + 3] = alpha + this.pixels[index + 3] * (1 - alpha);
+      }
+    }
+  }
+}
@@ -3,6 +3,7 @@ import { FlowchartEdge, FlowchartNode } from "../../ir/ir";
 export class StringProcessor {
   private static escapeCache = new Map<string, string>();
   private static readonly MAX_CACHE_SIZE = 1000;
+  private static readonly MAX_LABEL_LENGTH = 80;
 
   // Precompiled regex for better performance
   private static readonly escapeRegex = /"|\\|\n|<|>|`/g;
@@ -16,15 +17,26 @@ export class StringProcessor {
     "`": "#96;",
   };
 
-  static escapeString(str: string): string {
+  /**
+   * Escapes a label for the emitters and shortens it to `maxLength`
+   * characters; pass Infinity to keep the whole text.
+   */
+  static escapeString(
+    str: string,
+    maxLength: number = StringProcessor.MAX_LABEL_LENGTH
+  ): string {
     if (!str) return "";
 
     // Check cache first
-    const cached = this.escapeCache.get(str);
+    const cacheKey =
+      maxLength === StringProcessor.MAX_LABEL_LENGTH
+        ? str
+        : `${maxLength}\u0000${str}`;
+    const cached = this.escapeCache.get(cacheKey);
     if (cached !== undefined) {
       // Move to end for LRU behavior
-      this.escapeCache.delete(str);
-      this.escapeCache.set(str, cached);
+      this.escapeCache.delete(cacheKey);
+      this.escapeCache.set(cacheKey, cached);
       return cached;
     }
 
@@ -43,15 +55,28 @@ export class StringProcessor {
     escaped = escaped.replace(this.colonRegex, "").trim();
 
     // Length limiting for readability
-    const MAX_LABEL_LENGTH = 80;
-    if (escaped.length > MAX_LABEL_LENGTH) {
-      escaped = escaped.substring(0, MAX_LABEL_LENGTH - 3) + "...";
+    if (escaped.length > maxLength) {
+      escaped = escaped.substring(0, maxLength - 3) + "...";
     }
 
-    this.escapeCache.set(str, escaped);
+    this.escapeCache.set(cacheKey, escaped);
     return escaped;
   }
 
+  /**
+   * Reverses escapeString's Mermaid entity encoding, for emitters of other
+   * formats that read the same IR labels. Truncation is not reversible.
+   */
+  static unescapeString(str: string): string {
+    if (!str) return "";
+    return str
+      .replace(/#quot;/g, '"')
+      .replace(/#60;/g, "<")
+      .replace(/#62;/g, ">")
+      .replace(/#96;/g, "`")
+      .replace(/\\\\/g, "\\");
+  }
+
   static clearCache(): void {
     this.escapeCache.clear();
   }
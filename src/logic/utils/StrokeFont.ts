@@ -0,0 +1,227 @@
+/**
+ * A single-stroke vector font for the built-in rasterizer. Glyphs are
+ * centerline polylines, so text is drawn with the same anti-aliased strokes
+ * as the edges and stays sharp at any resolution.
+ *
+ * Font units: baseline at y = 0, y pointing up, cap height 10, x-height 7,
+ * descenders down to -3.
+ */
+
+export type FontStroke = [number, number][];
+
+interface Glyph {
+  width: number;
+  strokes: FontStroke[];
+}
+
+export const FONT_CAP_HEIGHT = 10;
+// Side bearing added to every glyph's advance
+const GLYPH_SPACING = 2;
+
+function line(...coords: number[]): FontStroke {
+  const points: FontStroke = [];
+  for (let i = 0; i + 1 < coords.length; i += 2) {
+    points.push([coords[i], coords[i + 1]]);
+  }
+  return points;
+}
+
+/** Elliptical arc from one angle to another, in degrees (counter-clockwise when `to > from`). */
+function arc(cx: number, cy: number, rx: number, ry: number, from: number, to: number): FontStroke {
+  const steps = Math.max(2, Math.ceil(Math.abs(to - from) / 15));
+  const points: FontStroke = [];
+  for (let i = 0; i <= steps; i++) {
+    const angle = ((from + ((to - from) * i) / steps) * Math.PI) / 180;
+    points.push([cx + rx * Math.cos(angle), cy + ry * Math.sin(angle)]);
+  }
+  return points;
+}
+
+function ellipse(cx: number, cy: number, rx: number, ry: number): FontStroke {
+  return arc(cx, cy, rx, ry, 0, 360);
+}
+
+function glyph(width: number, ...strokes: FontStroke[]): Glyph {
+  return { width, strokes };
+}
+
+const GLYPHS: Record<string, Glyph> = {
+  " ": glyph(3),
+  "!": glyph(1, line(0.5, 10, 0.5, 3), line(0.5, 0.2, 0.5, 0.8)),
+  '"': glyph(3, line(0.5, 10, 0.5, 7.5), line(2.5, 10, 2.5, 7.5)),
+  "#": glyph(
+    7,
+    line(2.5, 10, 1.5, 0),
+    line(5.5, 10, 4.5, 0),
+    line(0.5, 6.5, 7, 6.5),
+    line(0, 3.5, 6.5, 3.5)
+  ),
+  $: glyph(
+    6,
+    [...arc(3, 6.9, 3, 2.1, 20, 270), ...arc(3, 2.7, 3, 2.1, 90, -160)],
+    line(3, 10.5, 3, -0.5)
+  ),
+  "%": glyph(8, line(8, 10, 0, 0), ellipse(1.5, 8, 1.5, 2), ellipse(6.5, 2, 1.5, 2)),
+  "&": glyph(7, [
+    ...line(7, 0, 1.64, 7),
+    ...arc(3.2, 8, 1.8, 2, 210, -60),
+    ...line(0.6, 3.1),
+    ...arc(2.8, 2.2, 2.4, 2.2, 155, 330),
+    ...line(6.5, 3.5),
+  ]),
+  "'": glyph(1, line(0.5, 10, 0.5, 7.5)),
+  "(": glyph(2, arc(3, 3.5, 3, 7, 120, 240)),
+  ")": glyph(2, arc(-1, 3.5, 3, 7, 60, -60)),
+  "*": glyph(5, line(2.5, 9, 2.5, 4), line(0.3, 7.8, 4.7, 5.2), line(4.7, 7.8, 0.3, 5.2)),
+  "+": glyph(6, line(3, 6.5, 3, 0.5), line(0, 3.5, 6, 3.5)),
+  ",": glyph(1, line(0.8, 0.6, 0.8, 0, 0, -2)),
+  "-": glyph(5, line(0, 3.5, 5, 3.5)),
+  ".": glyph(1, line(0.5, 0.2, 0.5, 0.8)),
+  "/": glyph(5, line(5, 10, 0, -1)),
+  "0": glyph(6, ellipse(3, 5, 3, 5)),
+  "1": glyph(5, line(1, 8, 3, 10, 3, 0), line(1, 0, 5, 0)),
+  "2": glyph(6, [...arc(3, 7, 3, 3, 160, -35), ...line(0, 0, 6, 0)]),
+  "3": glyph(6, [...arc(3, 7.5, 2.8, 2.5, 150, -90), ...arc(3, 2.5, 3, 2.5, 90, -150)]),
+  "4": glyph(6.5, line(4.5, 0, 4.5, 10, 0, 3, 6.5, 3)),
+  "5": glyph(6, [...line(5.5, 10, 0.5, 10, 0, 5.6), ...arc(3, 3.2, 3, 3.2, 140, -150)]),
+  "6": glyph(6, [...arc(3, 5, 3, 5, 60, 180), ...line(0, 3), ...arc(3, 3, 3, 3, 180, 540)]),
+  "7": glyph(6, line(0, 10, 6, 10, 2, 0)),
+  "8": glyph(6, ellipse(3, 7.5, 2.6, 2.5), ellipse(3, 2.5, 3, 2.5)),
+  "9": glyph(6, [...arc(3, 7, 3, 3, 0, 360), ...line(6, 5), ...arc(3, 5, 3, 5, 0, -115)]),
+  ":": glyph(1, line(0.5, 6.2, 0.5, 6.8), line(0.5, 0.2, 0.5, 0.8)),
+  ";": glyph(1, line(0.5, 6.2, 0.5, 6.8), line(0.8, 0.6, 0.8, 0, 0, -2)),
+  "<": glyph(6, line(6, 7, 0, 3.5, 6, 0)),
+  "=": glyph(6, line(0, 5, 6, 5), line(0, 2, 6, 2)),
+  ">": glyph(6, line(0, 7, 6, 3.5, 0, 0)),
+  "?": glyph(6, [...arc(3, 7.2, 3, 2.8, 160, -60), ...line(3, 3.5, 3, 2.8)], line(3, 0.2, 3, 0.8)),
+  "@": glyph(
+    10,
+    ellipse(5, 4.5, 2, 2.3),
+    [...line(7, 6.8, 7, 3), ...arc(8.25, 3, 1.25, 1.25, 180, 360), ...arc(5, 4.5, 4.5, 5.5, -15, 300)]
+  ),
+  A: glyph(8, line(0, 0, 4, 10, 8, 0), line(1.5, 3.6, 6.5, 3.6)),
+  B: glyph(
+    7,
+    [...line(0, 0, 0, 10, 4.5, 10), ...arc(4.5, 7.5, 2.5, 2.5, 90, -90), ...line(0, 5)],
+    [...line(0, 5, 4.5, 5), ...arc(4.5, 2.5, 2.5, 2.5, 90, -90), ...line(0, 0)]
+  ),
+  C: glyph(7, arc(4, 5, 4, 5, 60, 300)),
+  D: glyph(7, [...line(0, 0, 0, 10, 3, 10), ...arc(3, 5, 4, 5, 90, -90), ...line(0, 0)]),
+  E: glyph(6, line(6, 10, 0, 10, 0, 0, 6, 0), line(0, 5, 4.5, 5)),
+  F: glyph(6, line(6, 10, 0, 10, 0, 0), line(0, 5, 4.5, 5)),
+  G: glyph(8, [...arc(4, 5, 4, 5, 60, 360), ...line(4.5, 5)]),
+  H: glyph(7, line(0, 0, 0, 10), line(7, 0, 7, 10), line(0, 5, 7, 5)),
+  I: glyph(4, line(2, 0, 2, 10), line(0, 10, 4, 10), line(0, 0, 4, 0)),
+  J: glyph(6, [...line(6, 10), ...arc(3, 3, 3, 3, 0, -180)]),
+  K: glyph(7, line(0, 0, 0, 10), line(7, 10, 0, 3.5), line(2.2, 5.5, 7, 0)),
+  L: glyph(6, line(0, 10, 0, 0, 6, 0)),
+  M: glyph(8, line(0, 0, 0, 10, 4, 3, 8, 10, 8, 0)),
+  N: glyph(7, line(0, 0, 0, 10, 7, 0, 7, 10)),
+  O: glyph(8, ellipse(4, 5, 4, 5)),
+  P: glyph(7, [...line(0, 0, 0, 10, 4, 10), ...arc(4, 7.25, 3, 2.75, 90, -90), ...line(0, 4.5)]),
+  Q: glyph(8, ellipse(4, 5, 4, 5), line(4.5, 2.5, 8, -0.5)),
+  R: glyph(
+    7,
+    [...line(0, 0, 0, 10, 4, 10), ...arc(4, 7.25, 3, 2.75, 90, -90), ...line(0, 4.5)],
+    line(3.5, 4.5, 7, 0)
+  ),
+  S: glyph(7, [...arc(3.5, 7.5, 3.5, 2.5, 20, 270), ...arc(3.5, 2.5, 3.5, 2.5, 90, -160)]),
+  T: glyph(8, line(0, 10, 8, 10), line(4, 10, 4, 0)),
+  U: glyph(7, [...line(0, 10), ...arc(3.5, 3, 3.5, 3, 180, 360), ...line(7, 10)]),
+  V: glyph(8, line(0, 10, 4, 0, 8, 10)),
+  W: glyph(10, line(0, 10, 2.5, 0, 5, 7, 7.5, 0, 10, 10)),
+  X: glyph(7, line(0, 10, 7, 0), line(7, 10, 0, 0)),
+  Y: glyph(8, line(0, 10, 4, 5, 8, 10), line(4, 5, 4, 0)),
+  Z: glyph(7, line(0, 10, 7, 10, 0, 0, 7, 0)),
+  "[": glyph(2.5, line(2.5, 10.5, 0, 10.5, 0, -2.5, 2.5, -2.5)),
+  "\\": glyph(5, line(0, 10, 5, -1)),
+  "]": glyph(2.5, line(0, 10.5, 2.5, 10.5, 2.5, -2.5, 0, -2.5)),
+  "^": glyph(6, line(0, 6, 3, 10, 6, 6)),
+  _: glyph(7, line(0, -2, 7, -2)),
+  "`": glyph(2, line(0, 10, 1.5, 8)),
+  a: glyph(6, ellipse(3, 3.5, 3, 3.5), line(6, 7, 6, 0)),
+  b: glyph(6, line(0, 10, 0, 0), ellipse(3, 3.5, 3, 3.5)),
+  c: glyph(6, arc(3.5, 3.5, 3.5, 3.5, 45, 315)),
+  d: glyph(6, line(6, 10, 6, 0), ellipse(3, 3.5, 3, 3.5)),
+  e: glyph(6.5, [...line(0, 3.5), ...arc(3.25, 3.5, 3.25, 3.5, 0, 315)]),
+  f: glyph(4, [...arc(3.5, 8.5, 1.5, 1.5, 60, 180), ...line(2, 0)], line(0, 7, 4, 7)),
+  g: glyph(
+    6,
+    ellipse(3, 3.5, 3, 3.5),
+    [...line(6, 7, 6, -1), ...arc(3, -1, 3, 2, 0, -160)]
+  ),
+  h: glyph(6, line(0, 10, 0, 0), [...line(0, 4), ...arc(3, 4, 3, 3, 180, 0), ...line(6, 0)]),
+  i: glyph(1, line(0.5, 0, 0.5, 7), line(0.5, 9.2, 0.5, 9.8)),
+  j: glyph(3, [...line(2.5, 7, 2.5, -1.5), ...arc(1, -1.5, 1.5, 1.5, 0, -150)], line(2.5, 9.2, 2.5, 9.8)),
+  k: glyph(6, line(0, 10, 0, 0), line(5.5, 7, 0, 2.5), line(2, 4, 6, 0)),
+  l: glyph(1, line(0.5, 10, 0.5, 0)),
+  m: glyph(
+    10,
+    line(0, 7, 0, 0),
+    [...line(0, 4.5), ...arc(2.5, 4.5, 2.5, 2.5, 180, 0), ...line(5, 0)],
+    [...line(5, 4.5), ...arc(7.5, 4.5, 2.5, 2.5, 180, 0), ...line(10, 0)]
+  ),
+  n: glyph(6, line(0, 7, 0, 0), [...line(0, 4), ...arc(3, 4, 3, 3, 180, 0), ...line(6, 0)]),
+  o: glyph(6.5, ellipse(3.25, 3.5, 3.25, 3.5)),
+  p: glyph(6, line(0, 7, 0, -3), ellipse(3, 3.5, 3, 3.5)),
+  q: glyph(6, line(6, 7, 6, -3), ellipse(3, 3.5, 3, 3.5)),
+  r: glyph(4, line(0, 7, 0, 0), [...line(0, 4), ...arc(3.5, 4, 3.5, 3, 180, 80)]),
+  s: glyph(5, [...arc(2.5, 5.25, 2.5, 1.75, 20, 270), ...arc(2.5, 1.75, 2.5, 1.75, 90, -160)]),
+  t: glyph(4, [...line(1.5, 9.5, 1.5, 1.5), ...arc(3, 1.5, 1.5, 1.5, 180, 300)], line(0, 7, 4, 7)),
+  u: glyph(6, [...line(0, 7, 0, 3), ...arc(3, 3, 3, 3, 180, 360)], line(6, 7, 6, 0)),
+  v: glyph(6, line(0, 7, 3, 0, 6, 7)),
+  w: glyph(9, line(0, 7, 2.25, 0, 4.5, 5, 6.75, 0, 9, 7)),
+  x: glyph(6, line(0, 7, 6, 0), line(6, 7, 0, 0)),
+  y: glyph(6, line(0, 7, 3.2, 0), line(6, 7, 2, -3)),
+  z: glyph(6, line(0, 7, 6, 7, 0, 0, 6, 0)),
+  "{": glyph(
+    4,
+    line(4, 10.5, 3, 10.5, 2.3, 9.8, 2.3, 5, 1.8, 4.2, 0.8, 4, 1.8, 3.8, 2.3, 3, 2.3, -1.8, 3, -2.5, 4, -2.5)
+  ),
+  "|": glyph(1, line(0.5, 10.5, 0.5, -2.5)),
+  "}": glyph(
+    4,
+    line(0, 10.5, 1, 10.5, 1.7, 9.8, 1.7, 5, 2.2, 4.2, 3.2, 4, 2.2, 3.8, 1.7, 3, 1.7, -1.8, 1, -2.5, 0, -2.5)
+  ),
+  "~": glyph(7, line(0, 3, 1, 4.3, 2.2, 4.5, 4.8, 3, 6, 3.2, 7, 4.5)),
+  "·": glyph(1, line(0.5, 3.2, 0.5, 3.8)),
+};
+
+// Drawn for characters the font does not cover
+const MISSING_GLYPH = glyph(5, line(0, 0, 5, 0, 5, 10, 0, 10, 0, 0));
+
+function glyphFor(char: string): Glyph {
+  const known = GLYPHS[char];
+  if (known) {
+    return known;
+  }
+  // Accented letters fall back to their base letter
+  const base = char.normalize("NFD").replace(/[\u0300-\u036f]/g, "");
+  return GLYPHS[base] || MISSING_GLYPH;
+}
+
+/** Advance width of a line of text, in font units. */
+export function measureText(text: string): number {
+  let width = 0;
+  for (const char of text.replace(/\t/g, "    ")) {
+    width += glyphFor(char).width + GLYPH_SPACING;
+  }
+  return width;
+}
+
+/**
+ * The strokes of a line of text in font units, starting at x = 0 on the
+ * baseline.
+ */
+export function textStrokes(text: string): FontStroke[] {
+  const strokes: FontStroke[] = [];
+  let x = GLYPH_SPACING / 2;
+  for (const char of text.replace(/\t/g, "    ")) {
+    const { width, strokes: glyphStrokes } = glyphFor(char);
+    for (const stroke of glyphStrokes) {
+      strokes.push(stroke.map(([gx, gy]) => [x + gx, gy] as [number, number]));
+    }
+    x += width + GLYPH_SPACING;
+  }
+  return strokes;
+}
@@ -0,0 +1,42 @@
+import { spawn } from "child_process";
+
+/**
+ * Clipboard commands to try, in order, per platform. Linux covers Wayland
+ * and both common X11 tools; none of them ship everywhere.
+ */
+const CLIPBOARD_COMMANDS: Readonly<Record<string, ReadonlyArray<[string, string[]]>>> = {
+  darwin: [["pbcopy", []]],
+  win32: [["clip", []]],
+  linux: [
+    ["wl-copy", []],
+    ["xclip", ["-selection", "clipboard"]],
+    ["xsel", ["--clipboard", "--input"]],
+  ],
+};
+
+/**
+ * Copies text to the system clipboard through the platform's clipboard
+ * command, for the CLI where VS Code's clipboard API is not available.
+ * Resolves to false when no clipboard backend is installed or usable (for
+ * example over SSH without a display) instead of throwing.
+ */
+export async function copyToSystemClipboard(text: string): Promise<boolean> {
+  const commands = CLIPBOARD_COMMANDS[process.platform] || CLIPBOARD_COMMANDS.linux;
+  for (const [command, args] of commands) {
+    if (await pipeTo(command, args, text)) {
+      return true;
+    }
+  }
+  return false;
+}
+
+function pipeTo(command: string, args: string[], input: string): Promise<boolean> {
+  return new Promise((resolve) => {
+    const child = spawn(command, args, { stdio: ["pipe", "ignore", "ignore"] });
+    // A missing command reports through "error"; a failing one through its exit code
+    child.on("error", () => resolve(false));
+    child.on("close", (code) => resolve(code === 0));
+    child.stdin.on("error", () => resolve(false));
+    child.stdin.end(input);
+  });
+}
@@ -147,6 +147,16 @@ export class SubtleThemeManager {
         emphasis: "low",
       },
     ],
+    // Suspension points: rounded, with a heavier border than plain steps
+    [
+      NodeType.AWAIT,
+      {
+        shape: "stadium",
+        borderStyle: "solid",
+        fontWeight: "normal",
+        emphasis: "medium",
+      },
+    ],
   ]);
 
   // A registry to hold all available themes
@@ -874,6 +884,26 @@ export class SubtleThemeManager {
     return vsCodeTheme === "dark" ? selectedTheme.dark : selectedTheme.light;
   }
 
+  /**
+   * Builds theme styles that give every node kind the same palette, used
+   * when an export supplies its own color map.
+   */
+  public static getUniformThemeStyles(palette: ThemeColorPalette): ThemeStyles {
+    return {
+      entry: palette,
+      exit: palette,
+      process: palette,
+      decision: palette,
+      loop: palette,
+      exception: palette,
+      assignment: palette,
+      functionCall: palette,
+      asyncOperation: palette,
+      breakContinue: palette,
+      returnNode: palette,
+    };
+  }
+
   /**
    * Returns a list of available themes to populate a settings dropdown.
    * @returns An array of objects with theme keys and their user-friendly names.
@@ -0,0 +1,333 @@
+import * as fs from "fs";
+import * as path from "path";
+import { DiagramFormat, isDiagramFormat } from "../DiagramExporter";
+import { isDiagramColorTheme } from "./DiagramColors";
+import { isLabelMode, LabelMode } from "./NodeLabels";
+
+/** Read from the working directory when no `--config` is given. */
+export const CONFIG_FILE_NAME = "visor.toml";
+
+/**
+ * Project-wide defaults for the command line, shared by checking a
+ * `visor.toml` into the repository. Keys are named after the flags they
+ * stand in for; a flag given on the command line wins over its key.
+ *
+ *   format = "mermaid"
+ *   theme = "dark"
+ *   labels = "summary"
+ *   include = ["src/**"]
+ *   exclude = ["vendor/**"]
+ *   line-numbers = true
+ *   compact = true
+ */
+export interface VisorConfig {
+  format?: DiagramFormat;
+  theme?: string;
+  labels?: LabelMode;
+  include?: string[];
+  exclude?: string[];
+  lineNumbers?: boolean;
+  compact?: boolean;
+}
+
+export class ConfigError extends Error {
+  constructor(file: string, line: number | undefined, message: string) {
+    super(line === undefined ? `${file}: ${message}` : `${file}:${line}: ${message}`);
+    this.name = "ConfigError";
+  }
+}
+
+type TomlValue = string | number | boolean | TomlValue[];
+
+// One entry per key: how to check the value and where it goes
+const CONFIG_KEYS: Record<string, (value: TomlValue, config: VisorConfig) => string | undefined> = {
+  format: (value, config) => {
+    if (!isDiagramFormat(value)) {
+      return `unknown format ${JSON.stringify(value)}`;
+    }
+    config.format = value;
+    return undefined;
+  },
+  theme: (value, config) => {
+    if (!isDiagramColorTheme(value)) {
+      return `unknown theme ${JSON.stringify(value)}`;
+    }
+    config.theme = value;
+    return undefined;
+  },
+  labels: (value, config) => {
+    if (typeof value !== "string" || !isLabelMode(value)) {
+      return `unknown label mode ${JSON.stringify(value)}`;
+    }
+    config.labels = value;
+    return undefined;
+  },
+  include: (value, config) => {
+    const globs = toGlobs(value);
+    if (!globs) {
+      return "include must be a glob or an array of globs";
+    }
+    config.include = globs;
+    return undefined;
+  },
+  exclude: (value, config) => {
+    const globs = toGlobs(value);
+    if (!globs) {
+      return "exclude must be a glob or an array of globs";
+    }
+    config.exclude = globs;
+    return undefined;
+  },
+  "line-numbers": (value, config) => {
+    if (typeof value !== "boolean") {
+      return "line-numbers must be true or false";
+    }
+    config.lineNumbers = value;
+    return undefined;
+  },
+  compact: (value, config) => {
+    if (typeof value !== "boolean") {
+      return "compact must be true or false";
+    }
+    config.compact = value;
+    return undefined;
+  },
+};
+
+function toGlobs(value: TomlValue): string[] | undefined {
+  const globs = Array.isArray(value) ? value : [value];
+  return globs.every((glob): glob is string => typeof glob === "string") ? globs : undefined;
+}
+
+/**
+ * Reads the config file: `explicitPath` (which must exist), or else
+ * `visor.toml` in `cwd` when there is one. Returns an empty config when
+ * neither applies.
+ */
+export function loadVisorConfig(cwd: string, explicitPath?: string): VisorConfig {
+  const file = explicitPath ?? path.join(cwd, CONFIG_FILE_NAME);
+  let text: string;
+  try {
+    text = fs.readFileSync(file, "utf8");
+  } catch (error) {
+    if (explicitPath === undefined && (error as NodeJS.ErrnoException).code === "ENOENT") {
+      return {};
+    }
+    const message = error instanceof Error ? error.message : String(error);
+    throw new ConfigError(file, undefined, `cannot read: ${message}`);
+  }
+  return parseVisorConfig(text, file);
+}
+
+/**
+ * Parses the TOML subset a config needs: `key = value` pairs with strings,
+ * booleans, integers and arrays (which may span lines), and `#` comments.
+ * Tables are not supported; unknown keys are errors, so a typo does not
+ * silently do nothing.
+ */
+export function parseVisorConfig(text: string, file: string = CONFIG_FILE_NAME): VisorConfig {
+  const config: VisorConfig = {};
+  const seen = new Set<string>();
+  const lines = text.split(/\r?\n/);
+
+  for (let index = 0; index < lines.length; index++) {
+    const lineNumber = index + 1;
+    let line = stripComment(lines[index]).trim();
+    if (line === "") {
+      continue;
+    }
+    if (line.startsWith("[")) {
+      throw new ConfigError(file, lineNumber, "tables are not supported; put the keys at the top level");
+    }
+    const equals = line.indexOf("=");
+    if (equals === -1) {
+      throw new ConfigError(file, lineNumber, "expected key = value");
+    }
+    const key = line.slice(0, equals).trim().replace(/^"(.*)"$/, "$1");
+    const apply = Object.prototype.hasOwnProperty.call(CONFIG_KEYS, key) ? CONFIG_KEYS[key] : undefined;
+    if (!apply) {
+      throw new ConfigError(file, lineNumber, `unknown key ${JSON.stringify(key)} (expected one of ${Object.keys(CONFIG_KEYS).join(", ")})`);
+    }
+    if (seen.has(key)) {
+      throw new ConfigError(file, lineNumber, `${key} is set twice`);
+    }
+    seen.add(key);
+
+    // An array continues until its brackets balance
+    let source = line.slice(equals + 1).trim();
+    while (source.startsWith("[") && !isBalanced(source) && index + 1 < lines.length) {
+      line = stripComment(lines[++index]).trim();
+      source += " " + line;
+    }
+
+    const reader = new ValueReader(source);
+    let value: TomlValue;
+    try {
+      value = reader.readValue();
+      reader.expectEnd();
+    } catch (error) {
+      throw new ConfigError(file, lineNumber, error instanceof Error ? error.message : String(error));
+    }
+    const problem = apply(value, config);
+    if (problem) {
+      throw new ConfigError(file, lineNumber, problem);
+    }
+  }
+  return config;
+}
+
+/** Drops a `#` comment, leaving `#` inside strings alone. */
+function stripComment(line: string): string {
+  let quote: string | undefined;
+  for (let index = 0; index < line.length; index++) {
+    const char = line[index];
+    if (quote) {
+      if (char === "\\" && quote === '"') {
+        index++;
+      } else if (char === quote) {
+        quote = undefined;
+      }
+    } else if (char === '"' || char === "'") {
+      quote = char;
+    } else if (char === "#") {
+      return line.slice(0, index);
+    }
+  }
+  return line;
+}
+
+function isBalanced(source: string): boolean {
+  let depth = 0;
+  let quote: string | undefined;
+  for (let index = 0; index < source.length; index++) {
+    const char = source[index];
+    if (quote) {
+      if (char === "\\" && quote === '"') {
+        index++;
+      } else if (char === quote) {
+        quote = undefined;
+      }
+    } else if (char === '"' || char === "'") {
+      quote = char;
+    } else if (char === "[") {
+      depth++;
+    } else if (char === "]") {
+      depth--;
+    }
+  }
+  return depth <= 0;
+}
+
+const ESCAPES: Record<string, string> = {
+  b: "\b",
+  t: "\t",
+  n: "\n",
+  f: "\f",
+  r: "\r",
+  '"': '"',
+  "\\": "\\",
+};
+
+class ValueReader {
+  private index = 0;
+
+  constructor(private readonly source: string) {}
+
+  readValue(): TomlValue {
+    this.skipSpace();
+    const char = this.source[this.index];
+    if (char === '"') {
+      return this.readBasicString();
+    }
+    if (char === "'") {
+      return this.readLiteralString();
+    }
+    if (char === "[") {
+      return this.readArray();
+    }
+    const match = /^(true|false|[+-]?\d[\d_]*)/.exec(this.source.slice(this.index));
+    if (!match) {
+      throw new Error(`unexpected value ${JSON.stringify(this.source.slice(this.index))}`);
+    }
+    this.index += match[0].length;
+    if (match[0] === "true" || match[0] === "false") {
+      return match[0] === "true";
+    }
+    return Number(match[0].replace(/_/g, ""));
+  }
+
+  expectEnd(): void {
+    this.skipSpace();
+    if (this.index < this.source.length) {
+      throw new Error(`unexpected ${JSON.stringify(this.source.slice(this.index))} after the value`);
+    }
+  }
+
+  private readBasicString(): string {
+    let text = "";
+    for (this.index++; this.index < this.source.length; this.index++) {
+      const char = this.source[this.index];
+      if (char === '"') {
+        this.index++;
+        return text;
+      }
+      if (char === "\\") {
+        const escaped = this.source[++this.index];
+        if (escaped === "u" || escaped === "U") {
+          const length = escaped === "u" ? 4 : 8;
+          const hex = this.source.slice(this.index + 1, this.index + 1 + length);
+          if (!/^[0-9a-fA-F]+$/.test(hex) || hex.length !== length) {
+            throw new Error(`invalid escape \\${escaped}${hex}`);
+          }
+          text += String.fromCodePoint(parseInt(hex, 16));
+          this.index += length;
+        } else if (escaped !== undefined && ESCAPES[escaped] !== undefined) {
+          text += ESCAPES[escaped];
+        } else {
+          throw new Error(`invalid escape \\${escaped ?? ""}`);
+        }
+      } else {
+        text += char;
+      }
+    }
+    throw new Error("unterminated string");
+  }
+
+  private readLiteralString(): string {
+    const end = this.source.indexOf("'", this.index + 1);
+    if (end === -1) {
+      throw new Error("unterminated string");
+    }
+    const text = this.source.slice(this.index + 1, end);
+    this.index = end + 1;
+    return text;
+  }
+
+  private readArray(): TomlValue[] {
+    const items: TomlValue[] = [];
+    this.index++;
+    for (;;) {
+      this.skipSpace();
+      if (this.source[this.index] === "]") {
+        this.index++;
+        return items;
+      }
+      if (this.index >= this.source.length) {
+        throw new Error("unterminated array");
+      }
+      items.push(this.readValue());
+      this.skipSpace();
+      if (this.source[this.index] === ",") {
+        this.index++;
+      } else if (this.source[this.index] !== "]") {
+        throw new Error("expected , or ] in array");
+      }
+    }
+  }
+
+  private skipSpace(): void {
+    while (this.index < this.source.length && /\s/.test(this.source[this.index])) {
+      this.index++;
+    }
+  }
+}
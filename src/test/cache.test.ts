@@ -0,0 +1,46 @@
+import * as assert from "assert";
+import { analyzeSource, clearCache } from "../api";
+import { DIAGRAM_FORMATS } from "../logic/DiagramExporter";
+import { useVisor } from "./setup";
+
+const FUNCTION = `fn clamp(value: i32, max: i32) -> i32 {
+    if value > max {
+        return max;
+    }
+    let doubled = value * 2;
+    doubled.min(max).unwrap_or(0)
+}
+`;
+
+const ORIGINAL = `/// Caps the value.\n${FUNCTION}`;
+
+// Each keeps the function's text, so the second analysis is a cache hit
+const EDITS: [string, string][] = [
+  ["a doc-comment edit", `/// Limits the value to at most max, doubling it otherwise.\n${FUNCTION}`],
+  ["a line shift", `\n\n// Helpers\n\n${ORIGINAL}`],
+  ["a function added above", `fn helper() {}\n\nmod inner {}\n\n${ORIGINAL}`],
+];
+
+suite("Flowchart cache", () => {
+  useVisor();
+
+  for (const [name, edited] of EDITS) {
+    test(`a cache hit after ${name} matches a cold run`, async () => {
+      for (const { id: format } of DIAGRAM_FORMATS) {
+        clearCache();
+        await analyzeSource(ORIGINAL, "rust", { functionName: "clamp" });
+        const cached = (await analyzeSource(edited, "rust", { functionName: "clamp" })).to(format);
+        clearCache();
+        const cold = (await analyzeSource(edited, "rust", { functionName: "clamp" })).to(format);
+        assert.strictEqual(cached, cold, `${format} output differs`);
+      }
+    });
+  }
+
+  test("the title follows the edited doc comment", async () => {
+    clearCache();
+    await analyzeSource(ORIGINAL, "rust");
+    const graph = JSON.parse((await analyzeSource(EDITS[0][1], "rust")).toJson());
+    assert.strictEqual(graph.title, "Limits the value to at most max, doubling it otherwise.");
+  });
+});
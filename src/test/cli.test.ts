@@ -0,0 +1,93 @@
+import * as assert from "assert";
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { analyzeSource, DiagramFormat, summarizeFunctions } from "../api";
+import { main, parseArgs } from "../cli";
+import { useVisor, WASM_DIR } from "./setup";
+
+const SOURCE = `
+fn sign(x: i32) -> i32 {
+    if x < 0 {
+        return -1;
+    }
+    1
+}
+`;
+
+/** Runs the CLI and returns its exit code and everything it wrote to stdout. */
+async function runCli(argv: string[]): Promise<{ code: number; stdout: string }> {
+  let stdout = "";
+  const write = process.stdout.write;
+  process.stdout.write = ((chunk: string | Uint8Array) => {
+    stdout += chunk.toString();
+    return true;
+  }) as typeof process.stdout.write;
+  try {
+    const code = await main([...argv, "--wasm-dir", WASM_DIR]);
+    return { code, stdout };
+  } finally {
+    process.stdout.write = write;
+  }
+}
+
+suite("Command line", () => {
+  let dir: string;
+  let file: string;
+
+  useVisor();
+
+  suiteSetup(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), "visor-cli-"));
+    file = path.join(dir, "sign.rs");
+    fs.writeFileSync(file, SOURCE);
+  });
+
+  suiteTeardown(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  test("--file prints nothing but the diagram", async () => {
+    const formats: DiagramFormat[] = ["mermaid", "dot", "json"];
+    for (const format of formats) {
+      const { code, stdout } = await runCli(["--file", file, "--format", format]);
+      const diagram = (await analyzeSource(SOURCE, "rust")).to(format);
+      assert.strictEqual(code, 0);
+      assert.strictEqual(stdout, diagram.endsWith("\n") ? diagram : `${diagram}\n`, format);
+    }
+  });
+
+  test("--summary json prints nothing but the summary", async () => {
+    const { code, stdout } = await runCli(["--file", file, "--summary", "json"]);
+    const summary = await summarizeFunctions(SOURCE, "rust", { file });
+    assert.strictEqual(code, 0);
+    assert.strictEqual(stdout, `${JSON.stringify(summary, null, 2)}\n`);
+
+    const [sign] = JSON.parse(stdout).functions;
+    assert.strictEqual(sign.name, "sign");
+    assert.strictEqual(sign.file, file);
+    assert.deepStrictEqual(sign.lines, { start: 2, end: 7 });
+    assert.strictEqual(sign.returns, 2);
+    assert.strictEqual(sign.loops, 0);
+    assert.strictEqual(sign.panics, 0);
+  });
+
+  test("--include splits on commas outside braces only", () => {
+    const options = parseArgs(["--dir", "src", "--out", "out", "--include", "src/{a,b}/*.rs, *.py", "--include", "lib/*"]);
+    assert.deepStrictEqual(options?.include, ["src/{a,b}/*.rs", "*.py", "lib/*"]);
+  });
+
+  test("flags override visor.toml, in both directions", () => {
+    const config = { format: "dot" as const, compact: true, lineNumbers: true, include: ["src/**"] };
+    const options = parseArgs(["--file", "x.rs", "--no-compact", "--format", "mermaid"], config);
+    assert.strictEqual(options?.format, "mermaid");
+    assert.strictEqual(options?.compact, false);
+    assert.strictEqual(options?.lineNumbers, true);
+
+    const fromConfig = parseArgs(["--dir", "src", "--out", "out"], config);
+    assert.strictEqual(fromConfig?.format, "dot");
+    assert.deepStrictEqual(fromConfig?.include, ["src/**"]);
+    assert.deepStrictEqual(parseArgs(["--dir", "src", "--out", "out", "--include", "lib/*"], config)?.include, ["lib/*"]);
+    assert.strictEqual(parseArgs(["--file", "x.rs", "--no-line-numbers"], config)?.lineNumbers, false);
+  });
+});
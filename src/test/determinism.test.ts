@@ -0,0 +1,48 @@
+import * as assert from "assert";
+import { analyzeSource, clearCache } from "../api";
+import { DIAGRAM_FORMATS } from "../logic/DiagramExporter";
+import { useVisor } from "./setup";
+
+// Branches, a loop, early returns and merge points: everything whose
+// node and edge order depends on how the parser walks the function
+const SOURCE = `
+/// Sums the positive entries, stopping at the first negative one.
+fn sum_until_negative(values: &[i32]) -> Option<i32> {
+    let mut total = 0;
+    for value in values {
+        if *value < 0 {
+            return None;
+        } else if *value == 0 {
+            continue;
+        }
+        total += value;
+    }
+    match total {
+        0 => None,
+        n => Some(n),
+    }
+}
+`;
+
+suite("Deterministic output", () => {
+  useVisor();
+
+  test("the same input gives byte-equal diagrams in every format", async () => {
+    for (const { id: format } of DIAGRAM_FORMATS) {
+      clearCache();
+      const first = (await analyzeSource(SOURCE, "rust")).to(format);
+      clearCache();
+      const second = (await analyzeSource(SOURCE, "rust")).to(format);
+      assert.strictEqual(second, first, `${format} output differs between runs`);
+    }
+  });
+
+  test("node ids follow source order", async () => {
+    const graph = JSON.parse((await analyzeSource(SOURCE, "rust")).to("json"));
+    const starts: number[] = graph.nodes
+      .filter((node: { kind: string; span: { start: number } | null }) =>
+        node.span !== null && node.kind !== "entry" && node.kind !== "exit")
+      .map((node: { span: { start: number } }) => node.span.start);
+    assert.deepStrictEqual(starts, [...starts].sort((a, b) => a - b));
+  });
+});
@@ -0,0 +1,48 @@
+import * as assert from "assert";
+import { GlobMatcher } from "../logic/utils/GlobMatcher";
+
+function check(glob: string, matching: string[], other: string[]): void {
+  const matcher = new GlobMatcher([glob]);
+  for (const file of matching) {
+    assert.ok(matcher.matches(file), `${glob} should match ${file}`);
+  }
+  for (const file of other) {
+    assert.ok(!matcher.matches(file), `${glob} should not match ${file}`);
+  }
+}
+
+suite("Glob matching", () => {
+  test("a glob without a slash matches the file name at any depth", () => {
+    check("*.rs", ["lib.rs", "src/lib.rs", "a/b/c/main.rs"], ["lib.rs.bak", "src/lib.py"]);
+  });
+
+  test("* and ? stay within one directory", () => {
+    check("src/*", ["src/lib.rs"], ["src/net/http.rs", "lib/src/x.rs"]);
+    check("a?.py", ["ab.py", "pkg/a1.py"], ["abc.py", "a.py"]);
+  });
+
+  test("**/ spans zero or more directories", () => {
+    check("src/**/*.rs", ["src/lib.rs", "src/net/http/client.rs"], ["lib/src/lib.rs", "src/lib.py"]);
+    check("vendor/**", ["vendor/a.rs", "vendor/x/y.rs"], ["src/vendor.rs"]);
+  });
+
+  test("{a,b} matches either alternative", () => {
+    check("src/{net,io}/*.rs", ["src/net/http.rs", "src/io/file.rs"], ["src/fs/file.rs", "src/{net,io}/x.rs"]);
+    check("*.{ts,tsx}", ["app.ts", "view/App.tsx"], ["app.js"]);
+  });
+
+  test("other regex characters are literal", () => {
+    check("a+b.(rs)", ["a+b.(rs)"], ["aab.rs", "a+bx(rs)"]);
+  });
+
+  test("backslashes and a leading ./ are normalized on both sides", () => {
+    check("./src/*.rs", ["src/lib.rs", "./src/lib.rs", "src\\lib.rs"], []);
+    check("src\\*.rs", ["src/lib.rs"], []);
+  });
+
+  test("blank globs are ignored", () => {
+    const matcher = new GlobMatcher(["", "  "]);
+    assert.strictEqual(matcher.isEmpty, true);
+    assert.strictEqual(matcher.matches("src/lib.rs"), false);
+  });
+});
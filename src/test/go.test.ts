@@ -0,0 +1,55 @@
+import * as assert from "assert";
+import { analyzeSource, JsonGraph } from "../api";
+import { useVisor } from "./setup";
+
+async function graphOf(source: string): Promise<JsonGraph> {
+  return JSON.parse((await analyzeSource(source, "go")).toJson());
+}
+
+suite("Go flowcharts", () => {
+  useVisor();
+
+  test("only the returns after a defer run the deferred call", async () => {
+    const graph = await graphOf(`
+func read(p string) error {
+	f, err := os.Open(p)
+	if err != nil {
+		return err
+	}
+	defer f.Close()
+	return process(f)
+}
+`);
+    const byId = new Map(graph.nodes.map((node) => [node.id, node]));
+    const next = (label: string): string[] => {
+      const node = graph.nodes.find((candidate) => candidate.label === label);
+      assert.ok(node, `no node for ${label}`);
+      return graph.edges.filter((edge) => edge.from === node.id).map((edge) => byId.get(edge.to)!.label);
+    };
+    assert.deepStrictEqual(next("return err"), ["End"]);
+    assert.deepStrictEqual(next("return process(f)"), ["deferred: f.Close()"]);
+    assert.deepStrictEqual(next("deferred: f.Close()"), ["End"]);
+
+    const run = graph.nodes.find((node) => node.label === "deferred: f.Close()")!;
+    assert.strictEqual(run.span, null);
+  });
+
+  test("a defer inside an if does not run on the path that skips it", async () => {
+    const graph = await graphOf(`
+func work(verbose bool) {
+	if verbose {
+		defer log.Println("done")
+		return
+	}
+	step()
+}
+`);
+    const run = graph.nodes.filter((node) => node.label.startsWith("deferred:"));
+    assert.strictEqual(run.length, 1);
+    const into = graph.edges.filter((edge) => edge.to === run[0].id).map((edge) => edge.from);
+    assert.deepStrictEqual(into.map((id) => graph.nodes.find((node) => node.id === id)?.label), ["return"]);
+    const step = graph.nodes.find((node) => node.label === "step()")!;
+    const end = graph.nodes.find((node) => node.label === "End")!;
+    assert.ok(graph.edges.some((edge) => edge.from === step.id && edge.to === end.id));
+  });
+});
@@ -0,0 +1,96 @@
+import * as assert from "assert";
+import { analyzeFunction, analyzeSource, JsonGraph } from "../api";
+import { useVisor } from "./setup";
+
+async function graphOf(source: string): Promise<JsonGraph> {
+  return JSON.parse((await analyzeSource(source, "rust")).toJson());
+}
+
+suite("Rust flowcharts", () => {
+  useVisor();
+
+  test("a guarded `_` arm is a condition, not the default", async () => {
+    const graph = await graphOf(`
+fn classify(x: i32) -> i32 {
+    match x {
+        _ if x > 0 => positive(),
+        _ => other(),
+    }
+    0
+}
+`);
+    const into = (text: string): (string | null)[] => {
+      const node = graph.nodes.find((candidate) => candidate.label.includes(text));
+      assert.ok(node, `no node for ${text}`);
+      return graph.edges.filter((edge) => edge.to === node.id).map((edge) => edge.label);
+    };
+    assert.deepStrictEqual(into("positive()"), ["if x > 0"]);
+    // The arms after a guarded `_` can still match
+    assert.deepStrictEqual(into("other()"), ["default"]);
+  });
+
+  test("closures in an iterator chain get a flow of their own", async () => {
+    const source = `
+fn higher_order_function(numbers: Vec<i32>) -> Vec<i32> {
+    let doubled: Vec<i32> = numbers.iter().filter(|&&x| x > 2).map(|&x| x * 2).collect();
+    doubled
+}
+`;
+    const analysis = await analyzeSource(source, "rust");
+    const graph: JsonGraph = JSON.parse(analysis.toJson());
+    const byId = new Map(graph.nodes.map((node) => [node.id, node]));
+    const from = (id: string) => graph.edges.filter((edge) => edge.from === id);
+    const into = (id: string) => graph.edges.filter((edge) => edge.to === id);
+
+    for (const [method, params, value] of [
+      [".filter", "|&&x|", "x > 2"],
+      [".map", "|&x|", "x * 2"],
+    ]) {
+      const header = graph.nodes.find((node) => node.label === params);
+      assert.ok(header, `no header for ${params}`);
+      const [call] = into(header.id);
+      assert.ok(byId.get(call.from)?.label.startsWith(method), `${params} does not branch off ${method}`);
+      assert.strictEqual(call.label, "closure");
+      // The cursor on the parameters selects the header
+      const entry = analysis.ir.locationMap.find((candidate) => candidate.nodeId === header.id);
+      assert.strictEqual(entry && source.slice(entry.start, entry.end), params);
+
+      const [toBody] = from(header.id);
+      const body = byId.get(toBody.to);
+      assert.ok(body && body.kind === "return", `${params} has no body`);
+      const [toEnd] = from(body.id);
+      assert.strictEqual(toEnd.label, value);
+      assert.strictEqual(byId.get(toEnd.to)?.label, "End Closure");
+    }
+    assert.strictEqual(graph.nodes.filter((node) => node.label === "End Closure").length, 2);
+  });
+
+  test("each definition of a name gets one graph", async () => {
+    const source = `
+mod a {
+    fn walk(n: u32) -> u32 {
+        if n < 2 {
+            return n;
+        }
+        walk(n - 1) + walk(n - 2)
+    }
+}
+
+struct Tree;
+
+impl Tree {
+    fn walk(&self) {}
+}
+
+fn main() {
+    let walk_twice = |n: u32| n * 2;
+}
+`;
+    const starts = (await analyzeFunction(source, "rust", "walk")).map((graph) => graph.ir.functionRange?.start);
+    // The recursive calls are not definitions of their own
+    assert.deepStrictEqual(starts, [source.indexOf("fn walk(n"), source.indexOf("fn walk(&self")]);
+
+    const [closure] = await analyzeFunction(source, "rust", "walk_twice");
+    assert.strictEqual(closure.ir.title, "Flowchart for closure: walk_twice");
+  });
+});
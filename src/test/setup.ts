@@ -0,0 +1,12 @@
+import * as path from "path";
+import { initVisor } from "../api";
+
+// The grammars are copied into dist by the extension build
+export const WASM_DIR = path.resolve(__dirname, "../../dist");
+
+/** Loads the grammars once before the tests of the calling suite. */
+export function useVisor(): void {
+  suiteSetup(async () => {
+    await initVisor(WASM_DIR);
+  });
+}
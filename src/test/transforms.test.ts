@@ -0,0 +1,114 @@
+import * as assert from "assert";
+import { FlowchartIR, FlowchartNode, NodeType } from "../ir/ir";
+import { applyViewOptions, compactLinearSequences } from "../logic/utils/FlowchartTransforms";
+
+/**
+ * x = 1; f(); y = 2; if y > 0 { z = 3; return z }, with the first three
+ * statements a straight run and the branch body one level deeper.
+ */
+function sampleIR(): FlowchartIR {
+  const node = (id: string, label: string, nodeType: NodeType, line?: number, depth?: number): FlowchartNode => ({
+    id,
+    label,
+    nodeType,
+    line,
+    depth,
+    location: line === undefined ? undefined : { start: line * 10, end: line * 10 + 5 },
+    shape: nodeType === NodeType.DECISION ? "diamond" : "rect",
+  });
+  return {
+    nodes: [
+      node("start", "Start", NodeType.ENTRY),
+      node("a", "x = 1", NodeType.ASSIGNMENT, 2, 1),
+      node("b", "f()", NodeType.FUNCTION_CALL, 3, 1),
+      node("c", "y = 2", NodeType.ASSIGNMENT, 4, 1),
+      node("cond", "if y > 0", NodeType.DECISION, 5, 1),
+      node("d", "z = 3", NodeType.ASSIGNMENT, 6, 2),
+      node("r", "return z", NodeType.RETURN, 7, 2),
+      node("end", "End", NodeType.EXIT),
+    ],
+    edges: [
+      { from: "start", to: "a" },
+      { from: "a", to: "b" },
+      { from: "b", to: "c" },
+      { from: "c", to: "cond" },
+      { from: "cond", to: "d", label: "true", cold: true },
+      { from: "cond", to: "end", label: "false" },
+      { from: "d", to: "r" },
+      { from: "r", to: "end" },
+    ],
+    entryNodeId: "start",
+    exitNodeId: "end",
+    locationMap: [{ start: 30, end: 35, nodeId: "b" }],
+  };
+}
+
+const ids = (ir: FlowchartIR): string[] => ir.nodes.map((node) => node.id);
+const edgesOf = (ir: FlowchartIR): string[] =>
+  ir.edges.map((edge) => `${edge.from}->${edge.to}${edge.label ? `:${edge.label}` : ""}`);
+
+suite("Flowchart view options", () => {
+  test("compaction merges a straight run into its first node", () => {
+    const ir = sampleIR();
+    compactLinearSequences(ir);
+    assert.deepStrictEqual(ids(ir), ["start", "a", "cond", "d", "r", "end"]);
+    assert.strictEqual(ir.nodes[1].label, "x = 1\nf()\ny = 2");
+    assert.deepStrictEqual(ir.nodes[1].location, { start: 20, end: 45 });
+    assert.deepStrictEqual(edgesOf(ir).sort(), [
+      "a->cond",
+      "cond->d:true",
+      "cond->end:false",
+      "d->r",
+      "r->end",
+      "start->a",
+    ]);
+    // Cursor sync on a merged statement lands on the merged node
+    assert.strictEqual(ir.locationMap[0].nodeId, "a");
+  });
+
+  test("line numbers are added before compaction, one per statement", () => {
+    const ir = applyViewOptions(sampleIR(), { lineNumbers: true, compact: true });
+    assert.strictEqual(ir.nodes[1].label, "L2: x = 1\nL3: f()\nL4: y = 2");
+    assert.strictEqual(ir.nodes[0].label, "Start");
+  });
+
+  test("with scopes, compaction stops at the edge of a body", () => {
+    const scoped = (): FlowchartIR => {
+      const ir = sampleIR();
+      ir.nodes[2].scope = "scope_1";
+      ir.scopes = [{ id: "scope_1", label: "unsafe" }];
+      return ir;
+    };
+    const boxed = applyViewOptions(scoped(), { compact: true, scopes: true });
+    assert.deepStrictEqual(ids(boxed).slice(0, 4), ["start", "a", "b", "c"]);
+    assert.strictEqual(boxed.nodes[2].scope, "scope_1");
+
+    const flat = applyViewOptions(scoped(), { compact: true });
+    assert.deepStrictEqual(ids(flat).slice(0, 3), ["start", "a", "cond"]);
+    assert.strictEqual(flat.scopes, undefined);
+    assert.ok(flat.nodes.every((node) => node.scope === undefined));
+  });
+
+  test("maxDepth folds the deeper body into one summary node", () => {
+    const ir = applyViewOptions(sampleIR(), { maxDepth: 1 });
+    assert.deepStrictEqual(ids(ir), ["start", "a", "b", "c", "cond", "fold_d", "end"]);
+    const summary = ir.nodes[5];
+    assert.strictEqual(summary.folded, 2);
+    assert.strictEqual(summary.label, "2 nested nodes folded");
+    assert.ok(edgesOf(ir).includes("cond->fold_d:true"));
+    assert.ok(edgesOf(ir).includes("fold_d->end"));
+
+    const expanded = applyViewOptions(sampleIR(), { maxDepth: 1, expandedFolds: ["fold_d"] });
+    assert.deepStrictEqual(ids(expanded), ids(sampleIR()));
+  });
+
+  test("cold edges are kept unless coldPaths is false", () => {
+    const isCold = (ir: FlowchartIR): boolean => ir.edges.some((edge) => edge.cold);
+    assert.strictEqual(isCold(applyViewOptions(sampleIR())), true);
+    assert.strictEqual(isCold(applyViewOptions(sampleIR(), { coldPaths: false })), false);
+  });
+
+  test("no options leave the graph as it is", () => {
+    assert.deepStrictEqual(applyViewOptions(sampleIR()), sampleIR());
+  });
+});
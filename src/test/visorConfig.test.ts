@@ -0,0 +1,94 @@
+import * as assert from "assert";
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+import { ConfigError, loadVisorConfig, parseVisorConfig } from "../logic/utils/VisorConfig";
+
+suite("visor.toml", () => {
+  test("reads every key", () => {
+    const config = parseVisorConfig(`
+format = "mermaid"
+theme = "dark"
+labels = "summary"
+include = ["src/**", "lib/*.rs"]
+exclude = "vendor/**"
+line-numbers = true
+compact = false
+`);
+    assert.deepStrictEqual(config, {
+      format: "mermaid",
+      theme: "dark",
+      labels: "summary",
+      include: ["src/**", "lib/*.rs"],
+      exclude: ["vendor/**"],
+      lineNumbers: true,
+      compact: false,
+    });
+  });
+
+  test("unescapes basic strings and leaves literal strings alone", () => {
+    const config = parseVisorConfig(String.raw`include = ["a\"b", "tab\there", "\u00e9", 'C:\src\*.rs']`);
+    assert.deepStrictEqual(config.include, ['a"b', "tab\there", "é", String.raw`C:\src\*.rs`]);
+  });
+
+  test("drops comments, but not a # inside a string", () => {
+    const config = parseVisorConfig(`
+# defaults for the whole team
+format = "dot" # graphviz
+exclude = ["#generated/**", '#tmp'] # both quoted
+`);
+    assert.deepStrictEqual(config, { format: "dot", exclude: ["#generated/**", "#tmp"] });
+  });
+
+  test("reads an array over several lines, with a trailing comma", () => {
+    const config = parseVisorConfig(`
+include = [
+  "src/**",   # sources
+  "tests/**",
+]
+compact = true
+`);
+    assert.deepStrictEqual(config, { include: ["src/**", "tests/**"], compact: true });
+  });
+
+  test("accepts quoted keys", () => {
+    assert.deepStrictEqual(parseVisorConfig(`"line-numbers" = true`), { lineNumbers: true });
+  });
+
+  test("names the file and line of each problem", () => {
+    const cases: [string, RegExp][] = [
+      [`format = "dot"\ncolour = "red"`, /^visor\.toml:2: unknown key "colour" \(expected one of format, theme, /],
+      [`format = "dot"\nformat = "svg"`, /^visor\.toml:2: format is set twice$/],
+      [`format = "gif"`, /^visor\.toml:1: unknown format "gif"$/],
+      [`theme = "neon"`, /^visor\.toml:1: unknown theme "neon"$/],
+      [`compact = "yes"`, /^visor\.toml:1: compact must be true or false$/],
+      [`include = [1, 2]`, /^visor\.toml:1: include must be a glob or an array of globs$/],
+      [`[visor]\nformat = "dot"`, /^visor\.toml:1: tables are not supported/],
+      [`format "dot"`, /^visor\.toml:1: expected key = value$/],
+      [`format = "dot`, /^visor\.toml:1: unterminated string$/],
+      [`include = ["a" "b"]`, /^visor\.toml:1: expected , or \] in array$/],
+      [`format = "dot" "svg"`, /^visor\.toml:1: unexpected "\\"svg\\"" after the value$/],
+      [String.raw`format = "\q"`, /^visor\.toml:1: invalid escape \\q$/],
+    ];
+    for (const [text, message] of cases) {
+      assert.throws(
+        () => parseVisorConfig(text),
+        (error: unknown) => error instanceof ConfigError && message.test(error.message),
+        text
+      );
+    }
+  });
+
+  test("a missing visor.toml is no config, a missing --config file is an error", () => {
+    const dir = fs.mkdtempSync(path.join(os.tmpdir(), "visor-config-"));
+    try {
+      assert.deepStrictEqual(loadVisorConfig(dir), {});
+      assert.throws(() => loadVisorConfig(dir, path.join(dir, "missing.toml")), /cannot read/);
+
+      fs.writeFileSync(path.join(dir, "visor.toml"), `labels = "kind"\n`);
+      assert.deepStrictEqual(loadVisorConfig(dir), { labels: "kind" });
+    } finally {
+      fs.rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
@@ -1,7 +1,27 @@
 import * as vscode from "vscode";
-import { analyzeCode } from "../logic/analyzer";
-import { FlowchartIR, LocationMapEntry } from "../ir/ir"; // <-- Import FlowchartIR
+import {
+  analyzeCode,
+  analyzeFunctionMatches,
+  describeParseError,
+  describeParseErrorRanges,
+  findFunctionAtPosition,
+  findParseErrors,
+  isPlaceholderIR,
+  listFunctions,
+} from "../logic/analyzer";
+import { FlowchartIR, LocationMapEntry, ParseErrorInfo } from "../ir/ir"; // <-- Import FlowchartIR
 import { EnhancedMermaidGenerator } from "../logic/EnhancedMermaidGenerator";
+import {
+  DIAGRAM_FORMATS,
+  DiagramExportOptions,
+  DiagramFormat,
+  generateDiagram,
+  generatePng,
+  getDiagramFormatInfo,
+  isDiagramFormat,
+  validateDiagram,
+} from "../logic/DiagramExporter";
+import { DEFAULT_PNG_DPI } from "../logic/PngGenerator";
 import {
   ComplexityConfiguration, // <-- Import ComplexityConfiguration
   getComplexityConfig,
@@ -9,6 +29,14 @@ import {
 import { LLMManager } from "../logic/llm/LLMManager";
 import { getExtensionContext } from "../logic/llm/LLMContext";
 import { EnvironmentDetector } from "../logic/utils/EnvironmentDetector";
+import { StringProcessor } from "../logic/utils/StringProcessor";
+import { validateDiagramColors } from "../logic/utils/DiagramColors";
+import { isLabelMode } from "../logic/utils/NodeLabels";
+import {
+  applyViewOptions,
+  FlowchartViewOptions,
+  isCollapsible,
+} from "../logic/utils/FlowchartTransforms";
 
 const MERMAID_VERSION = "11.8.0";
 const SVG_PAN_ZOOM_VERSION = "3.6.1";
@@ -24,6 +52,18 @@ export type ExportMessage = {
   payload: { fileType: "svg" | "png"; data: string };
 };
 
+// Saves an SVG rendered natively from the current IR rather than by Mermaid.
+export type SaveSvgMessage = {
+  command: "saveSvg";
+  payload: {};
+};
+
+// Saves a PNG rasterized natively from the current IR.
+export type SavePngMessage = {
+  command: "savePng";
+  payload: {};
+};
+
 export type ExportErrorMessage = {
   command: "exportError";
   payload: { error: string };
@@ -34,10 +74,40 @@ export type OpenInPanelMessage = {
   payload: {};
 };
 
-// New message type for copying mermaid code
-export type CopyMermaidMessage = {
-  command: "copyMermaid";
-  payload: { code: string };
+// Copies the diagram in the format picked in the webview. `code` is the
+// currently rendered Mermaid source (which may carry LLM labels).
+export type CopyCodeMessage = {
+  command: "copyCode";
+  payload: { code: string; format: DiagramFormat };
+};
+
+export type SelectFormatMessage = {
+  command: "selectFormat";
+  payload: { format: DiagramFormat };
+};
+
+// Jumps to the function picked in the webview's function dropdown.
+export type SelectFunctionMessage = {
+  command: "selectFunction";
+  payload: { name: string };
+};
+
+// Shows or hides the def-use annotations and edges.
+export type ToggleDataflowMessage = {
+  command: "toggleDataflow";
+  payload: {};
+};
+
+// Shows or hides the bold longest path from entry to exit.
+export type ToggleLongestPathMessage = {
+  command: "toggleLongestPath";
+  payload: {};
+};
+
+// Unfolds one summary node left by `visor.diagram.maxDepth`.
+export type ExpandFoldMessage = {
+  command: "expandFold";
+  payload: { id: string };
 };
 
 export type RequestLLMLabelsMessage = {
@@ -58,9 +128,16 @@ export type SetupLLMMessage = {
 export type WebviewMessage =
   | HighlightCodeMessage
   | ExportMessage
+  | SaveSvgMessage
+  | SavePngMessage
   | ExportErrorMessage
   | OpenInPanelMessage
-  | CopyMermaidMessage
+  | CopyCodeMessage
+  | SelectFormatMessage
+  | SelectFunctionMessage
+  | ToggleDataflowMessage
+  | ToggleLongestPathMessage
+  | ExpandFoldMessage
   | RequestLLMLabelsMessage
   | DisableLLMLabelsMessage
   | SetupLLMMessage;
@@ -86,6 +163,17 @@ export abstract class BaseFlowchartProvider {
   protected _eventListenersSetup: boolean = false;
   protected _mermaidCodeOriginal?: string;
   protected _mermaidCodeLLM?: string;
+  protected _currentIR?: FlowchartIR;
+  protected _exportFormat: DiagramFormat;
+  protected _functionNames: string[] = [];
+  // Regions of the current file that did not parse, outside the shown function
+  protected _parseErrors: ParseErrorInfo[] = [];
+  protected _currentFunctionName?: string;
+  protected _showDataflow: boolean = false;
+  protected _showLongestPath: boolean = false;
+  // Summary nodes the user expanded, kept while the same function is shown
+  protected _expandedFolds = new Set<string>();
+  private _foldScope?: string;
   private _cachedClickHandlers?: { source: string; lines: string[] };
 
   private complexityConfig: ComplexityConfiguration;
@@ -97,6 +185,7 @@ export abstract class BaseFlowchartProvider {
     this.initializeEnvironment();
 
     this.complexityConfig = getComplexityConfig();
+    this._exportFormat = this.getConfiguredExportFormat();
     this.complexityDecorationType = this.createDecorationType();
     this.activeEditorForDecoration = vscode.window.activeTextEditor;
 
@@ -108,6 +197,14 @@ export abstract class BaseFlowchartProvider {
           this.updateView(vscode.window.activeTextEditor);
         }
 
+        if (e.affectsConfiguration("visor.export.format")) {
+          this._exportFormat = this.getConfiguredExportFormat();
+        }
+
+        if (e.affectsConfiguration("visor.diagram") || e.affectsConfiguration("visor.analysis")) {
+          this.forceUpdateView(vscode.window.activeTextEditor);
+        }
+
         if (e.affectsConfiguration("visor.complexity")) {
           this.complexityConfig = getComplexityConfig();
           // Re-create decoration to apply new styles
@@ -122,6 +219,53 @@ export abstract class BaseFlowchartProvider {
     );
   }
 
+  /**
+   * Reads the default diagram format for the copy action from settings.
+   */
+  private getConfiguredExportFormat(): DiagramFormat {
+    const configured = vscode.workspace
+      .getConfiguration("visor")
+      .get<string>("export.format", "mermaid");
+    return isDiagramFormat(configured) ? configured : "mermaid";
+  }
+
+  /**
+   * Reads the colors applied to copied and exported PlantUML, DOT and SVG.
+   * The panel itself keeps following `visor.nodeReadability.theme`.
+   */
+  private getConfiguredColorOptions(): DiagramExportOptions {
+    const config = vscode.workspace.getConfiguration("visor");
+    const colorTheme = config.get<string>("export.colorTheme", "none");
+    const colors = config.get<Record<string, string>>("export.colors", {});
+    const errors = validateDiagramColors(colors);
+    if (errors.length > 0) {
+      console.warn(`Ignoring invalid visor.export.colors entries: ${errors.join("; ")}`);
+    }
+    return {
+      colorTheme: colorTheme === "none" ? undefined : colorTheme,
+      colors: Object.keys(colors).length > 0 ? colors : undefined,
+    };
+  }
+
+  /**
+   * Reads the presentation options applied to every generated diagram.
+   */
+  private getConfiguredViewOptions(): FlowchartViewOptions {
+    const config = vscode.workspace.getConfiguration("visor");
+    const labelMode = config.get<string>("diagram.labelMode");
+    return {
+      lineNumbers: config.get<boolean>("diagram.lineNumbers", false),
+      compact: config.get<boolean>("diagram.compact", false),
+      maxDepth: config.get<number>("diagram.maxDepth", 0) || undefined,
+      coldPaths: config.get<boolean>("diagram.coldPaths", true),
+      labelMode: labelMode && isLabelMode(labelMode) ? labelMode : undefined,
+      labelWidth: config.get<number>("diagram.labelWidth", 0) || undefined,
+      labelOverflow: config.get<boolean>("diagram.truncateLabels", false)
+        ? "truncate"
+        : "wrap",
+    };
+  }
+
   // ... (initializeEnvironment method is unchanged)
   /**
    * Initialize environment-specific settings and compatibility fixes
@@ -372,6 +516,16 @@ export abstract class BaseFlowchartProvider {
         break;
       }
 
+      case "saveSvg": {
+        await this.handleSaveSvg();
+        break;
+      }
+
+      case "savePng": {
+        await this.handleSavePng();
+        break;
+      }
+
       case "exportError": {
         vscode.window.showErrorMessage(
           `Export failed: ${message.payload.error}`
@@ -385,9 +539,47 @@ export abstract class BaseFlowchartProvider {
         break;
       }
 
-      case "copyMermaid": {
-        await vscode.env.clipboard.writeText(message.payload.code);
-        vscode.window.showInformationMessage("Mermaid code copied to clipboard!");
+      case "copyCode": {
+        await this.handleCopyCode(message.payload);
+        break;
+      }
+
+      case "selectFormat": {
+        if (isDiagramFormat(message.payload.format)) {
+          this._exportFormat = message.payload.format;
+        }
+        break;
+      }
+
+      case "selectFunction": {
+        await this.handleSelectFunction(message.payload.name);
+        break;
+      }
+
+      case "toggleDataflow": {
+        this._showDataflow = !this._showDataflow;
+        const editor = vscode.window.visibleTextEditors.find(
+          (e) => e.document === this._currentDocument
+        );
+        await this.forceUpdateView(editor || vscode.window.activeTextEditor);
+        break;
+      }
+
+      case "toggleLongestPath": {
+        this._showLongestPath = !this._showLongestPath;
+        const editor = vscode.window.visibleTextEditors.find(
+          (e) => e.document === this._currentDocument
+        );
+        await this.forceUpdateView(editor || vscode.window.activeTextEditor);
+        break;
+      }
+
+      case "expandFold": {
+        this._expandedFolds.add(message.payload.id);
+        const editor = vscode.window.visibleTextEditors.find(
+          (e) => e.document === this._currentDocument
+        );
+        await this.forceUpdateView(editor || vscode.window.activeTextEditor);
         break;
       }
 
@@ -503,6 +695,161 @@ export abstract class BaseFlowchartProvider {
     }
   }
 
+  /**
+   * Copies the current diagram to the clipboard in the requested format.
+   * Mermaid uses the rendered source so LLM labels are kept; other formats
+   * are emitted from the IR of the displayed function.
+   */
+  private async handleCopyCode(payload: {
+    code: string;
+    format: DiagramFormat;
+  }): Promise<void> {
+    const format = isDiagramFormat(payload.format)
+      ? payload.format
+      : this._exportFormat;
+    const formatInfo = getDiagramFormatInfo(format);
+
+    let code: string;
+    if (format === "mermaid") {
+      code = payload.code;
+    } else {
+      if (!this._currentIR) {
+        vscode.window.showErrorMessage("Cannot copy: no flowchart is displayed.");
+        return;
+      }
+      code = generateDiagram(this._currentIR, format, this.getConfiguredColorOptions());
+    }
+
+    const errors = validateDiagram(code, format);
+    if (errors.length > 0) {
+      vscode.window.showErrorMessage(
+        `Cannot copy: generated ${formatInfo.label} is invalid (${errors.join("; ")}).`
+      );
+      return;
+    }
+
+    await vscode.env.clipboard.writeText(code);
+    vscode.window.showInformationMessage(
+      `${formatInfo.label} code copied to clipboard!`
+    );
+  }
+
+  /**
+   * Saves the current flowchart as an SVG drawn by Visor's own layout, so
+   * exporting works without loading a renderer.
+   */
+  private async handleSaveSvg(): Promise<void> {
+    if (!this._currentIR) {
+      vscode.window.showErrorMessage("Cannot save: no flowchart is displayed.");
+      return;
+    }
+    await this.handleExport({
+      fileType: "svg",
+      data: generateDiagram(this._currentIR, "svg", this.getConfiguredColorOptions()),
+    });
+  }
+
+  private async handleSavePng(): Promise<void> {
+    if (!this._currentIR) {
+      vscode.window.showErrorMessage("Cannot save: no flowchart is displayed.");
+      return;
+    }
+    const config = vscode.workspace.getConfiguration("visor");
+    let png: Buffer;
+    try {
+      png = generatePng(this._currentIR, {
+        ...this.getConfiguredColorOptions(),
+        dpi: config.get<number>("export.pngDpi", DEFAULT_PNG_DPI),
+        transparent: config.get<boolean>("export.pngTransparent", false),
+      });
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      vscode.window.showErrorMessage(`Failed to render PNG: ${message}`);
+      return;
+    }
+    await this.handleExport({ fileType: "png", data: png.toString("base64") });
+  }
+
+  /**
+   * Refreshes the function dropdown for the document being shown.
+   */
+  private async updateFunctionNames(
+    document: vscode.TextDocument,
+    position: number
+  ): Promise<void> {
+    try {
+      const text = document.getText();
+      this._functionNames = Array.from(
+        new Set(await listFunctions(text, document.languageId))
+      ).filter((name) => !name.startsWith("["));
+      this._currentFunctionName = await findFunctionAtPosition(
+        text,
+        document.languageId,
+        position
+      );
+    } catch (error) {
+      console.warn("Visor: could not list functions:", error);
+      this._functionNames = [];
+      this._currentFunctionName = undefined;
+    }
+  }
+
+  /**
+   * Moves the cursor to the named function so the view follows it. When
+   * the name is defined more than once, asks which definition to show.
+   */
+  private async handleSelectFunction(name: string): Promise<void> {
+    const document = this._currentDocument;
+    if (!document) {
+      return;
+    }
+
+    const matches = await analyzeFunctionMatches(
+      document.getText(),
+      document.languageId,
+      name
+    );
+    let start = matches[0]?.functionRange?.start;
+    if (start === undefined) {
+      vscode.window.showErrorMessage(
+        `Visor: No function named '${name}'. Available: ${this._functionNames.join(", ")}`
+      );
+      return;
+    }
+
+    if (matches.length > 1) {
+      const picked = await vscode.window.showQuickPick(
+        matches.map((ir) => {
+          const offset = ir.functionRange?.start ?? 0;
+          return {
+            label: name,
+            description: `line ${document.positionAt(offset).line + 1}`,
+            offset,
+          };
+        }),
+        { placeHolder: `'${name}' is defined ${matches.length} times; pick one` }
+      );
+      if (!picked) {
+        return;
+      }
+      start = picked.offset;
+    }
+
+    const editor = await vscode.window.showTextDocument(document, {
+      viewColumn: vscode.window.visibleTextEditors.find(
+        (e) => e.document === document
+      )?.viewColumn,
+      preserveFocus: false,
+    });
+    const target = document.positionAt(start);
+    editor.selection = new vscode.Selection(target, target);
+    editor.revealRange(
+      new vscode.Range(target, target),
+      vscode.TextEditorRevealType.InCenterIfOutsideViewport
+    );
+    await this.forceUpdateView(editor);
+  }
+
   /**
    * Handle export functionality
    */
@@ -642,8 +989,38 @@ export abstract class BaseFlowchartProvider {
         undefined,
         position
       );
+      // A broken function would be drawn garbled; explain instead
+      const parseErrors = await findParseErrors(document.getText(), document.languageId);
+      const functionErrors = flowchartIR.parseErrors || [];
+      if (
+        functionErrors.length > 0 ||
+        (isPlaceholderIR(flowchartIR) && parseErrors.length > 0)
+      ) {
+        this.clearComplexityDecoration(editor);
+        this._currentIR = undefined;
+        this._analyzedIR = undefined;
+        this.setWebviewHtml(
+          this.getLoadingHtml(
+            this.escapeHtml(
+              this.getParseErrorMessage(functionErrors.length > 0 ? functionErrors : parseErrors)
+            )
+          )
+        );
+        return;
+      }
+      this._parseErrors = parseErrors;
+
+      const foldScope = `${document.uri.toString()}#${
+        flowchartIR.title ?? flowchartIR.functionRange?.start
+      }`;
+      if (foldScope !== this._foldScope) {
+        this._expandedFolds.clear();
+        this._collapsedBranches.clear();
+        this._foldScope = foldScope;
+      }
 
-      this._locationMap = flowchartIR.locationMap;
+      this._analyzedIR = flowchartIR;
+      await this.updateFunctionNames(document, position);
       this._currentDocument = document;
       this._currentPosition = position;
 
@@ -659,45 +1036,7 @@ export abstract class BaseFlowchartProvider {
       // Apply the inline complexity decoration
       this.updateComplexityDecoration(editor, flowchartIR);
 
-      // Generate Mermaid diagram from FlowchartIR with enhanced styling
-      const vsCodeTheme =
-        vscode.window.activeColorTheme.kind === vscode.ColorThemeKind.Dark
-          ? "dark"
-          : "light";
-
-      // Read the selected theme from user configuration
-      const config = vscode.workspace.getConfiguration("visor");
-      const selectedTheme = config.get<string>(
-        "nodeReadability.theme",
-        "monokai"
-      );
-
-      const mermaidGenerator = new EnhancedMermaidGenerator(
-        selectedTheme,
-        vsCodeTheme
-      );
-      const mermaidCode = mermaidGenerator.generate(flowchartIR);
-      this._mermaidCodeOriginal = mermaidCode;
-      this._mermaidCodeLLM = undefined;
-      // Only pass complexity info if it's enabled and should be displayed in panel
-      const complexityConfig = getComplexityConfig(); // Already have this in this.complexityConfig
-      const complexityToDisplay =
-        this.complexityConfig.enabled &&
-        this.complexityConfig.displayInPanel &&
-        flowchartIR.functionComplexity
-          ? flowchartIR.functionComplexity
-          : undefined;
-
-      const ctx = getExtensionContext();
-      const availability = ctx ? await LLMManager.getAvailability(ctx) : { enabled: false, provider: "openai", model: "" };
-      this.setWebviewHtml(this.getWebviewContent(mermaidCode, this.getNonce(), complexityToDisplay, availability));
-
-      // After updating the view, immediately highlight the node for the current cursor
-      const offset = editor.document.offsetAt(editor.selection.active);
-      const entry = this._locationMap.find(
-        (e) => offset >= e.start && offset <= e.end
-      );
-      this.highlightNode(entry ? entry.nodeId : null);
+      await this.renderFlowchart(editor);
 
     } catch (error) {
       this.clearComplexityDecoration(editor); 
@@ -710,6 +1049,89 @@ export abstract class BaseFlowchartProvider {
     }
   }
 
+  /**
+   * Emits and shows the analyzed graph with the current view options,
+   * collapsed branches and expanded folds. Toggles call this directly, so
+   * the source is not parsed again.
+   */
+  private async renderFlowchart(editor: vscode.TextEditor): Promise<void> {
+    if (!this._analyzedIR) {
+      return;
+    }
+    // Edits can renumber nodes; drop collapsed ids that no longer name a branch
+    for (const id of this._collapsedBranches) {
+      const node = this._analyzedIR.nodes.find((candidate) => candidate.id === id);
+      if (!node || !isCollapsible(node)) {
+        this._collapsedBranches.delete(id);
+      }
+    }
+
+    // Applied once here so the rendered view and every copied format agree
+    const flowchartIR = applyViewOptions(JSON.parse(JSON.stringify(this._analyzedIR)), {
+      ...this.getConfiguredViewOptions(),
+      dataflow: this._showDataflow,
+      longestPath: this._showLongestPath,
+      expandedFolds: Array.from(this._expandedFolds),
+      collapsedBranches: Array.from(this._collapsedBranches),
+    });
+    this._locationMap = flowchartIR.locationMap;
+    this._currentIR = flowchartIR;
+
+    // Generate Mermaid diagram from FlowchartIR with enhanced styling
+    const vsCodeTheme =
+      vscode.window.activeColorTheme.kind === vscode.ColorThemeKind.Dark
+        ? "dark"
+        : "light";
+
+    // Read the selected theme from user configuration
+    const config = vscode.workspace.getConfiguration("visor");
+    const selectedTheme = config.get<string>(
+      "nodeReadability.theme",
+      "monokai"
+    );
+
+    const mermaidGenerator = new EnhancedMermaidGenerator(
+      selectedTheme,
+      vsCodeTheme
+    );
+    const mermaidCode = mermaidGenerator.generate(flowchartIR);
+    this._mermaidCodeOriginal = mermaidCode;
+    this._mermaidCodeLLM = undefined;
+    // Only pass complexity info if it's enabled and should be displayed in panel
+    const complexityToDisplay =
+      this.complexityConfig.enabled &&
+      this.complexityConfig.displayInPanel &&
+      flowchartIR.functionComplexity
+        ? flowchartIR.functionComplexity
+        : undefined;
+
+    const ctx = getExtensionContext();
+    const availability = ctx ? await LLMManager.getAvailability(ctx) : { enabled: false, provider: "openai", model: "" };
+    this.setWebviewHtml(this.getWebviewContent(mermaidCode, this.getNonce(), complexityToDisplay, availability));
+
+    // After updating the view, immediately highlight the node for the current cursor
+    const offset = editor.document.offsetAt(editor.selection.active);
+    const entry = this._locationMap.find(
+      (e) => offset >= e.start && offset <= e.end
+    );
+    this.highlightNode(entry ? entry.nodeId : null);
+  }
+
+  /**
+   * Re-emits the shown function after a view toggle. Falls back to a full
+   * update when nothing is analyzed yet or the editor has moved on.
+   */
+  private async rerender(): Promise<void> {
+    const editor =
+      vscode.window.visibleTextEditors.find((e) => e.document === this._currentDocument) ||
+      vscode.window.activeTextEditor;
+    if (!editor || !this._analyzedIR || editor.document !== this._currentDocument) {
+      await this.forceUpdateView(editor);
+      return;
+    }
+    await this.renderFlowchart(editor);
+  }
+
   /**
    * Determines if the view should be updated based on document and position changes
    */
@@ -893,6 +1315,13 @@ export abstract class BaseFlowchartProvider {
                 });
             }
 
+            function onFoldClick(id) {
+                vscode.postMessage({
+                    command: 'expandFold',
+                    payload: { id }
+                });
+            }
+
             let highlightedNodeId = null;
 
             window.addEventListener('message', event => {
@@ -1014,6 +1443,8 @@ export abstract class BaseFlowchartProvider {
 
                 const panZoomInstance = svgPanZoom(svgElement, {
                     zoomEnabled: true,
+                    // Double-click collapses a branch instead of zooming
+                    dblClickZoomEnabled: false,
                     controlIconsEnabled: true,
                     fit: true,
                     center: true,
@@ -1131,6 +1562,15 @@ export abstract class BaseFlowchartProvider {
                             el.classList.remove('hover-highlight', 'child-highlight');
                         });
                     });
+
+                    // Collapses or expands a branch or loop body; other nodes ignore it
+                    node.addEventListener('dblclick', (event) => {
+                        event.stopPropagation();
+                        vscode.postMessage({
+                            command: 'toggleCollapse',
+                            payload: { id: extractBaseId(fullNodeId) }
+                        });
+                    });
                 });
             }
 
@@ -1147,11 +1587,24 @@ export abstract class BaseFlowchartProvider {
             });
 
             function setupButtonHandlers() {
+                const formatSelect = document.getElementById('format-select');
+                if (formatSelect) {
+                    formatSelect.addEventListener('change', () => {
+                        vscode.postMessage({ command: 'selectFormat', payload: { format: formatSelect.value } });
+                    });
+                }
+                const functionSelect = document.getElementById('function-select');
+                if (functionSelect) {
+                    functionSelect.addEventListener('change', () => {
+                        vscode.postMessage({ command: 'selectFunction', payload: { name: functionSelect.value } });
+                    });
+                }
                 const copyBtn = document.getElementById('copy-mermaid');
                 if (copyBtn) {
                     copyBtn.addEventListener('click', () => {
                         const source = document.getElementById('mermaid-source').textContent;
-                        vscode.postMessage({ command: 'copyMermaid', payload: { code: source } });
+                        const format = formatSelect ? formatSelect.value : 'mermaid';
+                        vscode.postMessage({ command: 'copyCode', payload: { code: source, format } });
                     });
                 }
                 const exportSvgBtn = document.getElementById('export-svg');
@@ -1160,6 +1613,34 @@ export abstract class BaseFlowchartProvider {
                 const exportPngBtn = document.getElementById('export-png');
                 if(exportPngBtn) exportPngBtn.addEventListener('click', () => exportFlowchart('png'));
 
+                const saveSvgBtn = document.getElementById('save-svg');
+                if (saveSvgBtn) {
+                    saveSvgBtn.addEventListener('click', () => {
+                        vscode.postMessage({ command: 'saveSvg', payload: {} });
+                    });
+                }
+
+                const savePngBtn = document.getElementById('save-png');
+                if (savePngBtn) {
+                    savePngBtn.addEventListener('click', () => {
+                        vscode.postMessage({ command: 'savePng', payload: {} });
+                    });
+                }
+
+                const dataflowBtn = document.getElementById('dataflow-toggle');
+                if (dataflowBtn) {
+                    dataflowBtn.addEventListener('click', () => {
+                        vscode.postMessage({ command: 'toggleDataflow', payload: {} });
+                    });
+                }
+
+                const longestPathBtn = document.getElementById('longest-path-toggle');
+                if (longestPathBtn) {
+                    longestPathBtn.addEventListener('click', () => {
+                        vscode.postMessage({ command: 'toggleLongestPath', payload: {} });
+                    });
+                }
+
                 const openPanelBtn = document.getElementById('open-panel-btn');
                 if (openPanelBtn) {
                     openPanelBtn.addEventListener('click', () => {
@@ -1196,6 +1677,126 @@ export abstract class BaseFlowchartProvider {
             }
             setupButtonHandlers();
 
+            // "/" opens a fuzzy search over the function dropdown; arrows move
+            // through the matches and Enter shows the selected one
+            function setupKeyboardNavigation() {
+                const functionSelect = document.getElementById('function-select');
+                const search = document.getElementById('function-search');
+                const input = document.getElementById('function-search-input');
+                const results = document.getElementById('function-search-results');
+                const names = functionSelect ? Array.from(functionSelect.options).map(option => option.value) : [];
+                let matches = [];
+                let selected = 0;
+
+                // Lower is better: every query character must appear in order;
+                // gaps between them and a late first match cost points
+                function fuzzyScore(name, query) {
+                    const text = name.toLowerCase();
+                    let score = 0;
+                    let last = -1;
+                    for (const char of query.toLowerCase()) {
+                        const index = text.indexOf(char, last + 1);
+                        if (index === -1) {
+                            return undefined;
+                        }
+                        score += last === -1 ? index : index - last - 1;
+                        last = index;
+                    }
+                    return score;
+                }
+
+                function render() {
+                    results.replaceChildren(...matches.map((name, index) => {
+                        const item = document.createElement('li');
+                        item.textContent = name;
+                        item.className = index === selected ? 'selected' : '';
+                        item.addEventListener('mousedown', (event) => {
+                            event.preventDefault();
+                            choose(name);
+                        });
+                        return item;
+                    }));
+                    const current = results.children[selected];
+                    if (current) {
+                        current.scrollIntoView({ block: 'nearest' });
+                    }
+                }
+
+                function filter() {
+                    const query = input.value.trim();
+                    matches = names
+                        .map((name, index) => ({ name, index, score: query ? fuzzyScore(name, query) : 0 }))
+                        .filter(match => match.score !== undefined)
+                        .sort((a, b) => a.score - b.score || a.index - b.index)
+                        .map(match => match.name);
+                    selected = 0;
+                    render();
+                }
+
+                function open() {
+                    search.hidden = false;
+                    input.value = '';
+                    filter();
+                    input.focus();
+                }
+
+                function close() {
+                    search.hidden = true;
+                    input.blur();
+                }
+
+                function choose(name) {
+                    close();
+                    if (name && name !== functionSelect.value) {
+                        functionSelect.value = name;
+                        vscode.postMessage({ command: 'selectFunction', payload: { name } });
+                    }
+                }
+
+                if (search && input && results && functionSelect) {
+                    input.addEventListener('input', filter);
+                    input.addEventListener('blur', () => { search.hidden = true; });
+                    input.addEventListener('keydown', (event) => {
+                        if (event.key === 'ArrowDown' || event.key === 'ArrowUp') {
+                            event.preventDefault();
+                            if (matches.length > 0) {
+                                const step = event.key === 'ArrowDown' ? 1 : -1;
+                                selected = (selected + step + matches.length) % matches.length;
+                                render();
+                            }
+                        } else if (event.key === 'Enter') {
+                            event.preventDefault();
+                            choose(matches[selected]);
+                        } else if (event.key === 'Escape') {
+                            event.preventDefault();
+                            close();
+                        }
+                    });
+                }
+
+                document.addEventListener('keydown', (event) => {
+                    const target = event.target;
+                    const typing = target instanceof HTMLInputElement || target instanceof HTMLSelectElement ||
+                        target instanceof HTMLTextAreaElement;
+                    if (typing) {
+                        return;
+                    }
+                    if (event.key === '/' && search && !event.ctrlKey && !event.metaKey && !event.altKey) {
+                        event.preventDefault();
+                        open();
+                    } else if ((event.ctrlKey || event.metaKey) && event.key.toLowerCase() === 'c') {
+                        // Selected text copies as usual; otherwise the diagram does
+                        const selection = window.getSelection();
+                        const copyBtn = document.getElementById('copy-mermaid');
+                        if ((!selection || selection.isCollapsed) && copyBtn) {
+                            event.preventDefault();
+                            copyBtn.click();
+                        }
+                    }
+                });
+            }
+            setupKeyboardNavigation();
+
             /**
              * Handles exporting the flowchart to SVG or PNG.
              * It generates a clean SVG from the source to ensure no UI controls are included.
@@ -1351,9 +1952,41 @@ export abstract class BaseFlowchartProvider {
             border: 1px solid var(--vscode-button-border, transparent);
             padding: 5px 10px; cursor: pointer; border-radius: 4px; font-size: 11px;
         }
+        #function-search {
+            position: fixed; top: 48px; left: 50%; transform: translateX(-50%);
+            z-index: 1002; width: min(360px, 90vw);
+            background-color: var(--vscode-quickInput-background, var(--vscode-editorWidget-background));
+            color: var(--vscode-quickInput-foreground, var(--vscode-foreground));
+            border: 1px solid var(--vscode-widget-border, var(--vscode-panel-border));
+            border-radius: 6px; box-shadow: 0 4px 12px rgba(0, 0, 0, 0.25); padding: 6px;
+        }
+        #function-search[hidden] { display: none; }
+        #function-search-input {
+            box-sizing: border-box; width: 100%; padding: 4px 6px; font-size: 12px;
+            background-color: var(--vscode-input-background); color: var(--vscode-input-foreground);
+            border: 1px solid var(--vscode-focusBorder, transparent); border-radius: 3px;
+        }
+        #function-search-results {
+            list-style: none; margin: 4px 0 0; padding: 0; max-height: 240px; overflow-y: auto; font-size: 12px;
+        }
+        #function-search-results li { padding: 3px 6px; border-radius: 3px; cursor: pointer; }
+        #function-search-results li.selected {
+            background-color: var(--vscode-list-activeSelectionBackground);
+            color: var(--vscode-list-activeSelectionForeground);
+        }
+        #format-select, #function-select {
+            background-color: var(--vscode-dropdown-background);
+            color: var(--vscode-dropdown-foreground);
+            border: 1px solid var(--vscode-dropdown-border, transparent);
+            padding: 4px 6px; border-radius: 4px; font-size: 11px;
+        }
         #export-controls button:hover, #open-panel-btn:hover {
             background-color: var(--vscode-button-hoverBackground);
         }
+        #parse-error-banner {
+            ${context.isPanel ? "" : "position: absolute; top: 44px; right: 10px; z-index: 1000;"}
+            color: var(--vscode-editorWarning-foreground); font-size: 11px;
+        }
         
         /* LLM toggle */
         #llm-toggle {
@@ -1411,31 +2044,69 @@ export abstract class BaseFlowchartProvider {
    * Helper to generate HTML for controls.
    */
   private getHtmlForControls(context: any, functionComplexity: any): string {
+    const formatOptions = DIAGRAM_FORMATS.map(
+      (format) =>
+        `<option value="${format.id}"${
+          format.id === this._exportFormat ? " selected" : ""
+        }>${format.label}</option>`
+    ).join("");
+    const formatSelect = `<select id="format-select" title="Format used by Copy Code">${formatOptions}</select>`;
+    const functionSelect = this.getFunctionSelectHtml();
+    const functionSearch = this.getFunctionSearchHtml();
+    const dataflowToggle = `<button id="dataflow-toggle" title="Show the variables each node defines and uses, with dashed def-use edges">${
+      this._showDataflow ? "Hide Data Flow" : "Data Flow"
+    }</button>`;
+    const longestPathToggle = `<button id="longest-path-toggle" title="Draw the longest path from entry to exit in bold">${
+      this._showLongestPath ? "Hide Longest Path" : "Longest Path"
+    }</button>`;
+
+    const functionLabel = this.getFunctionLabel();
+    const parseErrorBanner = this.getParseErrorBannerHtml();
+    const complexityBadge = functionComplexity
+      ? ` <span class="complexity-rating complexity-${functionComplexity.rating}" title="Cyclomatic complexity">CC ${functionComplexity.cyclomaticComplexity}</span>`
+      : "";
+
     const panelControls = `
         <div id="panel-controls">
-            <div>Flowchart Viewer</div>
+            <div>${functionLabel || "Flowchart Viewer"}${complexityBadge}${parseErrorBanner}</div>
             <div id="export-controls">
-                <button id="copy-mermaid" title="Copy Mermaid Code">Copy Code</button>
+                ${functionSelect}
+                ${formatSelect}
+                <button id="copy-mermaid" title="Copy diagram code in the selected format">Copy Code</button>
                 <button id="export-svg" title="Export as SVG">💾 SVG</button>
                 <button id="export-png" title="Export as PNG">🖼️ PNG</button>
+                <button id="save-svg" title="Save as SVG using the built-in layout (works offline)">📐 Save as SVG</button>
+                <button id="save-png" title="Save as PNG using the built-in layout (works offline)">📐 Save as PNG</button>
+                ${dataflowToggle}
+                ${longestPathToggle}
                 <button id="llm-toggle" title="Toggle human-friendly labels"></button>
             </div>
         </div>
+        ${functionSearch}
     `;
 
     const sidebarControls = `
         <div id="export-controls">
             ${context.showPanelButton ? '<button id="open-panel-btn">🚀 Open in New Window</button>' : ''}
-            <button id="copy-mermaid" title="Copy Mermaid Code">Copy Code</button>
+            ${functionSelect}
+            ${formatSelect}
+            <button id="copy-mermaid" title="Copy diagram code in the selected format">Copy Code</button>
             <button id="export-svg">Export as SVG</button>
             <button id="export-png">Export as PNG</button>
+            <button id="save-svg" title="Uses the built-in layout and works offline">Save as SVG</button>
+            <button id="save-png" title="Uses the built-in layout and works offline">Save as PNG</button>
+            ${dataflowToggle}
+            ${longestPathToggle}
             <button id="llm-toggle" title="Toggle human-friendly labels"></button>
         </div>
+        ${parseErrorBanner}
+        ${functionSearch}
         ${
           functionComplexity ? `
         <div id="complexity-container">
             <button id="complexity-toggle" title="Toggle complexity display">📊</button>
             <div id="complexity-panel">
+                ${functionLabel ? `<div>${functionLabel}</div>` : ""}
                 <div>
                     <strong>Cyclomatic Complexity:</strong> 
                     ${functionComplexity.cyclomaticComplexity}
@@ -1455,6 +2126,78 @@ export abstract class BaseFlowchartProvider {
     return context.isPanel ? panelControls : sidebarControls;
   }
 
+  private getFunctionSelectHtml(): string {
+    if (this._functionNames.length < 2) {
+      return "";
+    }
+    const options = this._functionNames
+      .map(
+        (name) =>
+          `<option value="${this.escapeHtml(name)}"${
+            name === this._currentFunctionName ? " selected" : ""
+          }>${this.escapeHtml(name)}</option>`
+      )
+      .join("");
+    return `<select id="function-select" title="Show another function in this file (press / to search)">${options}</select>`;
+  }
+
+  /** The box `/` opens to search the function dropdown; filled in by the webview script. */
+  private getFunctionSearchHtml(): string {
+    if (this._functionNames.length < 2) {
+      return "";
+    }
+    return `<div id="function-search" hidden>
+            <input id="function-search-input" type="text" placeholder="Go to function" aria-label="Search functions" spellcheck="false">
+            <ul id="function-search-results" role="listbox"></ul>
+        </div>`;
+  }
+
+  /**
+   * Notes the parts of the file that did not parse while the current
+   * function is still shown.
+   */
+  private getParseErrorBannerHtml(): string {
+    if (this._parseErrors.length === 0) {
+      return "";
+    }
+    const details = this._parseErrors.map(describeParseError).join("\n");
+    return `<div id="parse-error-banner" title="${this.escapeHtml(details)}">⚠ Not parsed: ${this.escapeHtml(
+      describeParseErrorRanges(this._parseErrors)
+    )}</div>`;
+  }
+
+  private getParseErrorMessage(errors: ParseErrorInfo[]): string {
+    const [first] = errors;
+    const more =
+      errors.length > 1
+        ? ` (${errors.length - 1} more: ${describeParseErrorRanges(errors.slice(1))})`
+        : "";
+    return `Cannot draw this function: syntax error at ${describeParseError(first)}${more}.`;
+  }
+
+  private escapeHtml(text: string): string {
+    return text
+      .replace(/&/g, "&amp;")
+      .replace(/</g, "&lt;")
+      .replace(/>/g, "&gt;")
+      .replace(/"/g, "&quot;");
+  }
+
+  /**
+   * The analyzed function as shown in the view header, e.g. `function: foo`,
+   * HTML-escaped.
+   */
+  private getFunctionLabel(): string {
+    const title = this._currentIR?.title;
+    if (!title) {
+      return "";
+    }
+    return StringProcessor.unescapeString(title.replace(/^Flowchart for /, ""))
+      .replace(/&/g, "&amp;")
+      .replace(/</g, "&lt;")
+      .replace(/>/g, "&gt;");
+  }
+
   /**
    * Generates a simple HTML page to show a loading or informational message.
    */
@@ -1515,6 +2258,8 @@ export abstract class BaseFlowchartProvider {
     this._currentPosition = undefined;
     this._isUpdating = false;
     this._locationMap = [];
+    this._currentIR = undefined;
+    this._analyzedIR = undefined;
     this._currentFunctionRange = undefined;
     this._eventListenersSetup = false;
 
@@ -3,6 +3,7 @@
 "use strict";
 
 const path = require("path");
+const webpack = require("webpack");
 const CopyPlugin = require("copy-webpack-plugin");
 
 /**@type {import('webpack').Configuration}*/
@@ -129,4 +130,27 @@ const config = {
     }),
   ],
 };
-module.exports = config;
+/**
+ * Standalone command-line tool (`visor --dir ... --out ...`). It is emitted
+ * into `dist` next to the wasm grammars copied by the extension build.
+ */
+/**@type {import('webpack').Configuration}*/
+const cliConfig = {
+  target: "node",
+  mode: "none",
+
+  entry: "./src/cli.ts",
+  output: {
+    path: path.resolve(__dirname, "dist"),
+    filename: "cli.js",
+    libraryTarget: "commonjs2",
+  },
+  devtool: false,
+  resolve: config.resolve,
+  module: config.module,
+  plugins: [
+    new webpack.BannerPlugin({ banner: "#!/usr/bin/env node", raw: true }),
+  ],
+};
+
+module.exports = [config, cliConfig];